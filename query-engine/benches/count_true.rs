@@ -0,0 +1,23 @@
+use arrow2::array::BooleanArray;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use query_engine::physical_plan::physical_expressions::count_true;
+
+fn count_true_benchmark(c: &mut Criterion) {
+    let array = (0..10_000_000)
+        .map(|i| if i % 7 == 0 { None } else { Some(i % 3 == 0) })
+        .collect::<BooleanArray>();
+    let mut group = c.benchmark_group("count true of 10M booleans");
+    group.bench_function("popcount", |b| b.iter(|| count_true(black_box(&array))));
+    group.bench_function("iterate", |b| {
+        b.iter(|| {
+            black_box(&array)
+                .iter()
+                .filter(|value| *value == Some(true))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, count_true_benchmark);
+criterion_main!(benches);
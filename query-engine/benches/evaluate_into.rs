@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Int32Array, MutablePrimitiveArray};
+use arrow2::chunk::Chunk;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use query_engine::physical_plan::physical_expressions::{
+    AddExpression, ColumnExpression, LiteralIntegerExpression, MulExpression, PhysicalExpression,
+};
+
+fn evaluate_into_benchmark(c: &mut Criterion) {
+    let batches = (0..100)
+        .map(|i| {
+            Chunk::new(vec![
+                Arc::new(Int32Array::from_vec((i..i + 8192).collect())) as Arc<dyn Array>,
+            ])
+        })
+        .collect::<Vec<_>>();
+    // #0 * 2 + 1
+    let expr = AddExpression::new(
+        Box::new(MulExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(LiteralIntegerExpression::new(2)),
+        )),
+        Box::new(LiteralIntegerExpression::new(1)),
+    );
+    let mut group = c.benchmark_group("col * 2 + 1 over 100 batches of 8192 rows");
+    group.bench_function("evaluate", |b| {
+        b.iter(|| {
+            for batch in &batches {
+                black_box(expr.evaluate(black_box(batch)).unwrap());
+            }
+        })
+    });
+    group.bench_function("evaluate_into", |b| {
+        let mut out = MutablePrimitiveArray::<i32>::with_capacity(8192);
+        b.iter(|| {
+            for batch in &batches {
+                expr.evaluate_into(black_box(batch), &mut out).unwrap();
+                black_box(&out);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, evaluate_into_benchmark);
+criterion_main!(benches);
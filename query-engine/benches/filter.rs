@@ -0,0 +1,29 @@
+use arrow2::array::{Array, BooleanArray, Int32Array};
+use arrow2::compute;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn filter_benchmark(c: &mut Criterion) {
+    let values = Int32Array::from_vec((0..1_000_000).collect());
+    let predicate =
+        BooleanArray::from_slice((0..1_000_000).map(|i| i % 2 == 0).collect::<Vec<_>>());
+    let mut group = c.benchmark_group("filter half of 1M Int32 rows");
+    group.bench_function("kernel", |b| {
+        b.iter(|| compute::filter::filter(black_box(&values), black_box(&predicate)).unwrap())
+    });
+    group.bench_function("row loop", |b| {
+        b.iter(|| {
+            let values = black_box(&values);
+            Int32Array::from_iter(
+                black_box(&predicate)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, keep)| *keep == Some(true))
+                    .map(|(i, _)| values.is_valid(i).then(|| values.value(i))),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, filter_benchmark);
+criterion_main!(benches);
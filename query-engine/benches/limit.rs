@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Int32Array, PrimitiveArray};
+use arrow2::chunk::Chunk;
+use arrow2::compute;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use query_engine::physical_plan::slice_chunk;
+
+fn limit_benchmark(c: &mut Criterion) {
+    let chunk = Chunk::new(
+        (0..4)
+            .map(|_| Arc::new(Int32Array::from_vec((0..1_000_000).collect())) as Arc<dyn Array>)
+            .collect(),
+    );
+    let mut group = c.benchmark_group("limit 100 of 1M rows");
+    group.bench_function("slice", |b| {
+        b.iter(|| slice_chunk(black_box(&chunk), 0, 100))
+    });
+    group.bench_function("take", |b| {
+        let indices = PrimitiveArray::<i32>::from_vec((0..100).collect());
+        b.iter(|| {
+            Chunk::new(
+                black_box(&chunk)
+                    .arrays()
+                    .iter()
+                    .map(|array| Arc::from(compute::take::take(&**array, &indices).unwrap()))
+                    .collect::<Vec<Arc<dyn Array>>>(),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, limit_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Float64Array, Int32Array, Utf8Array};
+use arrow2::compute;
+use arrow2::datatypes::DataType;
+use arrow2::scalar::{PrimitiveScalar, Scalar, Utf8Scalar};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use query_engine::columnar_value::scalar_to_array;
+
+type Kernel = fn(&dyn Array, &dyn Array) -> Box<dyn Array>;
+type ScalarKernel = fn(&dyn Array, &dyn Scalar) -> Box<dyn Array>;
+
+fn bench_kernel(
+    c: &mut Criterion,
+    name: &str,
+    array: impl Fn(usize) -> Arc<dyn Array>,
+    scalar: &dyn Scalar,
+    kernel: Kernel,
+    scalar_kernel: ScalarKernel,
+) {
+    let mut group = c.benchmark_group(name);
+    for length in [16, 256, 4096, 65536] {
+        let array = array(length);
+        group.bench_with_input(
+            BenchmarkId::new("scalar kernel", length),
+            &array,
+            |b, array| b.iter(|| scalar_kernel(black_box(&**array), scalar)),
+        );
+        group.bench_with_input(BenchmarkId::new("broadcast", length), &array, |b, array| {
+            b.iter(|| {
+                let scalar = scalar_to_array(scalar, array.len()).unwrap();
+                kernel(black_box(&**array), &*scalar)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn scalar_broadcast_benchmark(c: &mut Criterion) {
+    let int =
+        |length| Arc::new(Int32Array::from_vec((0..length as i32).collect())) as Arc<dyn Array>;
+    let float = |length| {
+        Arc::new(Float64Array::from_vec(
+            (0..length).map(|x| x as f64).collect(),
+        )) as Arc<dyn Array>
+    };
+    let string = |length| {
+        Arc::new(
+            (0..length)
+                .map(|x| Some(format!("value {}", x % 100)))
+                .collect::<Utf8Array<i32>>(),
+        ) as Arc<dyn Array>
+    };
+    bench_kernel(
+        c,
+        "Int32 add",
+        int,
+        &PrimitiveScalar::new(DataType::Int32, Some(7)),
+        |l, r| compute::arithmetics::add(l, r),
+        |l, r| compute::arithmetics::add_scalar(l, r),
+    );
+    bench_kernel(
+        c,
+        "Float64 mul",
+        float,
+        &PrimitiveScalar::new(DataType::Float64, Some(1.5)),
+        |l, r| compute::arithmetics::mul(l, r),
+        |l, r| compute::arithmetics::mul_scalar(l, r),
+    );
+    bench_kernel(
+        c,
+        "Int32 eq",
+        int,
+        &PrimitiveScalar::new(DataType::Int32, Some(7)),
+        |l, r| Box::new(compute::comparison::eq(l, r)),
+        |l, r| Box::new(compute::comparison::eq_scalar(l, r)),
+    );
+    bench_kernel(
+        c,
+        "Utf8 eq",
+        string,
+        &Utf8Scalar::<i32>::new(Some("value 7")),
+        |l, r| Box::new(compute::comparison::eq(l, r)),
+        |l, r| Box::new(compute::comparison::eq_scalar(l, r)),
+    );
+}
+
+criterion_group!(benches, scalar_broadcast_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Runs a filter over a Parquet scan and prints the `tracing` spans of its
+//! execution as they close.
+//!
+//! ```text
+//! cargo run --example tracing --features tracing
+//! ```
+
+use query_engine::{
+    dataframe::{DataFrame, DataFrameTrait},
+    prelude::*,
+};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/test.parquet");
+    let batches = DataFrame::parquet(path)
+        .filter(col("id").eq(lit(4)))
+        .execute()
+        .unwrap();
+    println!("{} batch(es)", batches.len());
+}
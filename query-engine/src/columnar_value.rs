@@ -1,7 +1,11 @@
 use arrow2::{
-    array::{BooleanArray, PrimitiveArray, Utf8Array},
-    datatypes::PhysicalType::{self},
+    array::{new_null_array, BooleanArray, PrimitiveArray, Utf8Array},
+    datatypes::{
+        DataType,
+        PhysicalType::{self},
+    },
     scalar::{BooleanScalar, PrimitiveScalar, Utf8Scalar},
+    types::NativeType,
 };
 use std::sync::Arc;
 
@@ -14,28 +18,65 @@ pub enum ColumnarValue {
     Scalar(Box<dyn Scalar>),
 }
 
+// A scalar has no length, so there's no meaningful `is_empty`.
+#[allow(clippy::len_without_is_empty)]
 impl ColumnarValue {
+    /// The number of rows of an array, `None` for a scalar.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            ColumnarValue::Array(array) => Some(array.len()),
+            ColumnarValue::Scalar(_) => None,
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        match self {
+            ColumnarValue::Array(array) => array.data_type().clone(),
+            ColumnarValue::Scalar(scalar) => scalar.data_type().clone(),
+        }
+    }
+
+    /// Like [`ColumnarValue::into_array`], but panics for scalars of a type
+    /// [`scalar_to_array`] doesn't support.
     pub fn to_array(self, len: usize) -> Arc<dyn Array> {
+        self.into_array(len).unwrap()
+    }
+
+    /// The value as an array, repeating a scalar `num_rows` times. Scalars of
+    /// a type [`scalar_to_array`] doesn't support are an
+    /// [`Error::ScalarToArrayError`].
+    pub fn into_array(self, num_rows: usize) -> Result<Arc<dyn Array>, Error> {
         match self {
-            ColumnarValue::Array(arr) => arr,
-            ColumnarValue::Scalar(scalar) => scalar_to_array(scalar, len).unwrap(),
+            ColumnarValue::Array(arr) => Ok(arr),
+            ColumnarValue::Scalar(scalar) => scalar_to_array(&*scalar, num_rows),
         }
     }
 }
 
-pub fn scalar_to_array(scalar: Box<dyn Scalar>, len: usize) -> Result<Arc<dyn Array>, Error> {
+fn repeat_primitive<T: NativeType>(scalar: &dyn Scalar, len: usize) -> Option<Arc<dyn Array>> {
+    scalar
+        .as_any()
+        .downcast_ref::<PrimitiveScalar<T>>()
+        .and_then(|x| x.value())
+        .map(|val| {
+            let array = PrimitiveArray::from_vec(vec![val; len]).to(scalar.data_type().clone());
+            Arc::new(array) as Arc<dyn Array>
+        })
+}
+
+/// Repeats `scalar` `len` times. A null scalar becomes a null array of its type.
+pub fn scalar_to_array(scalar: &dyn Scalar, len: usize) -> Result<Arc<dyn Array>, Error> {
+    if !scalar.is_valid() {
+        return Ok(Arc::from(new_null_array(scalar.data_type().clone(), len)));
+    }
     match scalar.data_type().to_physical_type() {
-        PhysicalType::Primitive(PrimitiveType::Int32) => scalar
-            .as_any()
-            .downcast_ref::<PrimitiveScalar<i32>>()
-            .and_then(|x| x.value())
-            .map(|val| Arc::new(PrimitiveArray::from_vec(vec![val; len])) as Arc<dyn Array>)
+        PhysicalType::Primitive(PrimitiveType::Int32) => repeat_primitive::<i32>(scalar, len)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
-        PhysicalType::Primitive(PrimitiveType::Float64) => scalar
-            .as_any()
-            .downcast_ref::<PrimitiveScalar<f64>>()
-            .and_then(|x| x.value())
-            .map(|val| Arc::new(PrimitiveArray::from_vec(vec![val; len])) as Arc<dyn Array>)
+        PhysicalType::Primitive(PrimitiveType::Int64) => repeat_primitive::<i64>(scalar, len)
+            .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
+        PhysicalType::Primitive(PrimitiveType::Float32) => repeat_primitive::<f32>(scalar, len)
+            .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
+        PhysicalType::Primitive(PrimitiveType::Float64) => repeat_primitive::<f64>(scalar, len)
             .ok_or(Error::ScalarToArrayError(format!("{:?}", scalar))),
         PhysicalType::Utf8 => scalar
             .as_any()
@@ -57,3 +98,60 @@ pub fn scalar_to_array(scalar: Box<dyn Scalar>, len: usize) -> Result<Arc<dyn Ar
         _ => Err(Error::ScalarToArrayError(format!("{:?}", scalar))),
     }
 }
+
+/// Reads the values of `scalars`, null scalars of any type become `None`.
+fn scalar_values<'a, S: Scalar + 'static, T>(
+    scalars: &'a [Box<dyn Scalar>],
+    value: impl Fn(&'a S) -> Option<T>,
+) -> Result<Vec<Option<T>>, Error> {
+    scalars
+        .iter()
+        .map(|scalar| match scalar.data_type() {
+            DataType::Null => Ok(None),
+            _ => scalar
+                .as_any()
+                .downcast_ref::<S>()
+                .map(&value)
+                .ok_or(Error::DowncastError),
+        })
+        .collect()
+}
+
+fn primitive_array<T: NativeType>(
+    scalars: &[Box<dyn Scalar>],
+    data_type: DataType,
+) -> Result<Arc<dyn Array>, Error> {
+    let values = scalar_values(scalars, |x: &PrimitiveScalar<T>| x.value())?;
+    Ok(Arc::new(PrimitiveArray::<T>::from(values).to(data_type)))
+}
+
+/// Builds an array with one slot per scalar. All non-null scalars must share
+/// a data type; null scalars become null slots.
+pub fn scalars_to_array(scalars: &[Box<dyn Scalar>]) -> Result<Arc<dyn Array>, Error> {
+    let data_type = scalars
+        .iter()
+        .map(|scalar| scalar.data_type())
+        .find(|data_type| **data_type != DataType::Null)
+        .cloned()
+        .unwrap_or(DataType::Null);
+    match data_type.to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => primitive_array::<i32>(scalars, data_type),
+        PhysicalType::Primitive(PrimitiveType::Int64) => primitive_array::<i64>(scalars, data_type),
+        PhysicalType::Primitive(PrimitiveType::Float32) => {
+            primitive_array::<f32>(scalars, data_type)
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            primitive_array::<f64>(scalars, data_type)
+        }
+        PhysicalType::Utf8 => {
+            let values = scalar_values(scalars, |x: &Utf8Scalar<i32>| x.value())?;
+            Ok(Arc::new(Utf8Array::<i32>::from(values)))
+        }
+        PhysicalType::Boolean => {
+            let values = scalar_values(scalars, |x: &BooleanScalar| x.value())?;
+            Ok(Arc::new(BooleanArray::from(values)))
+        }
+        PhysicalType::Null => Ok(Arc::from(new_null_array(data_type, scalars.len()))),
+        t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    }
+}
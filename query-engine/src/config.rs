@@ -0,0 +1,38 @@
+//! Process-wide execution settings.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static SCALAR_BROADCAST_THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+
+/// Arrays shorter than this are combined with a scalar by materializing the
+/// scalar into an array and using the array-array kernel, longer ones use
+/// arrow2's `*_scalar` kernels. Binary expressions read it when they are
+/// built, and `with_broadcast_threshold` overrides it for one expression.
+///
+/// Defaults to 0, so the scalar kernels are always used: in the
+/// `scalar_broadcast` benchmark they win for Int32, Float64 and Utf8 at every
+/// length from 16 to 65536 rows.
+pub fn scalar_broadcast_threshold() -> usize {
+    SCALAR_BROADCAST_THRESHOLD.load(Ordering::Relaxed)
+}
+
+pub fn set_scalar_broadcast_threshold(threshold: usize) {
+    SCALAR_BROADCAST_THRESHOLD.store(threshold, Ordering::Relaxed)
+}
+
+static MAX_EXPRESSION_DEPTH: AtomicUsize = AtomicUsize::new(1000);
+
+/// Logical expressions nested deeper than this are rejected with
+/// `Error::ExpressionTooDeep` when their plan is optimized or planned, before
+/// the recursive optimizer, planner and evaluation code can overflow the
+/// stack.
+///
+/// Defaults to 1000, which release builds plan comfortably within a 2 MiB
+/// thread stack; unoptimized builds need a lower limit or a larger stack.
+pub fn max_expression_depth() -> usize {
+    MAX_EXPRESSION_DEPTH.load(Ordering::Relaxed)
+}
+
+pub fn set_max_expression_depth(depth: usize) {
+    MAX_EXPRESSION_DEPTH.store(depth, Ordering::Relaxed)
+}
@@ -1,22 +1,84 @@
+use std::collections::HashSet;
 use std::fs::File;
+use std::sync::Arc;
 
 use crate::error::Error;
-use arrow2::datatypes::Schema;
+use crate::record_batch::RecordBatch;
+use crate::schema::SchemaExt;
+use arrow2::array::Array;
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::error::ArrowError;
+use arrow2::io::csv::read::{
+    deserialize_batch, deserialize_column, read_rows, ByteRecord, Reader, ReaderBuilder,
+};
 use arrow2::io::parquet::read::{infer_schema, read_metadata, FileMetaData, FileReader};
 
+/// The batches read from a data source. Sources yield them lazily, so a scan
+/// never holds more than one batch of a file in memory.
+pub type ScanBatches = Box<dyn Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>>;
+
+/// A source of batches. Every source returns projected columns in the order
+/// of its schema and each column once, however the projection orders or
+/// repeats them.
 pub enum DataSource {
     Parquet(ParquetDataSource),
+    Csv(CsvDataSource),
+    Memory(MemoryDataSource),
 }
 
 impl DataSource {
     pub fn schema(&self) -> Schema {
         match self {
             DataSource::Parquet(ds) => ds.schema(),
+            DataSource::Csv(ds) => ds.schema(),
+            DataSource::Memory(ds) => ds.schema(),
         }
     }
-    pub fn scan(self, projection: Option<Vec<String>>) -> FileReader<File> {
+    /// The schema of a scan with `projection`. Naming a column the schema
+    /// doesn't have is an [`Error::NoFieldInSchema`].
+    pub fn projected_schema(&self, projection: Option<&[String]>) -> Result<Schema, Error> {
+        let schema = self.schema();
+        match projection {
+            Some(projection) => {
+                let indices = projection_indices(&schema, projection)?;
+                Ok(Schema::from(
+                    indices
+                        .into_iter()
+                        .map(|i| schema.fields[i].clone())
+                        .collect::<Vec<_>>(),
+                ))
+            }
+            None => Ok(schema),
+        }
+    }
+    /// Reads the projected columns. Naming a column the schema doesn't have
+    /// is an [`Error::NoFieldInSchema`].
+    pub fn scan(self, projection: Option<Vec<String>>) -> Result<ScanBatches, Error> {
         match self {
-            DataSource::Parquet(ds) => ds.scan(projection),
+            DataSource::Parquet(ds) => Ok(Box::new(ds.scan(projection)?)),
+            DataSource::Csv(ds) => ds.scan(projection),
+            DataSource::Memory(ds) => ds.scan(projection),
+        }
+    }
+    /// Like [`DataSource::scan`], but selects columns by their position in the
+    /// schema. An index past the last field is an
+    /// [`Error::ExceedingBoundsError`], and indices that aren't strictly
+    /// increasing are an [`Error::InvalidArgument`], since the columns come in
+    /// schema order.
+    pub fn scan_indices(self, projection: Vec<usize>) -> Result<ScanBatches, Error> {
+        match self {
+            DataSource::Parquet(ds) => Ok(Box::new(ds.scan_indices(projection)?)),
+            DataSource::Csv(ds) => ds.scan_indices(projection),
+            DataSource::Memory(ds) => ds.scan_indices(projection),
+        }
+    }
+    /// Utf8 columns whose empty strings are read as nulls.
+    pub fn empty_as_null(&self) -> &[String] {
+        match self {
+            DataSource::Parquet(ds) => &ds.empty_as_null,
+            DataSource::Csv(ds) => &ds.empty_as_null,
+            DataSource::Memory(_) => &[],
         }
     }
 }
@@ -24,49 +86,282 @@ impl DataSource {
 pub struct ParquetDataSource {
     file: File,
     metadata: FileMetaData,
+    empty_as_null: Vec<String>,
 }
 
 impl ParquetDataSource {
     pub fn new(path: &str) -> Result<Self, Error> {
         match File::open(path) {
             Ok(mut file) => {
-                let metadata = read_metadata(&mut file).map_err(|err| Error::ArrowError(err))?;
+                let metadata = read_metadata(&mut file).map_err(Error::ArrowError)?;
                 Ok(ParquetDataSource {
-                    file: file,
-                    metadata: metadata,
+                    file,
+                    metadata,
+                    empty_as_null: vec![],
                 })
             }
             Err(err) => Err(Error::IoError(err)),
         }
     }
+
+    /// Reads empty strings in the given Utf8 columns as nulls.
+    pub fn with_empty_as_null(mut self, columns: Vec<String>) -> Self {
+        self.empty_as_null = columns;
+        self
+    }
 }
 
 impl ParquetDataSource {
     fn schema(&self) -> Schema {
         infer_schema(&self.metadata).unwrap()
     }
-    pub fn scan(self, projection: Option<Vec<String>>) -> FileReader<File> {
-        let projection: Option<Vec<usize>> = projection.map(|projection| {
-            self.schema()
-                .fields
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, x)| {
-                    if projection.contains(&x.name) {
-                        Some(i)
-                    } else {
-                        None
-                    }
-                })
-                .collect()
-        });
-        FileReader::try_new(
-            self.file,
-            projection.as_ref().map(|x| x.as_slice()),
-            None,
-            None,
-            None,
-        )
-        .unwrap()
+    pub fn scan(self, projection: Option<Vec<String>>) -> Result<FileReader<File>, Error> {
+        let projection = match projection {
+            Some(projection) => projection_indices(&self.schema(), &projection)?,
+            None => (0..self.schema().fields.len()).collect(),
+        };
+        self.scan_indices(projection)
+    }
+    /// Reads the columns at the given positions, which must be in the order
+    /// of the file, see [`DataSource::scan_indices`].
+    pub fn scan_indices(self, projection: Vec<usize>) -> Result<FileReader<File>, Error> {
+        let projection = check_indices(projection, self.schema().fields.len())?;
+        FileReader::try_new(self.file, Some(&projection), None, None, None)
+            .map_err(Error::ArrowError)
+    }
+}
+
+/// Checks the positions of a projection, erroring with the first one that is
+/// not below `len`, or if they are out of order or repeat.
+fn check_indices(projection: Vec<usize>, len: usize) -> Result<Vec<usize>, Error> {
+    if let Some(&i) = projection.iter().find(|&&i| i >= len) {
+        return Err(Error::ExceedingBoundsError(i));
+    }
+    if projection.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(Error::InvalidArgument(format!(
+            "projection indices must be strictly increasing, got {:?}",
+            projection
+        )));
+    }
+    Ok(projection)
+}
+
+/// The sorted and deduped positions of the named columns.
+fn projection_indices(schema: &Schema, projection: &[String]) -> Result<Vec<usize>, Error> {
+    let mut projection = projection
+        .iter()
+        .map(|name| {
+            schema
+                .index_of(name)
+                .ok_or_else(|| Error::NoFieldInSchema(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    projection.sort_unstable();
+    projection.dedup();
+    Ok(projection)
+}
+
+/// A CSV file with a header row. Column types are inferred from the first
+/// rows as Boolean, Int32, Float64 or Utf8, and empty cells are read as
+/// nulls. [`CsvDataSource::with_empty_as_null`] narrows that down for Utf8
+/// columns, whose other empty cells stay empty strings.
+pub struct CsvDataSource {
+    path: String,
+    schema: Schema,
+    batch_size: usize,
+    empty_as_null: Vec<String>,
+}
+
+/// How many rows schema inference looks at.
+const CSV_INFER_ROWS: usize = 1000;
+
+const CSV_BATCH_SIZE: usize = 1024;
+
+impl CsvDataSource {
+    pub fn new(path: &str) -> Result<Self, Error> {
+        let mut reader = csv_reader(path)?;
+        let schema = infer_csv_schema(&mut reader)?;
+        let empty_as_null = schema
+            .fields
+            .iter()
+            .filter(|field| field.data_type == DataType::Utf8)
+            .map(|field| field.name.clone())
+            .collect();
+        Ok(CsvDataSource {
+            path: path.to_string(),
+            schema,
+            batch_size: CSV_BATCH_SIZE,
+            empty_as_null,
+        })
+    }
+
+    /// Reads empty strings as nulls only in the given Utf8 columns, instead
+    /// of all of them.
+    pub fn with_empty_as_null(mut self, columns: Vec<String>) -> Self {
+        self.empty_as_null = columns;
+        self
+    }
+
+    /// Reads `batch_size` rows per batch instead of 1024.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+impl CsvDataSource {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+    /// Reads the projected columns, which keep their order in the file. Naming
+    /// a column the file doesn't have is an error.
+    pub fn scan(self, projection: Option<Vec<String>>) -> Result<ScanBatches, Error> {
+        let projection = match projection {
+            Some(projection) => projection_indices(&self.schema, &projection)?,
+            None => (0..self.schema.fields.len()).collect(),
+        };
+        self.scan_indices(projection)
+    }
+    /// Reads the columns at the given positions, which must be in the order
+    /// of the file, see [`DataSource::scan_indices`].
+    pub fn scan_indices(self, projection: Vec<usize>) -> Result<ScanBatches, Error> {
+        let projection = check_indices(projection, self.schema.fields.len())?;
+        Ok(Box::new(CsvBatches {
+            reader: csv_reader(&self.path)?,
+            fields: self.schema.fields,
+            projection,
+            rows: vec![ByteRecord::new(); self.batch_size],
+            line: 0,
+        }))
+    }
+}
+
+fn csv_reader(path: &str) -> Result<Reader<File>, Error> {
+    ReaderBuilder::new()
+        .from_path(path)
+        .map_err(|err| Error::ArrowError(ArrowError::from(err)))
+}
+
+fn infer_csv_type(cell: &[u8]) -> DataType {
+    let cell = String::from_utf8_lossy(cell);
+    if cell.eq_ignore_ascii_case("true") || cell.eq_ignore_ascii_case("false") {
+        DataType::Boolean
+    } else if cell.parse::<i32>().is_ok() {
+        DataType::Int32
+    } else if cell.parse::<f64>().is_ok() {
+        DataType::Float64
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Infers each column's type from its non-empty cells in the first rows. Int32
+/// and Float64 cells make a Float64 column, any other mix a Utf8 one.
+fn infer_csv_schema(reader: &mut Reader<File>) -> Result<Schema, Error> {
+    let to_error = |err| Error::ArrowError(ArrowError::from(err));
+    let headers = reader
+        .headers()
+        .map_err(to_error)?
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    let mut types = vec![HashSet::new(); headers.len()];
+    let mut record = ByteRecord::new();
+    for _ in 0..CSV_INFER_ROWS {
+        if !reader.read_byte_record(&mut record).map_err(to_error)? {
+            break;
+        }
+        for (types, cell) in types.iter_mut().zip(record.iter()) {
+            if !cell.is_empty() {
+                types.insert(infer_csv_type(cell));
+            }
+        }
+    }
+    let fields = headers
+        .into_iter()
+        .zip(types)
+        .map(|(name, types)| {
+            let data_type = match types.len() {
+                1 => types.into_iter().next().unwrap(),
+                2 if types.contains(&DataType::Int32) && types.contains(&DataType::Float64) => {
+                    DataType::Float64
+                }
+                _ => DataType::Utf8,
+            };
+            Field::new(&name, data_type, true)
+        })
+        .collect::<Vec<_>>();
+    Ok(Schema::from(fields))
+}
+
+struct CsvBatches {
+    reader: Reader<File>,
+    fields: Vec<Field>,
+    projection: Vec<usize>,
+    rows: Vec<ByteRecord>,
+    line: usize,
+}
+
+impl Iterator for CsvBatches {
+    type Item = Result<Chunk<Arc<dyn Array>>, ArrowError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let read = match read_rows(&mut self.reader, 0, &mut self.rows) {
+            Ok(0) => return None,
+            Ok(read) => read,
+            Err(err) => return Some(Err(err)),
+        };
+        let batch = deserialize_batch(
+            &self.rows[..read],
+            &self.fields,
+            Some(&self.projection),
+            self.line,
+            deserialize_column,
+        );
+        self.line += read;
+        Some(batch)
+    }
+}
+
+/// Batches already in memory, for tests and small data.
+pub struct MemoryDataSource {
+    schema: Schema,
+    batches: Vec<RecordBatch>,
+}
+
+impl MemoryDataSource {
+    /// Errors if a batch's schema differs from `schema`.
+    pub fn try_new(schema: Schema, batches: Vec<RecordBatch>) -> Result<Self, Error> {
+        if let Some(batch) = batches.iter().find(|batch| batch.schema() != &schema) {
+            return Err(Error::InvalidArgument(format!(
+                "batch schema {:?} differs from {:?}",
+                batch.schema(),
+                schema
+            )));
+        }
+        Ok(MemoryDataSource { schema, batches })
+    }
+}
+
+impl MemoryDataSource {
+    fn schema(&self) -> Schema {
+        self.schema.clone()
+    }
+    /// Returns the projected columns in the order of the schema. Naming a
+    /// column the schema doesn't have is an error.
+    pub fn scan(self, projection: Option<Vec<String>>) -> Result<ScanBatches, Error> {
+        let projection = match projection {
+            Some(projection) => projection_indices(&self.schema, &projection)?,
+            None => (0..self.schema.fields.len()).collect(),
+        };
+        self.scan_indices(projection)
+    }
+    /// Returns the columns at the given positions, which must be in the order
+    /// of the schema, see [`DataSource::scan_indices`].
+    pub fn scan_indices(self, projection: Vec<usize>) -> Result<ScanBatches, Error> {
+        let projection = check_indices(projection, self.schema.fields.len())?;
+        Ok(Box::new(self.batches.into_iter().map(move |batch| {
+            let columns = batch.into_chunk().into_arrays();
+            Chunk::try_new(projection.iter().map(|&i| columns[i].clone()).collect())
+        })))
     }
 }
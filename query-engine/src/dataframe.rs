@@ -3,7 +3,7 @@ use std::sync::Arc;
 use arrow2::{array::Array, chunk::Chunk, datatypes::Schema};
 
 use crate::{
-    data_source::{DataSource, ParquetDataSource},
+    data_source::{CsvDataSource, DataSource, ParquetDataSource},
     error::Error,
     logical_plan::{
         logical_expression::LogicalExpression, Aggregate, LogicalPlan, Projection, Scan, Selection,
@@ -32,21 +32,25 @@ pub struct DataFrame {
 
 impl DataFrame {
     fn new(plan: LogicalPlan) -> Self {
-        DataFrame { plan: plan }
+        DataFrame { plan }
     }
 
     pub fn parquet(path: &str) -> Self {
         let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
-        Self::new(LogicalPlan::Scan(Scan::new(path, ds, None)))
+        Self::new(LogicalPlan::Scan(Scan::new(path, ds, None).unwrap()))
+    }
+
+    pub fn csv(path: &str) -> Self {
+        let ds = DataSource::Csv(CsvDataSource::new(path).unwrap());
+        Self::new(LogicalPlan::Scan(Scan::new(path, ds, None).unwrap()))
     }
 }
 
 impl DataFrameTrait for DataFrame {
     fn project(self, exprs: Vec<LogicalExpression>) -> Self {
-        Self::new(LogicalPlan::Projection(Projection::new(
-            self.logical_plan(),
-            exprs,
-        )))
+        Self::new(LogicalPlan::Projection(
+            Projection::new(self.logical_plan(), exprs).unwrap(),
+        ))
     }
 
     fn filter(self, exprs: LogicalExpression) -> Self {
@@ -61,11 +65,9 @@ impl DataFrameTrait for DataFrame {
         group_by: Vec<LogicalExpression>,
         aggregate_expr: Vec<LogicalExpression>,
     ) -> Self {
-        Self::new(LogicalPlan::Aggregate(Aggregate::new(
-            self.logical_plan(),
-            group_by,
-            aggregate_expr,
-        )))
+        Self::new(LogicalPlan::Aggregate(
+            Aggregate::new(self.logical_plan(), group_by, aggregate_expr).unwrap(),
+        ))
     }
 
     fn schema(&self) -> &Schema {
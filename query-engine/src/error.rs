@@ -1,11 +1,19 @@
 use thiserror::Error;
 
+use crate::operator::Operator;
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("The index `{0}` is out of bounds.")]
     ExceedingBoundsError(usize),
+    #[error("The column index `{0}` is out of range.")]
+    ColumnIndexError(usize),
+    #[error("Columns of type `{0:?}` are not supported.")]
+    UnsupportedColumnType(arrow2::datatypes::DataType),
     #[error("Field `{0}` is not contained in logical plan.")]
     NoFieldInLogicalPlan(String),
+    #[error("Field `{0}` is not contained in the schema.")]
+    NoFieldInSchema(String),
     #[error("The expressions `{0}` and `{1}` have different sizes.")]
     DifferentSizes(String, String),
     #[error("Physical expression `{0}` is not supported.")]
@@ -16,6 +24,24 @@ pub enum Error {
     PhysicalTypeNotSuported(String),
     #[error("Primitive type `{0}` is not supported.")]
     PrimitiveTypeNotSuported(String),
+    #[error("Column `{0}` matches more than one field.")]
+    AmbiguousColumn(String),
+    #[error("Field `{0}` appears in both schemas.")]
+    DuplicateField(String),
+    #[error("Field `{0}` has type `{1}` in one schema and `{2}` in the other.")]
+    ConflictingFieldTypes(String, String, String),
+    #[error("Types `{0}` and `{1}` are incompatible for operator `{2}`.")]
+    IncompatibleTypes(String, String, Operator),
+    #[error("Arithmetic overflow.")]
+    ArithmeticOverflow,
+    #[error("Division by zero.")]
+    DivisionByZero,
+    #[error("Invalid argument: {0}.")]
+    InvalidArgument(String),
+    #[error("The expression is nested deeper than the limit of `{0}`.")]
+    ExpressionTooDeep(usize),
+    #[error("The schema has `{0}` fields but the batch has `{1}` columns.")]
+    SchemaColumnMismatch(usize, usize),
     #[error("Logical plan `{0}` is missing children.")]
     MissingChildren(String),
     #[error("Physical plan `{0}` is missing its input.")]
@@ -28,8 +54,16 @@ pub enum Error {
     ScalarToArrayError(String),
     #[error("Expr doesn't evaluate to a boolean array, which is needed to filter.")]
     NoBooleanArrayForFilter,
+    #[error("Couldn't parse SQL: {0}.")]
+    SqlParseError(String),
+    #[error("SQL `{0}` is not supported.")]
+    UnsupportedSql(String),
+    #[error("Table `{0}` is not registered.")]
+    UnknownTable(String),
     #[error("IoError: `{0}`.")]
-    IoError(std::io::Error),
-    #[error("IoError: `{0}`.")]
-    ArrowError(arrow2::error::ArrowError),
+    IoError(#[source] std::io::Error),
+    #[error("ArrowError: `{0}`.")]
+    ArrowError(#[source] arrow2::error::ArrowError),
+    #[error("RegexError: `{0}`.")]
+    RegexError(#[source] regex::Error),
 }
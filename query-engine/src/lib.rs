@@ -1,11 +1,20 @@
 pub mod columnar_value;
+pub mod config;
 pub mod data_source;
 pub mod dataframe;
 pub mod error;
 pub mod logical_plan;
+pub mod operator;
 pub mod physical_plan;
 pub mod prelude;
 pub mod query_planner;
+pub mod record_batch;
+pub mod schema;
+#[cfg(feature = "sql")]
+pub mod sql;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod type_coercion;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,69 @@
+use crate::data_source::DataSource;
+use crate::error::Error;
+
+use super::{
+    logical_expression::LogicalExpression, Aggregate, Limit, LogicalPlan, OrderBy, Projection,
+    Scan, Selection, Sort,
+};
+
+/// Builds a [`LogicalPlan`] bottom up, starting from a scan:
+///
+/// ```ignore
+/// let plan = LogicalPlanBuilder::scan(path, data_source)
+///     .filter(col("id").gt(lit(4)))
+///     .project(vec![col("id")])?
+///     .build();
+/// ```
+pub struct LogicalPlanBuilder {
+    plan: LogicalPlan,
+}
+
+impl LogicalPlanBuilder {
+    pub fn scan(path: &str, data_source: DataSource) -> Self {
+        Self::from(LogicalPlan::Scan(
+            Scan::new(path, data_source, None).expect("scanning every column"),
+        ))
+    }
+
+    /// Fails when an expression reads a column the plan doesn't have.
+    pub fn project(self, exprs: Vec<LogicalExpression>) -> Result<Self, Error> {
+        Ok(Self::from(LogicalPlan::Projection(Projection::new(
+            self.plan, exprs,
+        )?)))
+    }
+
+    pub fn filter(self, expr: LogicalExpression) -> Self {
+        Self::from(LogicalPlan::Selection(Selection::new(self.plan, expr)))
+    }
+
+    /// Fails when an expression reads a column the plan doesn't have.
+    pub fn aggregate(
+        self,
+        group_by: Vec<LogicalExpression>,
+        aggregate_expr: Vec<LogicalExpression>,
+    ) -> Result<Self, Error> {
+        Ok(Self::from(LogicalPlan::Aggregate(Aggregate::new(
+            self.plan,
+            group_by,
+            aggregate_expr,
+        )?)))
+    }
+
+    pub fn sort(self, order_by: Vec<OrderBy>) -> Self {
+        Self::from(LogicalPlan::Sort(Sort::new(self.plan, order_by)))
+    }
+
+    pub fn limit(self, skip: usize, fetch: usize) -> Self {
+        Self::from(LogicalPlan::Limit(Limit::new(self.plan, skip, fetch)))
+    }
+
+    pub fn build(self) -> LogicalPlan {
+        self.plan
+    }
+}
+
+impl From<LogicalPlan> for LogicalPlanBuilder {
+    fn from(plan: LogicalPlan) -> Self {
+        LogicalPlanBuilder { plan }
+    }
+}
@@ -6,6 +6,7 @@ use arrow2::{
 };
 
 use crate::error::Error;
+use crate::schema::SchemaExt;
 
 use super::LogicalPlan;
 
@@ -65,6 +66,48 @@ impl LogicalExpression {
     }
 }
 
+impl LogicalExpression {
+    pub(crate) fn children(&self) -> Vec<&LogicalExpression> {
+        match self {
+            LogicalExpression::Column(_)
+            | LogicalExpression::LiteralBool(_)
+            | LogicalExpression::LiteralString(_)
+            | LogicalExpression::LiteralInteger(_)
+            | LogicalExpression::LiteralFloat(_) => vec![],
+            LogicalExpression::Eq(eq) => vec![&eq.left, &eq.right],
+            LogicalExpression::Neq(neq) => vec![&neq.left, &neq.right],
+            LogicalExpression::Gt(gt) => vec![&gt.left, &gt.right],
+            LogicalExpression::GtEq(gteq) => vec![&gteq.left, &gteq.right],
+            LogicalExpression::Lt(lt) => vec![&lt.left, &lt.right],
+            LogicalExpression::LtEq(lteq) => vec![&lteq.left, &lteq.right],
+            LogicalExpression::And(and) => vec![&and.left, &and.right],
+            LogicalExpression::Or(or) => vec![&or.left, &or.right],
+            LogicalExpression::Add(add) => vec![&add.left, &add.right],
+            LogicalExpression::Sub(sub) => vec![&sub.left, &sub.right],
+            LogicalExpression::Mul(mul) => vec![&mul.left, &mul.right],
+            LogicalExpression::Div(div) => vec![&div.left, &div.right],
+            LogicalExpression::Mod(modu) => vec![&modu.left, &modu.right],
+            LogicalExpression::Sum(sum) => vec![&sum.expr],
+            LogicalExpression::Avg(avg) => vec![&avg.expr],
+            LogicalExpression::Max(max) => vec![&max.expr],
+            LogicalExpression::Min(min) => vec![&min.expr],
+            LogicalExpression::Count(count) => vec![&count.expr],
+        }
+    }
+
+    /// Nesting depth of the expression tree, computed without recursion so it
+    /// is safe to call on arbitrarily deep trees.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((expr, level)) = stack.pop() {
+            depth = depth.max(level);
+            stack.extend(expr.children().into_iter().map(|x| (x, level + 1)));
+        }
+        depth
+    }
+}
+
 impl Display for LogicalExpression {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -102,7 +145,7 @@ pub struct Column {
 
 impl Column {
     pub fn new(name: String) -> Self {
-        Column { name: name }
+        Column { name }
     }
 }
 
@@ -111,11 +154,8 @@ impl Column {
     fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
         input
             .schema()?
-            .fields
-            .iter()
-            .filter(|x| x.name == self.name)
-            .next()
-            .map(|x| x.clone())
+            .field_by_name(&self.name)
+            .cloned()
             .ok_or(Error::NoFieldInLogicalPlan(self.name.clone()))
     }
 }
@@ -134,7 +174,7 @@ pub struct LiteralBool {
 
 impl LiteralBool {
     pub fn new(value: bool) -> Self {
-        LiteralBool { value: value }
+        LiteralBool { value }
     }
 }
 
@@ -161,7 +201,7 @@ pub struct LiteralString {
 
 impl LiteralString {
     pub fn new(value: String) -> Self {
-        LiteralString { value: value }
+        LiteralString { value }
     }
 }
 
@@ -189,7 +229,7 @@ pub struct LiteralInteger {
 
 impl LiteralInteger {
     pub fn new(value: i32) -> Self {
-        LiteralInteger { value: value }
+        LiteralInteger { value }
     }
 }
 
@@ -217,7 +257,7 @@ pub struct LiteralFloat {
 
 impl LiteralFloat {
     pub fn new(value: f64) -> Self {
-        LiteralFloat { value: value }
+        LiteralFloat { value }
     }
 }
 
@@ -255,8 +295,8 @@ macro_rules! booleanBinaryExpression {
                 $i {
                     name: $name,
                     op: $op,
-                    left: left,
-                    right: right,
+                    left,
+                    right,
                 }
             }
         }
@@ -309,8 +349,8 @@ macro_rules! mathExpression {
                 $i {
                     name: $name,
                     op: $op,
-                    left: left,
-                    right: right,
+                    left,
+                    right,
                 }
             }
         }
@@ -346,7 +386,7 @@ mathExpression!(Mod, "mod".to_string(), "%".to_string());
 pub trait LogicalAggregateExpression {}
 
 macro_rules! aggregateExpression {
-    ($i: ident, $name: expr) => {
+    ($i: ident, $name: expr, $data_type: expr) => {
         pub struct $i {
             name: String,
             pub(crate) expr: LogicalExpression,
@@ -354,10 +394,7 @@ macro_rules! aggregateExpression {
 
         impl $i {
             pub fn new(expr: LogicalExpression) -> Self {
-                $i {
-                    name: $name,
-                    expr: expr,
-                }
+                $i { name: $name, expr }
             }
         }
 
@@ -366,7 +403,7 @@ macro_rules! aggregateExpression {
             fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
                 Ok(Field {
                     name: self.name.clone(),
-                    data_type: self.expr.to_field(input)?.data_type,
+                    data_type: ($data_type)(self.expr.to_field(input)?.data_type),
                     is_nullable: false,
                     metadata: Metadata::default(),
                 })
@@ -383,10 +420,18 @@ macro_rules! aggregateExpression {
     };
 }
 
-aggregateExpression!(Sum, "sum".to_string());
-aggregateExpression!(Avg, "avg".to_string());
-aggregateExpression!(Max, "max".to_string());
-aggregateExpression!(Min, "min".to_string());
+/// SUM adds up integers as Int64, so that it doesn't overflow as easily, and
+/// counts the true values of booleans.
+fn sum_type(data_type: datatypes::DataType) -> datatypes::DataType {
+    match data_type {
+        datatypes::DataType::Int32 | datatypes::DataType::Boolean => datatypes::DataType::Int64,
+        data_type => data_type,
+    }
+}
+
+aggregateExpression!(Sum, "sum".to_string(), sum_type);
+aggregateExpression!(Max, "max".to_string(), |data_type| data_type);
+aggregateExpression!(Min, "min".to_string(), |data_type| data_type);
 
 // Count Expression
 
@@ -399,7 +444,7 @@ impl Count {
     pub fn new(expr: LogicalExpression) -> Self {
         Count {
             name: "count".to_string(),
-            expr: expr,
+            expr,
         }
     }
 }
@@ -407,9 +452,11 @@ impl Count {
 impl Count {
     #[inline]
     fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        // Checks that the counted expression resolves.
+        self.expr.to_field(input)?;
         Ok(Field {
             name: self.name.clone(),
-            data_type: self.expr.to_field(input)?.data_type,
+            data_type: datatypes::DataType::Int64,
             is_nullable: false,
             metadata: Metadata::default(),
         })
@@ -422,6 +469,44 @@ impl fmt::Display for Count {
     }
 }
 
+// Avg Expression
+
+pub struct Avg {
+    name: String,
+    pub(crate) expr: LogicalExpression,
+}
+
+impl Avg {
+    pub fn new(expr: LogicalExpression) -> Self {
+        Avg {
+            name: "avg".to_string(),
+            expr,
+        }
+    }
+}
+
+impl Avg {
+    #[inline]
+    fn to_field(&self, input: &LogicalPlan) -> Result<Field, Error> {
+        // Checks that the averaged expression resolves.
+        self.expr.to_field(input)?;
+        Ok(Field {
+            name: self.name.clone(),
+            data_type: datatypes::DataType::Float64,
+            is_nullable: true,
+            metadata: Metadata::default(),
+        })
+    }
+}
+
+impl LogicalAggregateExpression for Avg {}
+
+impl fmt::Display for Avg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.expr)
+    }
+}
+
 pub trait LogicalExpressionMethods {
     fn eq(self, other: LogicalExpression) -> LogicalExpression
     where
@@ -470,3 +555,38 @@ impl LogicalExpressionMethods for LogicalExpression {
     booleanMethod!(and, And);
     booleanMethod!(or, Or);
 }
+
+/// Rust values that map to a literal expression of the matching type.
+pub trait Literal {
+    fn lit(self) -> LogicalExpression;
+}
+
+impl Literal for i32 {
+    fn lit(self) -> LogicalExpression {
+        LogicalExpression::LiteralInteger(LiteralInteger::new(self))
+    }
+}
+
+impl Literal for f64 {
+    fn lit(self) -> LogicalExpression {
+        LogicalExpression::LiteralFloat(LiteralFloat::new(self))
+    }
+}
+
+impl Literal for bool {
+    fn lit(self) -> LogicalExpression {
+        LogicalExpression::LiteralBool(LiteralBool::new(self))
+    }
+}
+
+impl Literal for &str {
+    fn lit(self) -> LogicalExpression {
+        LogicalExpression::LiteralString(LiteralString::new(self.to_string()))
+    }
+}
+
+impl Literal for String {
+    fn lit(self) -> LogicalExpression {
+        LogicalExpression::LiteralString(LiteralString::new(self))
+    }
+}
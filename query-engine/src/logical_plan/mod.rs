@@ -1,10 +1,13 @@
+use crate::config::max_expression_depth;
 use crate::data_source::DataSource;
 use crate::error::Error;
+use arrow2::compute::sort::SortOptions;
 use arrow2::datatypes::{Field, Schema};
 use std::fmt;
 
-use self::logical_expression::LogicalExpression;
+use self::logical_expression::{LogicalExpression, LogicalExpressionMethods};
 
+pub mod builder;
 pub mod logical_expression;
 pub mod optimizer;
 
@@ -13,6 +16,8 @@ pub enum LogicalPlan {
     Projection(Projection),
     Selection(Selection),
     Aggregate(Aggregate),
+    Sort(Sort),
+    Limit(Limit),
 }
 
 impl LogicalPlan {
@@ -22,6 +27,8 @@ impl LogicalPlan {
             LogicalPlan::Projection(proj) => proj.schema(),
             LogicalPlan::Selection(sel) => sel.schema(),
             LogicalPlan::Aggregate(agg) => agg.schema(),
+            LogicalPlan::Sort(sort) => sort.schema(),
+            LogicalPlan::Limit(limit) => limit.schema(),
         }
     }
     fn children(&self) -> Option<&[LogicalPlan]> {
@@ -30,8 +37,42 @@ impl LogicalPlan {
             LogicalPlan::Projection(proj) => proj.children(),
             LogicalPlan::Selection(sel) => sel.children(),
             LogicalPlan::Aggregate(agg) => agg.children(),
+            LogicalPlan::Sort(sort) => sort.children(),
+            LogicalPlan::Limit(limit) => limit.children(),
         }
     }
+    /// The expressions of this node, not of its children.
+    pub(crate) fn expressions(&self) -> Vec<&LogicalExpression> {
+        match self {
+            LogicalPlan::Scan(scan) => scan.filter.iter().map(|filter| &**filter).collect(),
+            LogicalPlan::Projection(proj) => proj.exprs.iter().collect(),
+            LogicalPlan::Selection(sel) => vec![&sel.expr],
+            LogicalPlan::Aggregate(agg) => {
+                agg.group_exprs.iter().chain(&agg.aggregate_exprs).collect()
+            }
+            LogicalPlan::Sort(_) | LogicalPlan::Limit(_) => vec![],
+        }
+    }
+    /// Fails with [`Error::ExpressionTooDeep`] if an expression anywhere in
+    /// the plan is nested deeper than [`max_expression_depth`], before the
+    /// recursive optimizer and planner walk it.
+    pub fn check_expression_depth(&self) -> Result<(), Error> {
+        self.expressions()
+            .into_iter()
+            .try_for_each(check_expression_depth)?;
+        self.children()
+            .unwrap_or_default()
+            .iter()
+            .try_for_each(|child| child.check_expression_depth())
+    }
+}
+
+pub(crate) fn check_expression_depth(expr: &LogicalExpression) -> Result<(), Error> {
+    let limit = max_expression_depth();
+    if expr.depth() > limit {
+        return Err(Error::ExpressionTooDeep(limit));
+    }
+    Ok(())
 }
 
 impl fmt::Display for LogicalPlan {
@@ -41,6 +82,8 @@ impl fmt::Display for LogicalPlan {
             LogicalPlan::Projection(proj) => write!(f, "{}", proj),
             LogicalPlan::Selection(sel) => write!(f, "{}", sel),
             LogicalPlan::Aggregate(agg) => write!(f, "{}", agg),
+            LogicalPlan::Sort(sort) => write!(f, "{}", sort),
+            LogicalPlan::Limit(limit) => write!(f, "{}", limit),
         }
     }
 }
@@ -50,10 +93,10 @@ pub fn format_logical_plan(plan: &LogicalPlan, indent: usize) -> String {
     (0..indent).for_each(|_| result.push_str(" \t"));
     result.push_str(&format!("{}", plan));
     result.push_str(" \n");
-    plan.children().map(|x| {
+    if let Some(x) = plan.children() {
         x.iter()
             .for_each(|child| result.push_str(&format_logical_plan(child, indent + 1)))
-    });
+    }
     result
 }
 
@@ -64,30 +107,38 @@ pub struct Scan {
     pub(crate) data_source: DataSource,
     pub(crate) projection: Option<Vec<String>>,
     pub(crate) schema: Schema,
+    /// A predicate pushed down by the optimizer. A source that can evaluate
+    /// it may skip rows early; none can yet, so the physical planner applies
+    /// it in a selection right above the scan.
+    pub(crate) filter: Option<Box<LogicalExpression>>,
 }
 
 impl Scan {
-    pub fn new(path: &str, data_source: DataSource, projection: Option<Vec<String>>) -> Self {
-        Scan {
+    /// The columns come in the order of the data source's schema, see
+    /// [`DataSource`]. Naming a column the source doesn't have is an
+    /// [`Error::NoFieldInSchema`].
+    pub fn new(
+        path: &str,
+        data_source: DataSource,
+        projection: Option<Vec<String>>,
+    ) -> Result<Self, Error> {
+        Ok(Scan {
             path: path.to_string(),
-            schema: Self::derive_schema(&data_source, &projection),
-            data_source: data_source,
-            projection: projection,
-        }
+            schema: data_source.projected_schema(projection.as_deref())?,
+            data_source,
+            projection,
+            filter: None,
+        })
     }
 
-    fn derive_schema(data_source: &DataSource, projection: &Option<Vec<String>>) -> Schema {
-        match projection {
-            Some(pro) => data_source
-                .schema()
-                .fields
-                .iter()
-                .filter(|x| pro.contains(&x.name))
-                .map(|y| y.clone())
-                .collect::<Vec<Field>>()
-                .into(),
-            None => data_source.schema(),
-        }
+    /// Keeps only the rows matching `expr`, as well as those of a filter the
+    /// scan already has.
+    pub fn with_filter(mut self, expr: LogicalExpression) -> Self {
+        self.filter = Some(Box::new(match self.filter.take() {
+            Some(filter) => filter.and(expr),
+            None => expr,
+        }));
+        self
     }
 }
 
@@ -104,6 +155,10 @@ impl fmt::Display for Scan {
                 }))
             ),
             None => write!(f, "Scan: {}; projection=None", self.path),
+        }?;
+        match &self.filter {
+            Some(filter) => write!(f, "; filter={}", filter),
+            None => Ok(()),
         }
     }
 }
@@ -127,21 +182,21 @@ pub struct Projection {
 }
 
 impl Projection {
-    pub fn new(input: LogicalPlan, exprs: Vec<LogicalExpression>) -> Self {
-        Projection {
-            schema: Self::derive_schema(&exprs, &input),
-            exprs: exprs,
+    /// Fails when an expression reads a column `input` doesn't have.
+    pub fn new(input: LogicalPlan, exprs: Vec<LogicalExpression>) -> Result<Self, Error> {
+        Ok(Projection {
+            schema: Self::derive_schema(&exprs, &input)?,
+            exprs,
             children: vec![input],
-        }
+        })
     }
 
-    fn derive_schema(exprs: &Vec<LogicalExpression>, input: &LogicalPlan) -> Schema {
+    fn derive_schema(exprs: &[LogicalExpression], input: &LogicalPlan) -> Result<Schema, Error> {
         exprs
             .iter()
             .map(|expr| expr.to_field(input))
             .collect::<Result<Vec<Field>, Error>>()
             .map(|x| x.into())
-            .unwrap()
     }
 }
 
@@ -180,23 +235,16 @@ pub struct Selection {
 impl Selection {
     pub fn new(input: LogicalPlan, expr: LogicalExpression) -> Self {
         Selection {
-            schema: Self::derive_schema(&expr, &input),
-            expr: expr,
+            schema: input.schema().unwrap().clone(),
+            expr,
             children: vec![input],
         }
     }
-
-    fn derive_schema(expr: &LogicalExpression, input: &LogicalPlan) -> Schema {
-        expr.to_field(input)
-            .map(|x| vec![x])
-            .map(|x| x.into())
-            .unwrap()
-    }
 }
 
 impl fmt::Display for Selection {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Selection: {}", format!("{}, ", self.expr))
+        write!(f, "Selection: {}, ", self.expr)
     }
 }
 
@@ -220,31 +268,31 @@ pub struct Aggregate {
 }
 
 impl Aggregate {
+    /// Fails when an expression reads a column `input` doesn't have.
     pub fn new(
         input: LogicalPlan,
         group_exprs: Vec<LogicalExpression>,
         aggregate_exprs: Vec<LogicalExpression>,
-    ) -> Self {
-        Aggregate {
-            schema: Self::derive_schema(&group_exprs, &aggregate_exprs, &input),
-            group_exprs: group_exprs,
-            aggregate_exprs: aggregate_exprs,
+    ) -> Result<Self, Error> {
+        Ok(Aggregate {
+            schema: Self::derive_schema(&group_exprs, &aggregate_exprs, &input)?,
+            group_exprs,
+            aggregate_exprs,
             children: vec![input],
-        }
+        })
     }
 
     fn derive_schema(
-        group_exprs: &Vec<LogicalExpression>,
-        aggregate_exprs: &Vec<LogicalExpression>,
+        group_exprs: &[LogicalExpression],
+        aggregate_exprs: &[LogicalExpression],
         input: &LogicalPlan,
-    ) -> Schema {
+    ) -> Result<Schema, Error> {
         group_exprs
             .iter()
             .chain(aggregate_exprs.iter())
             .map(|expr| expr.to_field(input))
             .collect::<Result<Vec<Field>, Error>>()
             .map(|x| x.into())
-            .unwrap()
     }
 }
 
@@ -272,3 +320,131 @@ impl Aggregate {
         Some(&self.children)
     }
 }
+
+// Sort
+
+/// A column to sort by and its direction.
+pub struct OrderBy {
+    pub(crate) name: String,
+    pub(crate) options: SortOptions,
+}
+
+impl OrderBy {
+    pub fn asc(name: &str) -> Self {
+        Self::new(name, false)
+    }
+
+    pub fn desc(name: &str) -> Self {
+        Self::new(name, true)
+    }
+
+    fn new(name: &str, descending: bool) -> Self {
+        OrderBy {
+            name: name.to_string(),
+            options: SortOptions {
+                descending,
+                nulls_first: descending,
+            },
+        }
+    }
+
+    /// Whether nulls come before every value. By default nulls sort as the
+    /// largest value, first when descending and last when ascending.
+    pub fn with_nulls_first(mut self, nulls_first: bool) -> Self {
+        self.options.nulls_first = nulls_first;
+        self
+    }
+}
+
+impl fmt::Display for OrderBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = if self.options.descending {
+            "DESC"
+        } else {
+            "ASC"
+        };
+        write!(f, "#{} {}", self.name, direction)?;
+        match (self.options.nulls_first, self.options.descending) {
+            (true, false) => write!(f, " NULLS FIRST"),
+            (false, true) => write!(f, " NULLS LAST"),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct Sort {
+    pub(crate) order_by: Vec<OrderBy>,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Schema,
+}
+
+impl Sort {
+    pub fn new(input: LogicalPlan, order_by: Vec<OrderBy>) -> Self {
+        Sort {
+            schema: input.schema().unwrap().clone(),
+            order_by,
+            children: vec![input],
+        }
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Sort: {}",
+            self.order_by
+                .iter()
+                .map(|key| format!("{}, ", key))
+                .collect::<String>()
+        )
+    }
+}
+
+impl Sort {
+    #[inline]
+    fn schema(&self) -> Result<&Schema, Error> {
+        Ok(&self.schema)
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
+
+// Limit
+
+pub struct Limit {
+    pub(crate) skip: usize,
+    pub(crate) fetch: usize,
+    pub(crate) children: Vec<LogicalPlan>,
+    pub(crate) schema: Schema,
+}
+
+impl Limit {
+    pub fn new(input: LogicalPlan, skip: usize, fetch: usize) -> Self {
+        Limit {
+            schema: input.schema().unwrap().clone(),
+            skip,
+            fetch,
+            children: vec![input],
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Limit: skip={}, fetch={}", self.skip, self.fetch)
+    }
+}
+
+impl Limit {
+    #[inline]
+    fn schema(&self) -> Result<&Schema, Error> {
+        Ok(&self.schema)
+    }
+    #[inline]
+    fn children(&self) -> Option<&[LogicalPlan]> {
+        Some(&self.children)
+    }
+}
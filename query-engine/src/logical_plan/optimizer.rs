@@ -1,12 +1,26 @@
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
+use crate::error::Error;
+use crate::prelude::lit;
+
 use super::{
-    logical_expression::LogicalExpression, Aggregate, LogicalPlan, Projection, Scan, Selection,
+    logical_expression::{
+        Add, And, Avg, Count, Div, Eq, Gt, GtEq, LogicalExpression, Lt, LtEq, Max, Min, Mod, Mul,
+        Neq, Or, Sub, Sum,
+    },
+    Aggregate, Limit, LogicalPlan, Projection, Scan, Selection, Sort,
 };
 
 impl LogicalPlan {
-    pub fn optimize(self) -> Self {
-        self.projection_push_down()
+    /// Fails when an expression is nested too deep to optimize, see
+    /// [`LogicalPlan::check_expression_depth`].
+    pub fn optimize(self) -> Result<Self, Error> {
+        self.check_expression_depth()?;
+        Ok(self
+            .fold_constants()
+            .predicate_push_down()
+            .projection_push_down())
     }
 }
 
@@ -18,6 +32,14 @@ impl LogicalPlan {
         self.push_down(&mut hash_set)
     }
     fn push_down(self, hash_set: &mut HashSet<String>) -> Self {
+        if let LogicalPlan::Scan(Scan {
+            filter: Some(filter),
+            ..
+        }) = &self
+        {
+            // The filter is evaluated on the scan's own columns.
+            extract_columns(filter, hash_set);
+        }
         match self {
             LogicalPlan::Scan(scan) => {
                 let hash_set = match scan.projection {
@@ -27,48 +49,70 @@ impl LogicalPlan {
                     }),
                     None => hash_set,
                 };
-                LogicalPlan::Scan(Scan::new(
-                    &scan.path,
-                    scan.data_source,
-                    Some(hash_set.iter().map(|x| x.clone()).collect::<Vec<String>>()),
-                ))
+                let projection = scan
+                    .data_source
+                    .schema()
+                    .fields
+                    .into_iter()
+                    .map(|field| field.name)
+                    .filter(|name| hash_set.contains(name))
+                    .collect::<Vec<String>>();
+                // The projection only names fields of the schema.
+                LogicalPlan::Scan(Scan {
+                    filter: scan.filter,
+                    ..Scan::new(&scan.path, scan.data_source, Some(projection))
+                        .expect("projecting fields of the schema")
+                })
             }
             LogicalPlan::Aggregate(mut agg) => {
                 let input = agg.children.pop().unwrap();
-                extract_all_columns(&agg.group_exprs, &input, hash_set);
-                extract_all_columns(&agg.aggregate_exprs, &input, hash_set);
-                LogicalPlan::Aggregate(Aggregate::new(
-                    input.push_down(hash_set),
-                    agg.group_exprs,
-                    agg.aggregate_exprs,
-                ))
+                extract_all_columns(&agg.group_exprs, hash_set);
+                extract_all_columns(&agg.aggregate_exprs, hash_set);
+                LogicalPlan::Aggregate(Aggregate {
+                    children: vec![input.push_down(hash_set)],
+                    ..agg
+                })
             }
             LogicalPlan::Projection(mut proj) => {
                 let input = proj.children.pop().unwrap();
-                extract_all_columns(&proj.exprs, &input, hash_set);
-                LogicalPlan::Projection(Projection::new(input.push_down(hash_set), proj.exprs))
+                extract_all_columns(&proj.exprs, hash_set);
+                LogicalPlan::Projection(Projection {
+                    children: vec![input.push_down(hash_set)],
+                    ..proj
+                })
             }
             LogicalPlan::Selection(mut sel) => {
                 let input = sel.children.pop().unwrap();
-                extract_columns(&sel.expr, &input, hash_set);
+                extract_columns(&sel.expr, hash_set);
                 LogicalPlan::Selection(Selection::new(input.push_down(hash_set), sel.expr))
             }
+            LogicalPlan::Sort(mut sort) => {
+                let input = sort.children.pop().unwrap();
+                sort.order_by.iter().for_each(|key| {
+                    hash_set.insert(key.name.clone());
+                });
+                LogicalPlan::Sort(Sort::new(input.push_down(hash_set), sort.order_by))
+            }
+            LogicalPlan::Limit(mut limit) => {
+                let input = limit.children.pop().unwrap();
+                LogicalPlan::Limit(Limit::new(
+                    input.push_down(hash_set),
+                    limit.skip,
+                    limit.fetch,
+                ))
+            }
         }
     }
 }
 
-fn extract_all_columns(
-    exprs: &[LogicalExpression],
-    plan: &LogicalPlan,
-    hash_set: &mut HashSet<String>,
-) {
+fn extract_all_columns(exprs: &[LogicalExpression], hash_set: &mut HashSet<String>) {
     exprs.iter().fold(hash_set, |hash_set, expr| {
-        extract_columns(expr, plan, hash_set);
+        extract_columns(expr, hash_set);
         hash_set
     });
 }
 
-fn extract_columns(expr: &LogicalExpression, plan: &LogicalPlan, hash_set: &mut HashSet<String>) {
+fn extract_columns(expr: &LogicalExpression, hash_set: &mut HashSet<String>) {
     match expr {
         LogicalExpression::Column(column) => {
             hash_set.insert(column.name.clone());
@@ -78,61 +122,301 @@ fn extract_columns(expr: &LogicalExpression, plan: &LogicalPlan, hash_set: &mut
         LogicalExpression::LiteralInteger(_) => {}
         LogicalExpression::LiteralFloat(_) => {}
         LogicalExpression::Eq(eq) => {
-            extract_columns(&eq.left, plan, hash_set);
-            extract_columns(&eq.right, plan, hash_set)
+            extract_columns(&eq.left, hash_set);
+            extract_columns(&eq.right, hash_set)
         }
         LogicalExpression::Neq(neq) => {
-            extract_columns(&neq.left, plan, hash_set);
-            extract_columns(&neq.right, plan, hash_set)
+            extract_columns(&neq.left, hash_set);
+            extract_columns(&neq.right, hash_set)
         }
         LogicalExpression::Gt(gt) => {
-            extract_columns(&gt.left, plan, hash_set);
-            extract_columns(&gt.right, plan, hash_set)
+            extract_columns(&gt.left, hash_set);
+            extract_columns(&gt.right, hash_set)
         }
         LogicalExpression::GtEq(gteq) => {
-            extract_columns(&gteq.left, plan, hash_set);
-            extract_columns(&gteq.right, plan, hash_set)
+            extract_columns(&gteq.left, hash_set);
+            extract_columns(&gteq.right, hash_set)
         }
         LogicalExpression::Lt(lt) => {
-            extract_columns(&lt.left, plan, hash_set);
-            extract_columns(&lt.right, plan, hash_set)
+            extract_columns(&lt.left, hash_set);
+            extract_columns(&lt.right, hash_set)
         }
         LogicalExpression::LtEq(lteq) => {
-            extract_columns(&lteq.left, plan, hash_set);
-            extract_columns(&lteq.right, plan, hash_set)
+            extract_columns(&lteq.left, hash_set);
+            extract_columns(&lteq.right, hash_set)
         }
         LogicalExpression::And(and) => {
-            extract_columns(&and.left, plan, hash_set);
-            extract_columns(&and.right, plan, hash_set)
+            extract_columns(&and.left, hash_set);
+            extract_columns(&and.right, hash_set)
         }
         LogicalExpression::Or(or) => {
-            extract_columns(&or.left, plan, hash_set);
-            extract_columns(&or.right, plan, hash_set)
+            extract_columns(&or.left, hash_set);
+            extract_columns(&or.right, hash_set)
         }
         LogicalExpression::Add(add) => {
-            extract_columns(&add.left, plan, hash_set);
-            extract_columns(&add.right, plan, hash_set)
+            extract_columns(&add.left, hash_set);
+            extract_columns(&add.right, hash_set)
         }
         LogicalExpression::Sub(sub) => {
-            extract_columns(&sub.left, plan, hash_set);
-            extract_columns(&sub.right, plan, hash_set)
+            extract_columns(&sub.left, hash_set);
+            extract_columns(&sub.right, hash_set)
         }
         LogicalExpression::Div(div) => {
-            extract_columns(&div.left, plan, hash_set);
-            extract_columns(&div.right, plan, hash_set)
+            extract_columns(&div.left, hash_set);
+            extract_columns(&div.right, hash_set)
         }
         LogicalExpression::Mul(mul) => {
-            extract_columns(&mul.left, plan, hash_set);
-            extract_columns(&mul.right, plan, hash_set)
+            extract_columns(&mul.left, hash_set);
+            extract_columns(&mul.right, hash_set)
         }
         LogicalExpression::Mod(modu) => {
-            extract_columns(&modu.left, plan, hash_set);
-            extract_columns(&modu.right, plan, hash_set)
-        }
-        LogicalExpression::Avg(avg) => extract_columns(&avg.expr, plan, hash_set),
-        LogicalExpression::Sum(sum) => extract_columns(&sum.expr, plan, hash_set),
-        LogicalExpression::Max(max) => extract_columns(&max.expr, plan, hash_set),
-        LogicalExpression::Min(min) => extract_columns(&min.expr, plan, hash_set),
-        LogicalExpression::Count(count) => extract_columns(&count.expr, plan, hash_set),
+            extract_columns(&modu.left, hash_set);
+            extract_columns(&modu.right, hash_set)
+        }
+        LogicalExpression::Avg(avg) => extract_columns(&avg.expr, hash_set),
+        LogicalExpression::Sum(sum) => extract_columns(&sum.expr, hash_set),
+        LogicalExpression::Max(max) => extract_columns(&max.expr, hash_set),
+        LogicalExpression::Min(min) => extract_columns(&min.expr, hash_set),
+        LogicalExpression::Count(count) => extract_columns(&count.expr, hash_set),
+    }
+}
+
+// Predicate push down
+
+impl LogicalPlan {
+    /// Moves every selection as close to its scan as it can go, so that rows
+    /// are dropped before they are projected or sorted. A selection that
+    /// reaches its scan becomes the scan's filter, see [`Scan::with_filter`].
+    fn predicate_push_down(self) -> Self {
+        match self {
+            LogicalPlan::Scan(scan) => LogicalPlan::Scan(scan),
+            LogicalPlan::Selection(mut sel) => {
+                let input = sel.children.pop().unwrap().predicate_push_down();
+                push_selection(input, sel.expr)
+            }
+            LogicalPlan::Projection(mut proj) => {
+                let input = proj.children.pop().unwrap().predicate_push_down();
+                LogicalPlan::Projection(Projection {
+                    children: vec![input],
+                    ..proj
+                })
+            }
+            LogicalPlan::Aggregate(mut agg) => {
+                let input = agg.children.pop().unwrap().predicate_push_down();
+                LogicalPlan::Aggregate(Aggregate {
+                    children: vec![input],
+                    ..agg
+                })
+            }
+            LogicalPlan::Sort(mut sort) => {
+                let input = sort.children.pop().unwrap().predicate_push_down();
+                LogicalPlan::Sort(Sort::new(input, sort.order_by))
+            }
+            LogicalPlan::Limit(mut limit) => {
+                let input = limit.children.pop().unwrap().predicate_push_down();
+                LogicalPlan::Limit(Limit::new(input, limit.skip, limit.fetch))
+            }
+        }
+    }
+}
+
+/// Filters `input` by `expr`, below the sorts and projections at the top of
+/// `input` that the predicate can pass, and into the scan if it gets there. A
+/// projection can only be passed when every column the predicate reads is one
+/// it passes through unchanged.
+fn push_selection(input: LogicalPlan, expr: LogicalExpression) -> LogicalPlan {
+    match input {
+        LogicalPlan::Scan(scan) => LogicalPlan::Scan(scan.with_filter(expr)),
+        LogicalPlan::Projection(mut proj) if passes_through(&proj, &expr) => {
+            let input = proj.children.pop().unwrap();
+            LogicalPlan::Projection(Projection {
+                children: vec![push_selection(input, expr)],
+                ..proj
+            })
+        }
+        LogicalPlan::Sort(mut sort) => {
+            let input = sort.children.pop().unwrap();
+            LogicalPlan::Sort(Sort::new(push_selection(input, expr), sort.order_by))
+        }
+        input => LogicalPlan::Selection(Selection::new(input, expr)),
+    }
+}
+
+fn passes_through(proj: &Projection, expr: &LogicalExpression) -> bool {
+    let mut columns = HashSet::new();
+    extract_columns(expr, &mut columns);
+    columns.iter().all(|name| {
+        proj.schema
+            .fields
+            .iter()
+            .zip(&proj.exprs)
+            .find(|(field, _)| field.name == *name)
+            .is_some_and(|(_, expr)| {
+                matches!(expr, LogicalExpression::Column(column) if column.name == *name)
+            })
+    })
+}
+
+// Constant folding
+
+impl LogicalPlan {
+    fn fold_constants(self) -> Self {
+        match self {
+            LogicalPlan::Scan(scan) => LogicalPlan::Scan(scan),
+            LogicalPlan::Projection(mut proj) => {
+                let input = proj.children.pop().unwrap().fold_constants();
+                let exprs = fold_all(proj.exprs);
+                // Folding only replaces literals, the columns stay the same.
+                LogicalPlan::Projection(
+                    Projection::new(input, exprs).expect("folding keeps the columns"),
+                )
+            }
+            LogicalPlan::Selection(mut sel) => {
+                let input = sel.children.pop().unwrap().fold_constants();
+                LogicalPlan::Selection(Selection::new(input, sel.expr.fold_constants()))
+            }
+            LogicalPlan::Aggregate(mut agg) => {
+                let input = agg.children.pop().unwrap().fold_constants();
+                LogicalPlan::Aggregate(
+                    Aggregate::new(
+                        input,
+                        fold_all(agg.group_exprs),
+                        fold_all(agg.aggregate_exprs),
+                    )
+                    .expect("folding keeps the columns"),
+                )
+            }
+            LogicalPlan::Sort(mut sort) => {
+                let input = sort.children.pop().unwrap().fold_constants();
+                LogicalPlan::Sort(Sort::new(input, sort.order_by))
+            }
+            LogicalPlan::Limit(mut limit) => {
+                let input = limit.children.pop().unwrap().fold_constants();
+                LogicalPlan::Limit(Limit::new(input, limit.skip, limit.fetch))
+            }
+        }
+    }
+}
+
+fn fold_all(exprs: Vec<LogicalExpression>) -> Vec<LogicalExpression> {
+    exprs.into_iter().map(|x| x.fold_constants()).collect()
+}
+
+macro_rules! fold_binary {
+    ($expr: expr, $i: ident, $fold: ident, $($arg: expr),*) => {{
+        let expr = *$expr;
+        let left = expr.left.fold_constants();
+        let right = expr.right.fold_constants();
+        match $fold(&left, &right, $($arg),*) {
+            Some(folded) => folded,
+            None => LogicalExpression::$i(Box::new($i::new(left, right))),
+        }
+    }};
+}
+
+macro_rules! fold_aggregate {
+    ($expr: expr, $i: ident) => {
+        LogicalExpression::$i(Box::new($i::new($expr.expr.fold_constants())))
+    };
+}
+
+impl LogicalExpression {
+    /// Replaces every subexpression that only combines literals with the
+    /// literal it evaluates to, e.g. `#a + 2 * 3` becomes `#a + 6`.
+    /// Subexpressions that would overflow, divide by zero or compare NaN are
+    /// left for execution to report.
+    pub fn fold_constants(self) -> Self {
+        match self {
+            LogicalExpression::Add(add) => {
+                fold_binary!(add, Add, arithmetic, i32::checked_add, |a, b| Some(a + b))
+            }
+            LogicalExpression::Sub(sub) => {
+                fold_binary!(sub, Sub, arithmetic, i32::checked_sub, |a, b| Some(a - b))
+            }
+            LogicalExpression::Mul(mul) => {
+                fold_binary!(mul, Mul, arithmetic, i32::checked_mul, |a, b| Some(a * b))
+            }
+            LogicalExpression::Div(div) => {
+                fold_binary!(div, Div, arithmetic, i32::checked_div, float_div)
+            }
+            LogicalExpression::Mod(modu) => {
+                fold_binary!(modu, Mod, arithmetic, i32::checked_rem, float_rem)
+            }
+            LogicalExpression::Eq(eq) => fold_binary!(eq, Eq, comparison, Ordering::is_eq),
+            LogicalExpression::Neq(neq) => fold_binary!(neq, Neq, comparison, Ordering::is_ne),
+            LogicalExpression::Gt(gt) => fold_binary!(gt, Gt, comparison, Ordering::is_gt),
+            LogicalExpression::GtEq(gteq) => fold_binary!(gteq, GtEq, comparison, Ordering::is_ge),
+            LogicalExpression::Lt(lt) => fold_binary!(lt, Lt, comparison, Ordering::is_lt),
+            LogicalExpression::LtEq(lteq) => fold_binary!(lteq, LtEq, comparison, Ordering::is_le),
+            LogicalExpression::And(and) => fold_binary!(and, And, logical, |a, b| a && b),
+            LogicalExpression::Or(or) => fold_binary!(or, Or, logical, |a, b| a || b),
+            LogicalExpression::Sum(sum) => fold_aggregate!(sum, Sum),
+            LogicalExpression::Avg(avg) => fold_aggregate!(avg, Avg),
+            LogicalExpression::Max(max) => fold_aggregate!(max, Max),
+            LogicalExpression::Min(min) => fold_aggregate!(min, Min),
+            LogicalExpression::Count(count) => fold_aggregate!(count, Count),
+            expr => expr,
+        }
+    }
+}
+
+fn arithmetic(
+    left: &LogicalExpression,
+    right: &LogicalExpression,
+    int: fn(i32, i32) -> Option<i32>,
+    float: fn(f64, f64) -> Option<f64>,
+) -> Option<LogicalExpression> {
+    match (left, right) {
+        (LogicalExpression::LiteralInteger(l), LogicalExpression::LiteralInteger(r)) => {
+            int(l.value, r.value).map(lit)
+        }
+        (LogicalExpression::LiteralFloat(l), LogicalExpression::LiteralFloat(r)) => {
+            float(l.value, r.value).map(lit)
+        }
+        _ => None,
+    }
+}
+
+fn float_div(a: f64, b: f64) -> Option<f64> {
+    (b != 0.0).then(|| a / b)
+}
+
+fn float_rem(a: f64, b: f64) -> Option<f64> {
+    (b != 0.0).then(|| a % b)
+}
+
+fn comparison(
+    left: &LogicalExpression,
+    right: &LogicalExpression,
+    holds: fn(Ordering) -> bool,
+) -> Option<LogicalExpression> {
+    let ordering = match (left, right) {
+        (LogicalExpression::LiteralInteger(l), LogicalExpression::LiteralInteger(r)) => {
+            l.value.cmp(&r.value)
+        }
+        (LogicalExpression::LiteralFloat(l), LogicalExpression::LiteralFloat(r)) => {
+            l.value.partial_cmp(&r.value)?
+        }
+        (LogicalExpression::LiteralString(l), LogicalExpression::LiteralString(r)) => {
+            l.value.cmp(&r.value)
+        }
+        (LogicalExpression::LiteralBool(l), LogicalExpression::LiteralBool(r)) => {
+            l.value.cmp(&r.value)
+        }
+        _ => return None,
+    };
+    Some(lit(holds(ordering)))
+}
+
+fn logical(
+    left: &LogicalExpression,
+    right: &LogicalExpression,
+    op: fn(bool, bool) -> bool,
+) -> Option<LogicalExpression> {
+    match (left, right) {
+        (LogicalExpression::LiteralBool(l), LogicalExpression::LiteralBool(r)) => {
+            Some(lit(op(l.value, r.value)))
+        }
+        _ => None,
     }
 }
@@ -1,33 +1,40 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::ops::{BitAnd, BitOr};
 use std::sync::Arc;
-use std::{borrow::Borrow, collections::HashMap};
 
-use arrow2::array::{
-    MutableArray, MutableBooleanArray, MutablePrimitiveArray, MutableUtf8Array, PrimitiveArray,
-    Utf8Array,
-};
-use arrow2::bitmap::Bitmap;
-use arrow2::buffer::Buffer;
-use arrow2::compute::arithmetics::ArrayAdd;
+use arrow2::array::{new_empty_array, new_null_array, PrimitiveArray, Utf8Array};
+use arrow2::bitmap::MutableBitmap;
+use arrow2::compute::sort::{SortColumn, SortOptions};
 use arrow2::datatypes::{DataType, PhysicalType, PrimitiveType};
-use arrow2::scalar::PrimitiveScalar;
+use arrow2::scalar::{new_scalar, BooleanScalar};
 use arrow2::{
     array::{Array, BooleanArray},
     chunk::Chunk,
     compute,
-    datatypes::Schema,
+    datatypes::{Field, Schema},
     error::ArrowError,
 };
 
 use crate::{
-    columnar_value::{scalar_to_array, ColumnarValue},
+    columnar_value::{scalar_to_array, scalars_to_array, ColumnarValue},
     data_source::DataSource,
     error::Error,
+    operator::Operator,
+    schema::SchemaExt,
 };
 
-use self::physical_expressions::{PhysicalAggregateExpression, PhysicalExpression};
+use self::ordering::{ordering_satisfies, sort_key, SortExpression, SortKey};
+use self::physical_expressions::{
+    empty_string_to_null, Accumulator, PhysicalAggregateExpression, PhysicalExpression,
+    WindowFunction,
+};
 
+pub mod ordering;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 pub mod physical_expressions;
+#[cfg(feature = "tracing")]
+mod trace;
 
 pub type Batch = Result<Chunk<Arc<dyn Array>>, Error>;
 
@@ -36,6 +43,13 @@ pub enum PhysicalPlan {
     Projection(ProjectionExec),
     Selection(SelectionExec),
     Aggregate(AggregateExec),
+    Limit(LimitExec),
+    Window(WindowExec),
+    Sort(SortExec),
+    HashJoin(HashJoinExec),
+    CrossJoin(CrossJoinExec),
+    Distinct(DistinctExec),
+    Union(UnionExec),
 }
 
 impl PhysicalPlan {
@@ -45,6 +59,13 @@ impl PhysicalPlan {
             PhysicalPlan::Projection(proj) => proj.schema(),
             PhysicalPlan::Selection(sel) => sel.schema(),
             PhysicalPlan::Aggregate(agg) => agg.schema(),
+            PhysicalPlan::Limit(limit) => limit.schema(),
+            PhysicalPlan::Window(window) => window.schema(),
+            PhysicalPlan::Sort(sort) => sort.schema(),
+            PhysicalPlan::HashJoin(join) => join.schema(),
+            PhysicalPlan::CrossJoin(join) => join.schema(),
+            PhysicalPlan::Distinct(distinct) => distinct.schema(),
+            PhysicalPlan::Union(union) => union.schema(),
         }
     }
     pub fn children(&self) -> Option<&[PhysicalPlan]> {
@@ -53,15 +74,99 @@ impl PhysicalPlan {
             PhysicalPlan::Projection(proj) => proj.children(),
             PhysicalPlan::Selection(sel) => sel.children(),
             PhysicalPlan::Aggregate(agg) => agg.children(),
+            PhysicalPlan::Limit(limit) => limit.children(),
+            PhysicalPlan::Window(window) => window.children(),
+            PhysicalPlan::Sort(sort) => sort.children(),
+            PhysicalPlan::HashJoin(join) => join.children(),
+            PhysicalPlan::CrossJoin(join) => join.children(),
+            PhysicalPlan::Distinct(distinct) => distinct.children(),
+            PhysicalPlan::Union(union) => union.children(),
         }
     }
-    pub fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+    pub(crate) fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        match self {
+            PhysicalPlan::Scan(scan) => scan.children_mut(),
+            PhysicalPlan::Projection(proj) => proj.children_mut(),
+            PhysicalPlan::Selection(sel) => sel.children_mut(),
+            PhysicalPlan::Aggregate(agg) => agg.children_mut(),
+            PhysicalPlan::Limit(limit) => limit.children_mut(),
+            PhysicalPlan::Window(window) => window.children_mut(),
+            PhysicalPlan::Sort(sort) => sort.children_mut(),
+            PhysicalPlan::HashJoin(join) => join.children_mut(),
+            PhysicalPlan::CrossJoin(join) => join.children_mut(),
+            PhysicalPlan::Distinct(distinct) => distinct.children_mut(),
+            PhysicalPlan::Union(union) => union.children_mut(),
+        }
+    }
+    /// The order the output rows are known to come in, empty if unknown.
+    ///
+    /// Filters, limits and distincts keep the order of their input.
+    /// Projections and windows renumber their columns, so they conservatively
+    /// report none.
+    pub fn output_ordering(&self) -> &[SortKey] {
+        match self {
+            PhysicalPlan::Selection(_) | PhysicalPlan::Limit(_) | PhysicalPlan::Distinct(_) => self
+                .children()
+                .and_then(|children| children.first())
+                .map(|input| input.output_ordering())
+                .unwrap_or_default(),
+            PhysicalPlan::Sort(sort) => &sort.ordering,
+            _ => &[],
+        }
+    }
+    /// The order each child's output must come in, `None` for no requirement.
+    ///
+    /// A window over bare columns wants its input sorted by its partition and
+    /// order keys, and skips its own sort when it is.
+    pub fn required_input_ordering(&self) -> Vec<Option<Vec<SortKey>>> {
+        match self {
+            PhysicalPlan::Window(window) => vec![window.sort_keys()],
+            _ => {
+                let children = self.children().map(|x| x.len()).unwrap_or_default();
+                vec![None; children]
+            }
+        }
+    }
+    /// The operator's name, as used in `tracing` spans.
+    pub fn name(&self) -> &'static str {
         match self {
+            PhysicalPlan::Scan(_) => "Scan",
+            PhysicalPlan::Projection(_) => "Projection",
+            PhysicalPlan::Selection(_) => "Selection",
+            PhysicalPlan::Aggregate(_) => "Aggregate",
+            PhysicalPlan::Limit(_) => "Limit",
+            PhysicalPlan::Window(_) => "Window",
+            PhysicalPlan::Sort(_) => "Sort",
+            PhysicalPlan::HashJoin(_) => "HashJoin",
+            PhysicalPlan::CrossJoin(_) => "CrossJoin",
+            PhysicalPlan::Distinct(_) => "Distinct",
+            PhysicalPlan::Union(_) => "Union",
+        }
+    }
+    /// Executes the plan, returning an iterator over its output batches.
+    ///
+    /// With the `tracing` feature every operator runs in an `execute` span,
+    /// which the spans of its inputs nest in, and each batch it produces in a
+    /// `batch` span below it.
+    pub fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        #[cfg(feature = "tracing")]
+        let execution = trace::Execution::start(self.name());
+        let batches = match self {
             PhysicalPlan::Scan(scan) => scan.execute(),
             PhysicalPlan::Projection(proj) => proj.execute(),
             PhysicalPlan::Selection(sel) => sel.execute(),
             PhysicalPlan::Aggregate(agg) => agg.execute(),
-        }
+            PhysicalPlan::Limit(limit) => limit.execute(),
+            PhysicalPlan::Window(window) => window.execute(),
+            PhysicalPlan::Sort(sort) => sort.execute(),
+            PhysicalPlan::HashJoin(join) => join.execute(),
+            PhysicalPlan::CrossJoin(join) => join.execute(),
+            PhysicalPlan::Distinct(distinct) => distinct.execute(),
+            PhysicalPlan::Union(union) => union.execute(),
+        }?;
+        #[cfg(feature = "tracing")]
+        let batches = execution.finish(batches);
+        Ok(batches)
     }
 }
 
@@ -74,24 +179,54 @@ pub struct ScanExec {
 impl ScanExec {
     pub fn new(data_source: DataSource, projection: Option<Vec<String>>, schema: Schema) -> Self {
         ScanExec {
-            schema: schema,
-            data_source: data_source,
-            projection: projection,
+            schema,
+            data_source,
+            projection,
         }
     }
 }
 
 pub struct ScanIterator<I: Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>> {
     input_iter: I,
+    empty_as_null: Vec<usize>,
+}
+
+impl<I: Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>> ScanIterator<I> {
+    fn empty_as_null(&self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        if self.empty_as_null.is_empty() {
+            return Ok(chunk);
+        }
+        chunk
+            .into_arrays()
+            .into_iter()
+            .enumerate()
+            .map(|(i, array)| {
+                if self.empty_as_null.contains(&i) {
+                    array
+                        .as_any()
+                        .downcast_ref::<Utf8Array<i32>>()
+                        .map(|array| Arc::new(empty_string_to_null(array)) as Arc<dyn Array>)
+                        .ok_or(Error::PhysicalTypeNotSuported(format!(
+                            "{:?}",
+                            array.data_type()
+                        )))
+                } else {
+                    Ok(array)
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map(Chunk::new)
+    }
 }
 
 impl<I: Iterator<Item = Result<Chunk<Arc<dyn Array>>, ArrowError>>> Iterator for ScanIterator<I> {
     type Item = Batch;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.input_iter.next() {
-            Some(chunk) => Some(chunk.map_err(|err| Error::ArrowError(err))),
-            None => None,
-        }
+        self.input_iter.next().map(|chunk| {
+            chunk
+                .map_err(Error::ArrowError)
+                .and_then(|chunk| self.empty_as_null(chunk))
+        })
     }
 }
 
@@ -102,9 +237,21 @@ impl ScanExec {
     fn children(&self) -> Option<&[PhysicalPlan]> {
         None
     }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        None
+    }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let empty_as_null = self
+            .schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| self.data_source.empty_as_null().contains(&field.name))
+            .map(|(i, _)| i)
+            .collect();
         Ok(Box::new(ScanIterator {
-            input_iter: self.data_source.scan(self.projection),
+            input_iter: self.data_source.scan(self.projection)?,
+            empty_as_null,
         }))
     }
 }
@@ -113,6 +260,8 @@ pub struct ProjectionExec {
     input: Vec<PhysicalPlan>,
     schema: Schema,
     exprs: Vec<Box<dyn PhysicalExpression>>,
+    #[cfg(feature = "rayon")]
+    parallel: bool,
 }
 
 impl ProjectionExec {
@@ -122,12 +271,65 @@ impl ProjectionExec {
         schema: Schema,
     ) -> Self {
         ProjectionExec {
-            schema: schema,
-            input: input,
-            exprs: exprs,
+            schema,
+            input,
+            exprs,
+            #[cfg(feature = "rayon")]
+            parallel: false,
         }
     }
+
+    /// A projection whose output schema has a nullable field per expression,
+    /// named after its `Display` and typed after what it evaluates to on an
+    /// empty batch of the input's schema.
+    pub fn try_new(
+        input: Vec<PhysicalPlan>,
+        exprs: Vec<Box<dyn PhysicalExpression>>,
+    ) -> Result<Self, Error> {
+        let empty = input
+            .first()
+            .map(|input| {
+                input
+                    .schema()
+                    .fields
+                    .iter()
+                    .map(|field| Arc::from(new_empty_array(field.data_type().clone())))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let empty = Chunk::new(empty);
+        let fields = exprs
+            .iter()
+            .map(|expr| {
+                let data_type = expr.evaluate(&empty)?.data_type();
+                Ok(Field::new(expr.to_string(), data_type, true))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Self::new(input, exprs, Schema::from(fields)))
+    }
+
+    /// Projects several batches at once on rayon's thread pool, see
+    /// [`parallel::ParallelMap`]. The output is the same, in the same order.
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+}
+
+fn project(exprs: &[Box<dyn PhysicalExpression>], chunk: Chunk<Arc<dyn Array>>) -> Batch {
+    exprs
+        .iter()
+        .map(|expr| {
+            expr.evaluate(&chunk).and_then(|col| match col {
+                ColumnarValue::Array(array) => Ok(array),
+                ColumnarValue::Scalar(scalar) => Ok(scalar_to_array(&*scalar, chunk.len())?),
+            })
+        })
+        .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
+        .map(Chunk::new)
 }
+
 pub struct ProjectionIterator<I: Iterator<Item = Batch>> {
     input_iter: I,
     exprs: Vec<Box<dyn PhysicalExpression>>,
@@ -136,23 +338,9 @@ pub struct ProjectionIterator<I: Iterator<Item = Batch>> {
 impl<I: Iterator<Item = Batch>> Iterator for ProjectionIterator<I> {
     type Item = Batch;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.input_iter.next() {
-            Some(res) => Some(res.and_then(|chunk| {
-                self.exprs
-                    .iter()
-                    .map(|expr| {
-                        expr.evaluate(&chunk).and_then(|col| match col {
-                            ColumnarValue::Array(array) => Ok(array),
-                            ColumnarValue::Scalar(scalar) => {
-                                Ok(scalar_to_array(scalar, chunk.len())?)
-                            }
-                        })
-                    })
-                    .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
-                    .map(|v| Chunk::new(v))
-            })),
-            None => None,
-        }
+        self.input_iter
+            .next()
+            .map(|res| res.and_then(|chunk| project(&self.exprs, chunk)))
     }
 }
 
@@ -163,11 +351,22 @@ impl ProjectionExec {
     fn children(&self) -> Option<&[PhysicalPlan]> {
         Some(&self.input)
     }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
         let mut vec = self.input;
         let input = vec
             .pop()
             .ok_or(Error::MissingInputPhysicalPlan("Projection".to_string()))?;
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            let exprs = self.exprs;
+            return Ok(Box::new(parallel::ParallelMap::new(
+                input.execute()?,
+                move |chunk| project(&exprs, chunk),
+            )));
+        }
         Ok(Box::new(ProjectionIterator {
             input_iter: input.execute()?,
             exprs: self.exprs,
@@ -179,6 +378,9 @@ pub struct SelectionExec {
     input: Vec<PhysicalPlan>,
     schema: Schema,
     expr: Box<dyn PhysicalExpression>,
+    keep_nulls: bool,
+    #[cfg(feature = "rayon")]
+    parallel: bool,
 }
 
 impl SelectionExec {
@@ -188,44 +390,93 @@ impl SelectionExec {
         schema: Schema,
     ) -> Self {
         SelectionExec {
-            schema: schema,
-            input: input,
-            expr: expr,
+            schema,
+            input,
+            expr,
+            keep_nulls: false,
+            #[cfg(feature = "rayon")]
+            parallel: false,
         }
     }
+
+    /// Keeps the rows where the predicate is null instead of dropping them as
+    /// SQL does. Meant for auditing which rows a predicate can't decide.
+    pub fn with_keep_nulls(mut self, keep_nulls: bool) -> Self {
+        self.keep_nulls = keep_nulls;
+        self
+    }
+
+    /// Filters several batches at once on rayon's thread pool, see
+    /// [`parallel::ParallelMap`]. The output is the same, in the same order.
+    #[cfg(feature = "rayon")]
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+}
+
+/// Maps the null entries of a filter predicate to false, so that like SQL's
+/// `WHERE` only rows where the predicate is true are kept.
+pub fn null_as_false(predicate: &BooleanArray) -> BooleanArray {
+    match predicate.validity() {
+        Some(validity) => {
+            BooleanArray::new(DataType::Boolean, predicate.values().bitand(validity), None)
+        }
+        None => predicate.clone(),
+    }
+}
+
+/// Maps the null entries of a filter predicate to true.
+pub fn null_as_true(predicate: &BooleanArray) -> BooleanArray {
+    match predicate.validity() {
+        Some(validity) => BooleanArray::new(
+            DataType::Boolean,
+            predicate.values().bitor(&!validity),
+            None,
+        ),
+        None => predicate.clone(),
+    }
 }
 
 pub struct SelectionIterator<I: Iterator<Item = Batch>> {
     input_iter: I,
     expr: Box<dyn PhysicalExpression>,
+    keep_nulls: bool,
+}
+
+fn select(expr: &dyn PhysicalExpression, keep_nulls: bool, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+    let bitvector = expr.evaluate(&chunk).and_then(|col| match col {
+        ColumnarValue::Array(array) => Ok(array),
+        ColumnarValue::Scalar(scalar) if !scalar.is_valid() => Ok(Arc::new(
+            BooleanArray::new_null(DataType::Boolean, chunk.len()),
+        )),
+        ColumnarValue::Scalar(scalar) => Ok(scalar_to_array(&*scalar, chunk.len())?),
+    })?;
+    let bitvector = bitvector
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .ok_or(Error::NoBooleanArrayForFilter)?;
+    let predicate = if keep_nulls {
+        null_as_true(bitvector)
+    } else {
+        null_as_false(bitvector)
+    };
+    Ok(Chunk::new(
+        compute::filter::filter_chunk(&chunk, &predicate)
+            .map_err(Error::ArrowError)?
+            .into_arrays()
+            .into_iter()
+            .map(|array| Arc::from(array) as Arc<dyn Array>)
+            .collect::<Vec<Arc<dyn Array>>>(),
+    ))
 }
 
 impl<I: Iterator<Item = Batch>> Iterator for SelectionIterator<I> {
     type Item = Batch;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.input_iter.next() {
-            Some(res) => Some(res.and_then(|chunk| {
-                let bitvector = self.expr.evaluate(&chunk).and_then(|col| match col {
-                    ColumnarValue::Array(array) => Ok(array),
-                    ColumnarValue::Scalar(scalar) => Ok(scalar_to_array(scalar, chunk.len())?),
-                })?;
-                Ok(Chunk::new(
-                    compute::filter::filter_chunk(
-                        &chunk,
-                        bitvector
-                            .as_any()
-                            .downcast_ref::<BooleanArray>()
-                            .ok_or(Error::NoBooleanArrayForFilter)?,
-                    )
-                    .map_err(|err| Error::ArrowError(err))?
-                    .into_arrays()
-                    .into_iter()
-                    .map(|array| Arc::from(array) as Arc<dyn Array>)
-                    .collect::<Vec<Arc<dyn Array>>>(),
-                ))
-            })),
-            None => None,
-        }
+        self.input_iter
+            .next()
+            .map(|res| res.and_then(|chunk| select(&*self.expr, self.keep_nulls, chunk)))
     }
 }
 
@@ -236,18 +487,190 @@ impl SelectionExec {
     fn children(&self) -> Option<&[PhysicalPlan]> {
         Some(&self.input)
     }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
         let mut vec = self.input;
         let input = vec
             .pop()
             .ok_or(Error::MissingInputPhysicalPlan("Selection".to_string()))?;
+        // A constant predicate keeps either every row or none, so the input is
+        // passed through as is or not executed at all.
+        if let Some(scalar) = self.expr.evaluate_scalar()? {
+            if let Some(predicate) = scalar.as_any().downcast_ref::<BooleanScalar>() {
+                return match predicate.value() {
+                    Some(true) => input.execute(),
+                    None if self.keep_nulls => input.execute(),
+                    _ => Ok(Box::new(std::iter::empty())),
+                };
+            }
+        }
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            let (expr, keep_nulls) = (self.expr, self.keep_nulls);
+            return Ok(Box::new(parallel::ParallelMap::new(
+                input.execute()?,
+                move |chunk| select(&*expr, keep_nulls, chunk),
+            )));
+        }
         Ok(Box::new(SelectionIterator {
             input_iter: input.execute()?,
             expr: self.expr,
+            keep_nulls: self.keep_nulls,
         }))
     }
 }
 
+pub struct LimitExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    fetch: usize,
+    skip: usize,
+}
+
+impl LimitExec {
+    pub fn new(input: Vec<PhysicalPlan>, fetch: usize, schema: Schema) -> Self {
+        LimitExec {
+            input,
+            schema,
+            fetch,
+            skip: 0,
+        }
+    }
+
+    /// Drops the first `skip` rows before passing on at most `fetch` rows,
+    /// like SQL's `OFFSET`.
+    pub fn with_skip(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+}
+
+/// Slices every column of `chunk` to `[offset, offset + length)`.
+///
+/// Slicing only adjusts the offset and length of each array, the underlying
+/// buffers are shared with `chunk` and no data is copied.
+pub fn slice_chunk(
+    chunk: &Chunk<Arc<dyn Array>>,
+    offset: usize,
+    length: usize,
+) -> Chunk<Arc<dyn Array>> {
+    Chunk::new(
+        chunk
+            .arrays()
+            .iter()
+            .map(|array| Arc::from(array.slice(offset, length)))
+            .collect(),
+    )
+}
+
+pub struct LimitIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    skip: usize,
+    remaining: usize,
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for LimitIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Stop pulling from the input as soon as the limit is reached.
+            if self.remaining == 0 {
+                return None;
+            }
+            let chunk = match self.input_iter.next()? {
+                Ok(chunk) => chunk,
+                Err(err) => return Some(Err(err)),
+            };
+            let offset = self.skip.min(chunk.len());
+            self.skip -= offset;
+            let length = self.remaining.min(chunk.len() - offset);
+            // Batches consumed entirely by the skip aren't passed on.
+            if length == 0 && offset > 0 {
+                continue;
+            }
+            self.remaining -= length;
+            return Some(Ok(if length == chunk.len() {
+                chunk
+            } else {
+                slice_chunk(&chunk, offset, length)
+            }));
+        }
+    }
+}
+
+impl LimitExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Limit".to_string()))?;
+        Ok(Box::new(LimitIterator {
+            input_iter: input.execute()?,
+            skip: self.skip,
+            remaining: self.fetch,
+        }))
+    }
+}
+
+/// Computes the indices that sort `columns` lexicographically.
+///
+/// arrow2's lexsort is not stable, rows with equal keys may come out in any
+/// order. With `stable` set the original row index is appended as an implicit
+/// last ascending key, so equal-key rows keep their input order. This costs an
+/// extra `u32` column of the input length plus one more comparison for every
+/// pair of rows whose keys tie.
+pub fn lexsort_to_indices(
+    columns: &[SortColumn],
+    stable: bool,
+) -> Result<PrimitiveArray<u32>, Error> {
+    if !stable {
+        return compute::sort::lexsort_to_indices::<u32>(columns, None).map_err(Error::ArrowError);
+    }
+    let length = columns.first().map(|x| x.values.len()).unwrap_or_default();
+    let row_index = PrimitiveArray::<u32>::from_vec((0..length as u32).collect());
+    let columns = columns
+        .iter()
+        .map(|column| SortColumn {
+            values: column.values,
+            options: column.options,
+        })
+        .chain(std::iter::once(SortColumn {
+            values: &row_index,
+            options: None,
+        }))
+        .collect::<Vec<_>>();
+    compute::sort::lexsort_to_indices::<u32>(&columns, None).map_err(Error::ArrowError)
+}
+
+/// Finalizes one accumulator per group into a single array holding the
+/// groups' results in order.
+pub fn finalize_accumulators(
+    accumulators: Vec<Box<dyn Accumulator>>,
+) -> Result<Arc<dyn Array>, Error> {
+    let scalars = accumulators
+        .into_iter()
+        .map(|acc| match acc.final_value()? {
+            ColumnarValue::Scalar(scalar) => Ok(scalar),
+            ColumnarValue::Array(array) => Ok(new_scalar(&*array, 0)),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    scalars_to_array(&scalars)
+}
+
+/// The accumulators of a group together with its one-row key arrays.
+type Group = (Vec<Box<dyn Accumulator>>, Vec<Arc<dyn Array>>);
+
 pub struct AggregateExec {
     input: Vec<PhysicalPlan>,
     schema: Schema,
@@ -263,14 +686,133 @@ impl AggregateExec {
         schema: Schema,
     ) -> Self {
         AggregateExec {
-            schema: schema,
-            input: input,
-            group_exprs: group_exprs,
-            agg_exprs: agg_exprs,
+            schema,
+            input,
+            group_exprs,
+            agg_exprs,
         }
     }
 }
 
+impl AggregateExec {
+    /// The key the input is sorted by when every aggregate is MIN or MAX of
+    /// the column it leads with, so that each batch only has to be read at its
+    /// ends. Float columns are left out because sorting puts NaN at an end,
+    /// where MIN and MAX may have to skip it.
+    pub(crate) fn extremum_key(&self) -> Option<SortKey> {
+        let input = self.input.last()?;
+        let key = *input.output_ordering().first()?;
+        let data_type = input.schema().fields.get(key.column)?.data_type();
+        let is_float = matches!(data_type, DataType::Float32 | DataType::Float64);
+        let all_extrema = self
+            .agg_exprs
+            .iter()
+            .all(|expr| expr.extremum_column() == Some(key.column));
+        (self.group_exprs.is_empty() && !self.agg_exprs.is_empty() && all_extrema && !is_float)
+            .then_some(key)
+    }
+}
+
+/// The first and last non-null rows of `chunk`, which is sorted by `key`, so
+/// the rows holding the smallest and largest value of the key column.
+pub fn extreme_rows(
+    chunk: &Chunk<Arc<dyn Array>>,
+    key: &SortKey,
+) -> Result<Chunk<Arc<dyn Array>>, Error> {
+    let column = chunk
+        .arrays()
+        .get(key.column)
+        .ok_or(Error::ExceedingBoundsError(key.column))?;
+    let nulls = column.null_count();
+    let (first, end) = if key.options.nulls_first {
+        (nulls as u32, column.len() as u32)
+    } else {
+        (0, (column.len() - nulls) as u32)
+    };
+    let indices = match end - first {
+        0 => vec![],
+        1 => vec![first],
+        _ => vec![first, end - 1],
+    };
+    take_chunk(chunk, &PrimitiveArray::from_vec(indices))
+}
+
+/// Whether row `i` of `left` equals row `j` of `right`, like comparing
+/// one-row slices of them but without allocating those. Two nulls are equal,
+/// and NaN is unequal to itself.
+fn values_equal(left: &dyn Array, i: usize, right: &dyn Array, j: usize) -> bool {
+    fn values<'a, A: 'static>(left: &'a dyn Array, right: &'a dyn Array) -> Option<(&'a A, &'a A)> {
+        Some((
+            left.as_any().downcast_ref::<A>()?,
+            right.as_any().downcast_ref::<A>()?,
+        ))
+    }
+    match (left.is_valid(i), right.is_valid(j)) {
+        (true, true) if left.data_type() == right.data_type() => {}
+        (false, false) => return left.data_type() == right.data_type(),
+        _ => return false,
+    }
+    let equal = match left.data_type().to_physical_type() {
+        PhysicalType::Boolean => {
+            values::<BooleanArray>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        PhysicalType::Primitive(PrimitiveType::Int32) => {
+            values::<PrimitiveArray<i32>>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        PhysicalType::Primitive(PrimitiveType::Int64) => {
+            values::<PrimitiveArray<i64>>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float32) => {
+            values::<PrimitiveArray<f32>>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            values::<PrimitiveArray<f64>>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        PhysicalType::Utf8 => {
+            values::<Utf8Array<i32>>(left, right).map(|(l, r)| l.value(i) == r.value(j))
+        }
+        _ => None,
+    };
+    equal.unwrap_or_else(|| left.slice(i, 1).as_ref() == right.slice(j, 1).as_ref())
+}
+
+/// The rows of `values` at `indices`. Scalars stay scalars.
+fn take_values(
+    values: &[ColumnarValue],
+    indices: &PrimitiveArray<u32>,
+) -> Result<Vec<ColumnarValue>, Error> {
+    values
+        .iter()
+        .map(|value| match value {
+            ColumnarValue::Array(array) => compute::take::take(array.as_ref(), indices)
+                .map(|array| ColumnarValue::Array(Arc::from(array)))
+                .map_err(Error::ArrowError),
+            ColumnarValue::Scalar(scalar) => Ok(ColumnarValue::Scalar(new_scalar(
+                scalar_to_array(scalar.as_ref(), 1)?.as_ref(),
+                0,
+            ))),
+        })
+        .collect()
+}
+
+/// Hashes each row of the group keys. Null keys hash apart from the values,
+/// and rows with equal keys have equal hashes.
+fn hash_rows(keys: &[Arc<dyn Array>], length: usize) -> Result<Vec<u64>, Error> {
+    const NULL_HASH: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut hashes = vec![0u64; length];
+    for key in keys {
+        let key_hashes = compute::hash::hash(key.as_ref()).map_err(Error::ArrowError)?;
+        for (i, hash) in hashes.iter_mut().enumerate() {
+            let key_hash = match key.is_valid(i) {
+                true => key_hashes.value(i),
+                false => NULL_HASH,
+            };
+            *hash = hash.wrapping_mul(31).wrapping_add(key_hash);
+        }
+    }
+    Ok(hashes)
+}
+
 pub struct AggregateIterator {
     output: Option<Batch>,
 }
@@ -289,202 +831,759 @@ impl AggregateExec {
     fn children(&self) -> Option<&[PhysicalPlan]> {
         Some(&self.input)
     }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
     fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let extremum_key = self.extremum_key();
         let mut vec = self.input;
         let input = vec
             .pop()
             .ok_or(Error::MissingInputPhysicalPlan("Aggregate".to_string()))?;
-        let mut hashmap = HashMap::new();
-        input
-            .execute()?
-            .map(|res| match res {
-                Ok(batch) => {
-                    let length = batch.len();
-                    let mut hashset = HashSet::new();
-                    let group_keys = Chunk::new(
-                        self.group_exprs
-                            .iter()
-                            .map(|expr| expr.evaluate(&batch).map(|x| x.to_array(length)))
-                            .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?,
-                    );
-                    let group_hashes = group_keys.iter().fold(
-                        compute::hash::hash_primitive::<u64>(&PrimitiveArray::new(
-                            DataType::UInt64,
-                            Buffer::new_zeroed(length),
-                            None,
-                        )),
-                        |acc: PrimitiveArray<u64>, x| {
-                            let new = compute::hash::hash(x.borrow()).unwrap();
-                            acc.add(&new)
-                        },
-                    );
-                    let agg_input = self
-                        .agg_exprs
+        // Maps a row hash to the positions in `groups` of the groups with that
+        // hash; `groups` keeps the groups in the order they were first seen.
+        let mut hashmap: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut groups: Vec<Group> = vec![];
+        for batch in input.execute()? {
+            let batch = batch?;
+            let batch = match &extremum_key {
+                Some(key) => extreme_rows(&batch, key)?,
+                None => batch,
+            };
+            let length = batch.len();
+            let group_keys = self
+                .group_exprs
+                .iter()
+                .map(|expr| expr.evaluate(&batch)?.into_array(length))
+                .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?;
+            let agg_input = self
+                .agg_exprs
+                .iter()
+                .map(|expr| expr.evaluate(&batch))
+                .collect::<Result<Vec<ColumnarValue>, Error>>()?;
+            // The groups of this batch in the order they are first seen, with
+            // the rows of each, and the position of each group in that list.
+            let mut batch_groups: Vec<(usize, Vec<u32>)> = vec![];
+            let mut positions: HashMap<usize, usize> = HashMap::new();
+            for (i, hash) in hash_rows(&group_keys, length)?.into_iter().enumerate() {
+                let bucket = hashmap.entry(hash).or_default();
+                let group = bucket.iter().copied().find(|group| {
+                    groups[*group]
+                        .1
                         .iter()
-                        .map(|expr| expr.evaluate(&batch))
-                        .collect::<Result<Vec<ColumnarValue>, Error>>()?;
-                    group_hashes
+                        .zip(&group_keys)
+                        .all(|(x, y)| values_equal(x.as_ref(), 0, y.as_ref(), i))
+                });
+                let group = group.unwrap_or_else(|| {
+                    let accumulators = self
+                        .agg_exprs
                         .iter()
                         .enumerate()
-                        .map(|(i, key)| match key {
-                            Some(key) => {
-                                if !hashset.contains(key) {
-                                    hashset.insert(key);
-                                    let validity = Bitmap::from_trusted_len_iter(
-                                        compute::comparison::eq_scalar(
-                                            &group_hashes,
-                                            &PrimitiveScalar::new(
-                                                DataType::UInt64,
-                                                Some(key.clone()),
-                                            ),
-                                        )
-                                        .values_iter(),
-                                    );
-                                    if !hashmap.contains_key(key) {
-                                        let accumulators = self
-                                            .agg_exprs
-                                            .iter()
-                                            .enumerate()
-                                            .map(|(i, x)| x.create_accumulator(i))
-                                            .collect::<Vec<_>>();
-                                        let group_keys = group_keys
-                                            .iter()
-                                            .map(|x| x.slice(i, 1))
-                                            .map(|y| Arc::from(y))
-                                            .collect::<Vec<_>>();
-                                        hashmap.insert(*key, (accumulators, group_keys));
-                                    }
-                                    hashmap
-                                        .get_mut(key)
-                                        .unwrap()
-                                        .0
-                                        .iter_mut()
-                                        .map(|acc| acc.accumulate(&agg_input, Some(&validity)))
-                                        .collect::<Result<Vec<()>, Error>>()?;
-                                };
-                                Ok(())
-                            }
-                            None => Ok(()),
-                        })
-                        .collect::<Result<Vec<()>, Error>>()?;
-                    Ok(())
-                }
-                Err(e) => Err(e),
-            })
-            .collect::<Result<Vec<()>, Error>>()?;
-        let rows = hashmap.len();
-        let mut iter = hashmap.into_values();
-        let (accs, mut groups) = iter.next().ok_or(Error::EmptyHashmapForAggregate)?;
-        accs.into_iter()
-            .map(|x| {
-                groups.push(x.final_value()?.to_array(1));
-                Ok(())
-            })
-            .collect::<Result<Vec<()>, Error>>()?;
-        let mut columns = groups
-            .into_iter()
-            .map(|col| match col.data_type().to_physical_type() {
-                PhysicalType::Primitive(PrimitiveType::Int32) => {
-                    let mut mutable_array =
-                        MutablePrimitiveArray::with_capacity_from(rows, DataType::Int32);
-                    mutable_array.extend_from_slice(
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<i32>>()
-                            .unwrap()
-                            .values(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Primitive(PrimitiveType::Float64) => {
-                    let mut mutable_array =
-                        MutablePrimitiveArray::with_capacity_from(rows, DataType::Float64);
-                    mutable_array.extend_from_slice(
-                        col.as_any()
-                            .downcast_ref::<PrimitiveArray<f64>>()
-                            .unwrap()
-                            .values(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Utf8 => {
-                    let mut mutable_array = MutableUtf8Array::<i32>::with_capacity(rows);
-                    mutable_array.extend_trusted_len(
-                        col.as_any()
-                            .downcast_ref::<Utf8Array<i32>>()
-                            .unwrap()
-                            .iter(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
-                }
-                PhysicalType::Boolean => {
-                    let mut mutable_array = MutableBooleanArray::with_capacity(rows);
-                    mutable_array.extend_trusted_len(
-                        col.as_any().downcast_ref::<BooleanArray>().unwrap().iter(),
-                    );
-                    Ok(Box::new(mutable_array) as Box<dyn MutableArray>)
+                        .map(|(i, x)| x.create_accumulator(i))
+                        .collect::<Vec<_>>();
+                    let row_keys = group_keys
+                        .iter()
+                        .map(|x| Arc::from(x.slice(i, 1)))
+                        .collect::<Vec<Arc<dyn Array>>>();
+                    bucket.push(groups.len());
+                    groups.push((accumulators, row_keys));
+                    groups.len() - 1
+                });
+                let position = *positions.entry(group).or_insert_with(|| {
+                    batch_groups.push((group, vec![]));
+                    batch_groups.len() - 1
+                });
+                batch_groups[position].1.push(i as u32);
+            }
+            for (group, rows) in batch_groups {
+                let mut accumulators = groups[group].0.iter_mut();
+                if rows.len() == length {
+                    accumulators.try_for_each(|acc| acc.accumulate(&agg_input, None))?;
+                } else {
+                    let input = take_values(&agg_input, &PrimitiveArray::from_vec(rows))?;
+                    accumulators.try_for_each(|acc| acc.accumulate(&input, None))?;
                 }
-                t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+            }
+        }
+        if groups.is_empty() {
+            // Grouping no rows gives no groups, so an empty batch.
+            if !self.group_exprs.is_empty() {
+                let columns = self
+                    .schema
+                    .fields
+                    .iter()
+                    .map(|field| Arc::from(new_empty_array(field.data_type().clone())))
+                    .collect::<Vec<_>>();
+                return Ok(Box::new(AggregateIterator {
+                    output: Some(Ok(Chunk::new(columns))),
+                }));
+            }
+            // Without group keys the whole input is one group, which SQL keeps
+            // even when it is empty: `COUNT(*)` over no rows is one row of 0.
+            let accumulators = self
+                .agg_exprs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| x.create_accumulator(i))
+                .collect::<Vec<_>>();
+            groups.push((accumulators, vec![]));
+        }
+        let (accumulators, group_keys): (Vec<_>, Vec<_>) = groups.into_iter().unzip();
+        let mut columns = (0..self.group_exprs.len())
+            .map(|i| {
+                let keys = group_keys
+                    .iter()
+                    .map(|keys: &Vec<Arc<dyn Array>>| keys[i].as_ref())
+                    .collect::<Vec<&dyn Array>>();
+                compute::concatenate::concatenate(&keys)
+                    .map(Arc::from)
+                    .map_err(Error::ArrowError)
             })
-            .collect::<Result<Vec<_>, _>>()?;
-        iter.for_each(|(accs, mut groups)| {
+            .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?;
+        let mut per_expr = self
+            .agg_exprs
+            .iter()
+            .map(|_| Vec::with_capacity(accumulators.len()))
+            .collect::<Vec<_>>();
+        accumulators.into_iter().for_each(|accs| {
             accs.into_iter()
-                .for_each(|x| groups.push(x.final_value().unwrap().to_array(1)));
-            groups
-                .into_iter()
-                .zip(columns.iter_mut())
-                .for_each(|(new, col)| match col.data_type().to_physical_type() {
-                    PhysicalType::Primitive(PrimitiveType::Int32) => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutablePrimitiveArray<i32>>()
-                            .unwrap()
-                            .extend_from_slice(
-                                new.as_any()
-                                    .downcast_ref::<PrimitiveArray<i32>>()
-                                    .unwrap()
-                                    .values(),
-                            );
-                    }
-                    PhysicalType::Primitive(PrimitiveType::Float64) => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutablePrimitiveArray<f64>>()
-                            .unwrap()
-                            .extend_from_slice(
-                                new.as_any()
-                                    .downcast_ref::<PrimitiveArray<f64>>()
-                                    .unwrap()
-                                    .values(),
-                            );
-                    }
-                    PhysicalType::Utf8 => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutableUtf8Array<i32>>()
-                            .unwrap()
-                            .extend_trusted_len(
-                                new.as_any()
-                                    .downcast_ref::<Utf8Array<i32>>()
-                                    .unwrap()
-                                    .iter(),
-                            );
-                    }
-                    PhysicalType::Boolean => {
-                        col.as_mut_any()
-                            .downcast_mut::<MutableBooleanArray>()
-                            .unwrap()
-                            .extend_trusted_len(
-                                new.as_any().downcast_ref::<BooleanArray>().unwrap().iter(),
-                            );
-                    }
-                    _ => (),
-                });
+                .zip(per_expr.iter_mut())
+                .for_each(|(acc, column)| column.push(acc))
         });
-        let columns = Chunk::new(
-            columns
-                .into_iter()
-                .map(|mut col| col.as_arc())
-                .collect::<Vec<Arc<_>>>(),
-        );
+        for accs in per_expr {
+            let column = finalize_accumulators(accs)?;
+            // Groups where every accumulator saw no values finalize untyped.
+            let column = match (column.data_type(), self.schema.fields.get(columns.len())) {
+                (DataType::Null, Some(field)) if field.data_type() != &DataType::Null => {
+                    Arc::from(new_null_array(field.data_type().clone(), column.len()))
+                }
+                _ => column,
+            };
+            columns.push(column);
+        }
+        let columns = Chunk::new(columns);
         Ok(Box::new(AggregateIterator {
             output: Some(Ok(columns)),
         }))
     }
 }
+
+/// Evaluates window functions over partitions of its input.
+///
+/// The whole input is buffered and sorted by the partition keys and then the
+/// order keys, so the output comes as a single batch in that order. It holds
+/// the input columns followed by one column per window function. The sort is
+/// skipped when the keys are bare columns the input is already ordered by.
+pub struct WindowExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    partition_by: Vec<Box<dyn PhysicalExpression>>,
+    order_by: Vec<(Box<dyn PhysicalExpression>, SortOptions)>,
+    window_functions: Vec<Box<dyn WindowFunction>>,
+    stable: bool,
+}
+
+impl WindowExec {
+    pub fn new(
+        input: Vec<PhysicalPlan>,
+        partition_by: Vec<Box<dyn PhysicalExpression>>,
+        order_by: Vec<(Box<dyn PhysicalExpression>, SortOptions)>,
+        window_functions: Vec<Box<dyn WindowFunction>>,
+        schema: Schema,
+    ) -> Self {
+        WindowExec {
+            input,
+            schema,
+            partition_by,
+            order_by,
+            window_functions,
+            stable: true,
+        }
+    }
+
+    /// Whether rows with equal partition and order keys keep their input
+    /// order, see [`lexsort_to_indices`]. Defaults to `true`.
+    pub fn with_stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
+
+    /// The partition keys, ascending with nulls first, and then the order
+    /// keys, if there are any and all of them are bare columns.
+    fn sort_keys(&self) -> Option<Vec<SortKey>> {
+        let partition_keys = self
+            .partition_by
+            .iter()
+            .map(|expr| sort_key(expr.as_ref(), SortOptions::default()));
+        let order_keys = self
+            .order_by
+            .iter()
+            .map(|(expr, options)| sort_key(expr.as_ref(), *options));
+        partition_keys
+            .chain(order_keys)
+            .collect::<Option<Vec<_>>>()
+            .filter(|keys| !keys.is_empty())
+    }
+}
+
+/// Concatenates `batches` column by column into a single chunk.
+fn concatenate_batches(batches: &[Chunk<Arc<dyn Array>>]) -> Result<Chunk<Arc<dyn Array>>, Error> {
+    let columns = batches
+        .first()
+        .map(|batch| batch.arrays().len())
+        .unwrap_or_default();
+    (0..columns)
+        .map(|i| {
+            let arrays = batches
+                .iter()
+                .map(|batch| batch[i].as_ref())
+                .collect::<Vec<&dyn Array>>();
+            compute::concatenate::concatenate(&arrays)
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+        })
+        .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
+        .map(Chunk::new)
+}
+
+/// Reorders every column of `chunk` by `indices`.
+fn take_chunk(
+    chunk: &Chunk<Arc<dyn Array>>,
+    indices: &PrimitiveArray<u32>,
+) -> Result<Chunk<Arc<dyn Array>>, Error> {
+    chunk
+        .arrays()
+        .iter()
+        .map(|array| {
+            compute::take::take(array.as_ref(), indices)
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+        })
+        .collect::<Result<Vec<Arc<dyn Array>>, Error>>()
+        .map(Chunk::new)
+}
+
+impl WindowExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let sort_keys = self.sort_keys();
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Window".to_string()))?;
+        let sorted =
+            sort_keys.is_some_and(|keys| ordering_satisfies(input.output_ordering(), &keys));
+        let batches = input.execute()?.collect::<Result<Vec<_>, Error>>()?;
+        if batches.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let input = concatenate_batches(&batches)?;
+        let length = input.len();
+        let evaluate = |expr: &dyn PhysicalExpression| expr.evaluate(&input)?.into_array(length);
+        let partition_keys = self
+            .partition_by
+            .iter()
+            .map(|expr| evaluate(expr.as_ref()))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let order_keys = self
+            .order_by
+            .iter()
+            .map(|(expr, options)| evaluate(expr.as_ref()).map(|key| (key, *options)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let sort_columns = partition_keys
+            .iter()
+            .map(|key| SortColumn {
+                values: key.as_ref(),
+                options: None,
+            })
+            .chain(order_keys.iter().map(|(key, options)| SortColumn {
+                values: key.as_ref(),
+                options: Some(*options),
+            }))
+            .collect::<Vec<_>>();
+        let (input, partition_keys) = if sorted || sort_columns.is_empty() {
+            (input, Chunk::new(partition_keys))
+        } else {
+            let indices = lexsort_to_indices(&sort_columns, self.stable)?;
+            (
+                take_chunk(&input, &indices)?,
+                take_chunk(&Chunk::new(partition_keys), &indices)?,
+            )
+        };
+        // A partition starts at every row whose keys differ from the previous row.
+        let mut starts = vec![0];
+        starts.extend((1..length).filter(|&row| {
+            partition_keys
+                .arrays()
+                .iter()
+                .any(|key| key.slice(row, 1) != key.slice(row - 1, 1))
+        }));
+        starts.push(length);
+        let partitions = starts
+            .windows(2)
+            .map(|bounds| slice_chunk(&input, bounds[0], bounds[1] - bounds[0]))
+            .collect::<Vec<_>>();
+        let mut columns = input.into_arrays();
+        for function in &self.window_functions {
+            let values = partitions
+                .iter()
+                .map(|partition| function.evaluate_partition(partition))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let values = values
+                .iter()
+                .map(|x| x.as_ref())
+                .collect::<Vec<&dyn Array>>();
+            columns.push(Arc::from(
+                compute::concatenate::concatenate(&values).map_err(Error::ArrowError)?,
+            ));
+        }
+        Ok(Box::new(std::iter::once(Ok(Chunk::new(columns)))))
+    }
+}
+
+/// Sorts its whole input by `keys` into a single batch. Rows with equal keys
+/// keep their input order. The keys are evaluated on the concatenated input,
+/// so they can be computed expressions as well as columns.
+///
+/// All input batches are concatenated before sorting, so the input is held in
+/// memory twice over while the sorted copy is built.
+pub struct SortExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    keys: Vec<SortExpression>,
+    /// The leading keys that are plain columns, the order the output is
+    /// known to come in.
+    ordering: Vec<SortKey>,
+    stable: bool,
+}
+
+impl SortExec {
+    pub fn new(input: Vec<PhysicalPlan>, keys: Vec<SortExpression>, schema: Schema) -> Self {
+        let ordering = keys.iter().map_while(SortExpression::sort_key).collect();
+        SortExec {
+            input,
+            schema,
+            keys,
+            ordering,
+            stable: true,
+        }
+    }
+
+    /// Whether rows with equal keys keep their input order, see
+    /// [`lexsort_to_indices`]. Defaults to `true`; an unstable sort skips the
+    /// implicit row index key.
+    pub fn with_stable(mut self, stable: bool) -> Self {
+        self.stable = stable;
+        self
+    }
+}
+
+impl SortExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Sort".to_string()))?;
+        let batches = input.execute()?.collect::<Result<Vec<_>, Error>>()?;
+        if batches.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let input = concatenate_batches(&batches)?;
+        let values = self
+            .keys
+            .iter()
+            .map(|key| key.expr.evaluate(&input)?.into_array(input.len()))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let columns = values
+            .iter()
+            .zip(&self.keys)
+            .map(|(values, key)| SortColumn {
+                values: values.as_ref(),
+                options: Some(key.options),
+            })
+            .collect::<Vec<_>>();
+        let output = if columns.is_empty() {
+            input
+        } else {
+            take_chunk(&input, &lexsort_to_indices(&columns, self.stable)?)?
+        };
+        Ok(Box::new(std::iter::once(Ok(output))))
+    }
+}
+
+/// Inner equi-join of its two inputs on `left_key = right_key`.
+///
+/// The whole left input is buffered and hashed on its key, then each batch of
+/// the right input is probed against it, so the left input should be the
+/// smaller one. The output holds the left columns followed by the right ones,
+/// one batch per right batch. Null keys never match.
+pub struct HashJoinExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+    left_key: Box<dyn PhysicalExpression>,
+    right_key: Box<dyn PhysicalExpression>,
+}
+
+impl HashJoinExec {
+    pub fn new(
+        left: PhysicalPlan,
+        right: PhysicalPlan,
+        left_key: Box<dyn PhysicalExpression>,
+        right_key: Box<dyn PhysicalExpression>,
+        schema: Schema,
+    ) -> Self {
+        HashJoinExec {
+            input: vec![left, right],
+            schema,
+            left_key,
+            right_key,
+        }
+    }
+}
+
+/// The buffered left input of a hash join, with its rows by key hash.
+struct JoinTable {
+    batch: Chunk<Arc<dyn Array>>,
+    keys: Arc<dyn Array>,
+    rows: HashMap<u64, Vec<u32>>,
+}
+
+pub struct HashJoinIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    table: JoinTable,
+    key: Box<dyn PhysicalExpression>,
+}
+
+impl<I: Iterator<Item = Batch>> HashJoinIterator<I> {
+    fn probe(&self, batch: Chunk<Arc<dyn Array>>) -> Batch {
+        let keys = self.key.evaluate(&batch)?.into_array(batch.len())?;
+        if keys.data_type() != self.table.keys.data_type() {
+            return Err(Error::IncompatibleTypes(
+                format!("{:?}", self.table.keys.data_type()),
+                format!("{:?}", keys.data_type()),
+                Operator::Eq,
+            ));
+        }
+        let mut left = vec![];
+        let mut right = vec![];
+        let hashes = hash_rows(std::slice::from_ref(&keys), batch.len())?;
+        for (i, hash) in hashes.iter().enumerate() {
+            if let (true, Some(rows)) = (keys.is_valid(i), self.table.rows.get(hash)) {
+                left.extend_from_slice(rows);
+                right.extend(std::iter::repeat_n(i as u32, rows.len()));
+            }
+        }
+        // Equal hashes don't imply equal keys, so the candidate pairs are
+        // checked with the comparison kernel.
+        let left = PrimitiveArray::from_vec(left);
+        let right = PrimitiveArray::from_vec(right);
+        let left_keys =
+            compute::take::take(self.table.keys.as_ref(), &left).map_err(Error::ArrowError)?;
+        let right_keys = compute::take::take(keys.as_ref(), &right).map_err(Error::ArrowError)?;
+        let matches = compute::comparison::eq(left_keys.as_ref(), right_keys.as_ref());
+        let left = compute::filter::filter(&left, &matches).map_err(Error::ArrowError)?;
+        let right = compute::filter::filter(&right, &matches).map_err(Error::ArrowError)?;
+        let left = left.as_any().downcast_ref().ok_or(Error::DowncastError)?;
+        let right = right.as_any().downcast_ref().ok_or(Error::DowncastError)?;
+        let mut columns = take_chunk(&self.table.batch, left)?.into_arrays();
+        columns.extend(take_chunk(&batch, right)?.into_arrays());
+        Ok(Chunk::new(columns))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for HashJoinIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.input_iter.next()?;
+        Some(batch.and_then(|batch| self.probe(batch)))
+    }
+}
+
+impl HashJoinExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let missing = || Error::MissingInputPhysicalPlan("HashJoin".to_string());
+        let right = vec.pop().ok_or_else(missing)?;
+        let left = vec.pop().ok_or_else(missing)?;
+        let batches = left.execute()?.collect::<Result<Vec<_>, Error>>()?;
+        if batches.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        let batch = concatenate_batches(&batches)?;
+        let keys = self.left_key.evaluate(&batch)?.into_array(batch.len())?;
+        let mut rows: HashMap<u64, Vec<u32>> = HashMap::new();
+        let hashes = hash_rows(std::slice::from_ref(&keys), batch.len())?;
+        for (i, hash) in hashes.into_iter().enumerate() {
+            if keys.is_valid(i) {
+                rows.entry(hash).or_default().push(i as u32);
+            }
+        }
+        Ok(Box::new(HashJoinIterator {
+            input_iter: right.execute()?,
+            table: JoinTable { batch, keys, rows },
+            key: self.right_key,
+        }))
+    }
+}
+
+/// The Cartesian product of its two inputs, with the left columns followed by
+/// the right ones.
+///
+/// The right input is buffered, the left one streamed: each left batch is
+/// paired with every right batch in turn, one output batch per pair, so the
+/// full product is never held in memory at once.
+pub struct CrossJoinExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+}
+
+impl CrossJoinExec {
+    pub fn new(left: PhysicalPlan, right: PhysicalPlan, schema: Schema) -> Self {
+        CrossJoinExec {
+            input: vec![left, right],
+            schema,
+        }
+    }
+}
+
+pub struct CrossJoinIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    right: Vec<Chunk<Arc<dyn Array>>>,
+    /// The left batch being paired and the next right batch to pair it with.
+    current: Option<(Chunk<Arc<dyn Array>>, usize)>,
+}
+
+/// Every row of `left` paired with every row of `right`.
+fn cross_product(
+    left: &Chunk<Arc<dyn Array>>,
+    right: &Chunk<Arc<dyn Array>>,
+) -> Result<Chunk<Arc<dyn Array>>, Error> {
+    let (n, m) = (left.len() as u32, right.len() as u32);
+    let left_indices = (0..n).flat_map(|i| std::iter::repeat_n(i, m as usize));
+    let right_indices = (0..n).flat_map(|_| 0..m);
+    let left = take_chunk(left, &PrimitiveArray::from_vec(left_indices.collect()))?;
+    let right = take_chunk(right, &PrimitiveArray::from_vec(right_indices.collect()))?;
+    let mut columns = left.into_arrays();
+    columns.extend(right.into_arrays());
+    Ok(Chunk::new(columns))
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for CrossJoinIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((left, next)) = &mut self.current {
+                if let Some(right) = self.right.get(*next) {
+                    *next += 1;
+                    return Some(cross_product(left, right));
+                }
+            }
+            match self.input_iter.next()? {
+                Ok(left) => self.current = Some((left, 0)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+impl CrossJoinExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let missing = || Error::MissingInputPhysicalPlan("CrossJoin".to_string());
+        let right = vec.pop().ok_or_else(missing)?;
+        let left = vec.pop().ok_or_else(missing)?;
+        let right = right.execute()?.collect::<Result<Vec<_>, Error>>()?;
+        Ok(Box::new(CrossJoinIterator {
+            input_iter: left.execute()?,
+            right,
+            current: None,
+        }))
+    }
+}
+
+/// Removes duplicate rows, comparing all columns, with nulls equal to nulls.
+/// The first occurrence of each row is kept, in input order.
+///
+/// Every input batch with a row that was kept stays in memory, so that later
+/// rows can be compared against it.
+pub struct DistinctExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+}
+
+impl DistinctExec {
+    pub fn new(input: Vec<PhysicalPlan>, schema: Schema) -> Self {
+        DistinctExec { input, schema }
+    }
+}
+
+pub struct DistinctIterator<I: Iterator<Item = Batch>> {
+    input_iter: I,
+    /// The batches holding the rows seen so far.
+    batches: Vec<Chunk<Arc<dyn Array>>>,
+    /// Maps a row hash to the batch and row of each distinct row with it.
+    seen: HashMap<u64, Vec<(usize, usize)>>,
+}
+
+fn rows_equal(
+    left: &Chunk<Arc<dyn Array>>,
+    left_row: usize,
+    right: &Chunk<Arc<dyn Array>>,
+    right_row: usize,
+) -> bool {
+    left.arrays()
+        .iter()
+        .zip(right.arrays())
+        .all(|(x, y)| values_equal(x.as_ref(), left_row, y.as_ref(), right_row))
+}
+
+impl<I: Iterator<Item = Batch>> DistinctIterator<I> {
+    fn deduplicate(&mut self, chunk: Chunk<Arc<dyn Array>>) -> Batch {
+        let index = self.batches.len();
+        let hashes = hash_rows(chunk.arrays(), chunk.len())?;
+        self.batches.push(chunk);
+        let mut keep = MutableBitmap::with_capacity(hashes.len());
+        for (row, hash) in hashes.into_iter().enumerate() {
+            let rows = self.seen.entry(hash).or_default();
+            let batches = &self.batches;
+            let duplicate = rows
+                .iter()
+                .any(|&(batch, other)| rows_equal(&batches[batch], other, &batches[index], row));
+            if !duplicate {
+                rows.push((index, row));
+            }
+            keep.push(!duplicate);
+        }
+        let keep = BooleanArray::new(DataType::Boolean, keep.into(), None);
+        let chunk = &self.batches[index];
+        let output = compute::filter::filter_chunk(chunk, &keep).map_err(Error::ArrowError)?;
+        if output.is_empty() {
+            self.batches.pop();
+        }
+        Ok(Chunk::new(
+            output.into_arrays().into_iter().map(Arc::from).collect(),
+        ))
+    }
+}
+
+impl<I: Iterator<Item = Batch>> Iterator for DistinctIterator<I> {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = self.input_iter.next()?;
+        Some(batch.and_then(|chunk| self.deduplicate(chunk)))
+    }
+}
+
+impl DistinctExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let input = vec
+            .pop()
+            .ok_or(Error::MissingInputPhysicalPlan("Distinct".to_string()))?;
+        Ok(Box::new(DistinctIterator {
+            input_iter: input.execute()?,
+            batches: vec![],
+            seen: HashMap::new(),
+        }))
+    }
+}
+
+/// The rows of its left input followed by those of its right input, like
+/// SQL's `UNION ALL`. [`UnionExec::distinct`] gives `UNION`.
+pub struct UnionExec {
+    input: Vec<PhysicalPlan>,
+    schema: Schema,
+}
+
+impl UnionExec {
+    /// Errors unless both inputs have the same column names and types in the
+    /// same order. A column is nullable if it is in either input.
+    pub fn try_new(left: PhysicalPlan, right: PhysicalPlan) -> Result<Self, Error> {
+        let schema = left.schema().union(right.schema())?;
+        let mut fields = left.schema().fields.iter().zip(&right.schema().fields);
+        if let Some((left, right)) = fields.find(|(l, r)| l.name != r.name) {
+            return Err(Error::InvalidArgument(format!(
+                "union inputs have column `{}` where the other has `{}`",
+                left.name, right.name
+            )));
+        }
+        Ok(UnionExec {
+            input: vec![left, right],
+            schema,
+        })
+    }
+
+    /// Removes duplicate rows from the union, like SQL's `UNION`.
+    pub fn distinct(self) -> PhysicalPlan {
+        let schema = self.schema.clone();
+        PhysicalPlan::Distinct(DistinctExec::new(vec![PhysicalPlan::Union(self)], schema))
+    }
+}
+
+impl UnionExec {
+    fn schema(&self) -> &Schema {
+        &self.schema
+    }
+    fn children(&self) -> Option<&[PhysicalPlan]> {
+        Some(&self.input)
+    }
+    fn children_mut(&mut self) -> Option<&mut Vec<PhysicalPlan>> {
+        Some(&mut self.input)
+    }
+    fn execute(self) -> Result<Box<dyn Iterator<Item = Batch>>, Error> {
+        let mut vec = self.input;
+        let missing = || Error::MissingInputPhysicalPlan("Union".to_string());
+        let right = vec.pop().ok_or_else(missing)?;
+        let left = vec.pop().ok_or_else(missing)?;
+        // The right input only starts executing once the left one is done.
+        let right = std::iter::once(right).flat_map(|right| match right.execute() {
+            Ok(batches) => batches,
+            Err(err) => Box::new(std::iter::once(Err(err))),
+        });
+        Ok(Box::new(left.execute()?.chain(right)))
+    }
+}
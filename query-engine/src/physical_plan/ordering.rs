@@ -0,0 +1,100 @@
+use arrow2::compute::sort::SortOptions;
+
+use super::{
+    physical_expressions::{ColumnExpression, PhysicalExpression},
+    PhysicalPlan, SortExec,
+};
+
+/// One key of a row ordering: a column of the plan's output and its direction.
+#[derive(Clone, Copy, Debug)]
+pub struct SortKey {
+    pub column: usize,
+    pub options: SortOptions,
+}
+
+impl SortKey {
+    pub fn new(column: usize, options: SortOptions) -> Self {
+        SortKey { column, options }
+    }
+}
+
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.column == other.column
+            && self.options.descending == other.options.descending
+            && self.options.nulls_first == other.options.nulls_first
+    }
+}
+
+/// One key of a sort: an expression evaluated on the input and its direction.
+pub struct SortExpression {
+    pub expr: Box<dyn PhysicalExpression>,
+    pub options: SortOptions,
+}
+
+impl SortExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, options: SortOptions) -> Self {
+        SortExpression { expr, options }
+    }
+
+    /// A key on the input column at `index`.
+    pub fn column(index: usize, options: SortOptions) -> Self {
+        SortExpression::new(Box::new(ColumnExpression { index }), options)
+    }
+
+    /// The key as a column ordering, `None` if it is computed.
+    pub fn sort_key(&self) -> Option<SortKey> {
+        sort_key(self.expr.as_ref(), self.options)
+    }
+}
+
+/// `expr` in the direction of `options` as a column ordering, `None` if it
+/// isn't a bare column.
+pub fn sort_key(expr: &dyn PhysicalExpression, options: SortOptions) -> Option<SortKey> {
+    expr.column_index()
+        .map(|column| SortKey::new(column, options))
+}
+
+impl From<SortKey> for SortExpression {
+    fn from(key: SortKey) -> Self {
+        SortExpression::column(key.column, key.options)
+    }
+}
+
+/// Whether rows ordered by `provided` are also ordered by `required`, which
+/// holds when `required` is a prefix of `provided`.
+pub fn ordering_satisfies(provided: &[SortKey], required: &[SortKey]) -> bool {
+    required.len() <= provided.len() && provided.iter().zip(required).all(|(p, r)| p == r)
+}
+
+/// Returns `input` ordered by `required`, adding a sort only when the input
+/// doesn't already come in that order.
+pub fn ensure_input_ordering(input: PhysicalPlan, required: &[SortKey]) -> PhysicalPlan {
+    if ordering_satisfies(input.output_ordering(), required) {
+        return input;
+    }
+    let schema = input.schema().clone();
+    let keys = required.iter().copied().map(SortExpression::from).collect();
+    PhysicalPlan::Sort(SortExec::new(vec![input], keys, schema))
+}
+
+/// Walks `plan` and sorts the input of every operator whose
+/// `required_input_ordering` its child doesn't already provide.
+pub fn enforce_ordering(mut plan: PhysicalPlan) -> PhysicalPlan {
+    let required = plan.required_input_ordering();
+    if let Some(children) = plan.children_mut() {
+        let inputs = std::mem::take(children);
+        *children = inputs
+            .into_iter()
+            .zip(required.into_iter().chain(std::iter::repeat(None)))
+            .map(|(child, required)| {
+                let child = enforce_ordering(child);
+                match required {
+                    Some(required) => ensure_input_ordering(child, &required),
+                    None => child,
+                }
+            })
+            .collect();
+    }
+    plan
+}
@@ -0,0 +1,51 @@
+//! Batch-parallel execution on rayon's thread pool, behind the `rayon` feature.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use arrow2::{array::Array, chunk::Chunk};
+use rayon::prelude::*;
+
+use super::Batch;
+
+/// Maps the batches of `input` with `f` on rayon's thread pool.
+///
+/// The input is pulled a window of one batch per thread at a time, so at most
+/// that many batches are buffered, and the results come out in input order.
+pub struct ParallelMap<I, F> {
+    input: I,
+    f: F,
+    window: usize,
+    output: VecDeque<Batch>,
+}
+
+impl<I, F> ParallelMap<I, F> {
+    pub fn new(input: I, f: F) -> Self {
+        ParallelMap {
+            input,
+            f,
+            window: rayon::current_num_threads(),
+            output: VecDeque::new(),
+        }
+    }
+}
+
+impl<I, F> Iterator for ParallelMap<I, F>
+where
+    I: Iterator<Item = Batch>,
+    F: Fn(Chunk<Arc<dyn Array>>) -> Batch + Sync,
+{
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.output.is_empty() {
+            let window = self.input.by_ref().take(self.window).collect::<Vec<_>>();
+            let f = &self.f;
+            self.output = window
+                .into_par_iter()
+                .map(|batch| batch.and_then(f))
+                .collect::<Vec<_>>()
+                .into();
+        }
+        self.output.pop_front()
+    }
+}
@@ -1,26 +1,242 @@
-use std::borrow::Borrow;
+use std::any::Any;
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Display};
-use std::ops::{Add, Div, Mul, Sub};
+use std::ops::{Add, Div, Mul, Rem, Sub};
 use std::sync::Arc;
 
-use arrow2::array::{BooleanArray, Utf8Array};
-use arrow2::bitmap::Bitmap;
+use arrow2::array::{
+    new_null_array, BooleanArray, MutableArray, MutablePrimitiveArray, StructArray, Utf8Array,
+};
+use arrow2::bitmap::{Bitmap, MutableBitmap};
 use arrow2::chunk::Chunk;
-use arrow2::datatypes::{DataType, PhysicalType};
-use arrow2::scalar::{BooleanScalar, NullScalar, PrimitiveScalar, Scalar};
+use arrow2::compute::cast::CastOptions;
+use arrow2::datatypes::{DataType, Field, PhysicalType, Schema};
+use arrow2::error::ArrowError;
+use arrow2::scalar::{new_scalar, BooleanScalar, NullScalar, PrimitiveScalar, Scalar};
+use arrow2::types::NativeType;
 use arrow2::{
     array::{Array, PrimitiveArray},
     compute,
     datatypes::PrimitiveType,
     scalar::Utf8Scalar,
 };
+use regex::Regex;
 use std::ops::BitAnd;
 
-use crate::columnar_value::ColumnarValue;
+use crate::columnar_value::{scalar_to_array, scalars_to_array, ColumnarValue};
+use crate::config::scalar_broadcast_threshold;
 use crate::error::Error;
+use crate::operator::Operator;
+use crate::record_batch::RecordBatch;
+use crate::type_coercion::coerce_types;
 
-pub trait PhysicalExpression: Display {
+pub trait PhysicalExpression: Display + Send + Sync {
     fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error>;
+
+    /// Evaluates against the columns of `batch`.
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ColumnarValue, Error> {
+        self.evaluate(batch.columns())
+    }
+
+    /// Evaluates into `out`, replacing its contents, so that a loop over many
+    /// same-shaped batches can reuse one output buffer. `out` decides the
+    /// output type, Int32 and Float64 are supported.
+    ///
+    /// The default evaluates and copies the result. Arithmetic expressions
+    /// write into `out` directly and don't allocate once it has grown to the
+    /// batch size.
+    fn evaluate_into(
+        &self,
+        input: &Chunk<Arc<dyn Array>>,
+        out: &mut dyn MutableArray,
+    ) -> Result<(), Error> {
+        copy_value_into(self.evaluate(input)?.into_array(input.len())?, out)
+    }
+
+    /// Whether `evaluate_into` writes into `out` directly instead of copying
+    /// the result of `evaluate`.
+    fn evaluates_in_place(&self) -> bool {
+        false
+    }
+
+    /// Whether the expression reads no columns, so that it evaluates to the
+    /// same scalar for every batch.
+    fn is_constant(&self) -> bool {
+        false
+    }
+
+    /// The input column the expression returns unchanged, if it is a bare
+    /// column reference.
+    fn column_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Evaluates a constant expression without any input, or returns `None`
+    /// if the expression isn't constant.
+    fn evaluate_scalar(&self) -> Result<Option<Box<dyn Scalar>>, Error> {
+        if !self.is_constant() {
+            return Ok(None);
+        }
+        match self.evaluate(&Chunk::new(vec![]))? {
+            ColumnarValue::Scalar(scalar) => Ok(Some(scalar)),
+            ColumnarValue::Array(_) => Ok(None),
+        }
+    }
+}
+
+fn downcast_mut<T: NativeType>(
+    out: &mut dyn MutableArray,
+) -> Result<&mut MutablePrimitiveArray<T>, Error> {
+    out.as_mut_any()
+        .downcast_mut::<MutablePrimitiveArray<T>>()
+        .ok_or(Error::DowncastError)
+}
+
+/// Clears `out` while keeping its allocation, returning its values buffer.
+fn take_values<T: NativeType>(out: &mut MutablePrimitiveArray<T>) -> (DataType, Vec<T>) {
+    let (data_type, mut values, _) = std::mem::take(out).into_data();
+    values.clear();
+    (data_type, values)
+}
+
+fn copy_into<T: NativeType>(
+    array: &dyn Array,
+    out: &mut MutablePrimitiveArray<T>,
+) -> Result<(), Error> {
+    let array = array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<T>>()
+        .ok_or(Error::DowncastError)?;
+    let (data_type, mut values) = take_values(out);
+    values.extend_from_slice(array.values());
+    let validity = array
+        .validity()
+        .map(|validity| validity.iter().collect::<MutableBitmap>());
+    *out = MutablePrimitiveArray::from_data(data_type, values, validity);
+    Ok(())
+}
+
+/// Writes `op(left, right)` into `out`, reusing its buffers. Like arrow2's
+/// kernels, `op` is skipped on rows where `right` is null so that null slots
+/// never reach a division.
+fn binary_into<T: NativeType>(
+    out: &mut MutablePrimitiveArray<T>,
+    left: &PrimitiveArray<T>,
+    right: &ColumnarValue,
+    op: impl Fn(T, T) -> T,
+) -> Result<(), Error> {
+    let (data_type, mut values) = take_values(out);
+    let validity = match right {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar
+                .as_any()
+                .downcast_ref::<PrimitiveScalar<T>>()
+                .ok_or(Error::DowncastError)?;
+            match scalar.value() {
+                Some(right) => {
+                    values.extend(left.values().iter().map(|left| op(*left, right)));
+                    left.validity().map(|validity| validity.iter().collect())
+                }
+                None => {
+                    values.extend_from_slice(left.values());
+                    Some(MutableBitmap::from_len_zeroed(left.len()))
+                }
+            }
+        }
+        ColumnarValue::Array(array) => {
+            let right = array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<T>>()
+                .ok_or(Error::DowncastError)?;
+            if left.len() != right.len() {
+                return Err(Error::DifferentSizes(
+                    format!("{:?}", left),
+                    format!("{:?}", right),
+                ));
+            }
+            let pairs = left.values().iter().zip(right.values().iter());
+            if right.null_count() == 0 {
+                values.extend(pairs.map(|(l, r)| op(*l, *r)));
+            } else {
+                values.extend(pairs.enumerate().map(|(i, (l, r))| {
+                    if right.is_valid(i) {
+                        op(*l, *r)
+                    } else {
+                        *l
+                    }
+                }));
+            }
+            match (left.validity(), right.validity()) {
+                (None, None) => None,
+                _ => Some(
+                    (0..left.len())
+                        .map(|i| left.is_valid(i) && right.is_valid(i))
+                        .collect(),
+                ),
+            }
+        }
+    };
+    *out = MutablePrimitiveArray::from_data(data_type, values, validity);
+    Ok(())
+}
+
+/// Like `binary_into`, with the current contents of `out` as the left operand.
+fn apply_in_place<T: NativeType>(
+    out: &mut MutablePrimitiveArray<T>,
+    right: &ColumnarValue,
+    op: impl Fn(T, T) -> T,
+) -> Result<(), Error> {
+    let length = out.len();
+    match right {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar
+                .as_any()
+                .downcast_ref::<PrimitiveScalar<T>>()
+                .ok_or(Error::DowncastError)?;
+            match scalar.value() {
+                Some(right) => out
+                    .values_mut_slice()
+                    .iter_mut()
+                    .for_each(|left| *left = op(*left, right)),
+                None => out.set_validity(Some(MutableBitmap::from_len_zeroed(length))),
+            }
+        }
+        ColumnarValue::Array(array) => {
+            let array = array
+                .as_any()
+                .downcast_ref::<PrimitiveArray<T>>()
+                .ok_or(Error::DowncastError)?;
+            if array.len() != length {
+                return Err(Error::DifferentSizes(
+                    format!("{:?}", out),
+                    format!("{:?}", array),
+                ));
+            }
+            let validity = match (out.validity(), array.validity()) {
+                (None, None) => None,
+                _ => Some(
+                    (0..length)
+                        .map(|i| out.is_valid(i) && array.is_valid(i))
+                        .collect::<MutableBitmap>(),
+                ),
+            };
+            let pairs = out.values_mut_slice().iter_mut().zip(array.values().iter());
+            if array.null_count() == 0 {
+                pairs.for_each(|(left, right)| *left = op(*left, *right));
+            } else {
+                pairs
+                    .enumerate()
+                    .filter(|(i, _)| array.is_valid(*i))
+                    .for_each(|(_, (left, right))| *left = op(*left, *right));
+            }
+            if validity.is_some() {
+                out.set_validity(validity);
+            }
+        }
+    }
+    Ok(())
 }
 
 #[derive(Clone, Debug)]
@@ -30,34 +246,42 @@ pub struct ColumnExpression {
 
 impl PhysicalExpression for ColumnExpression {
     fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-        input
+        let x: &dyn Array = input
             .get(self.index)
-            .and_then(|x| {
-                let x: &dyn Array = x.borrow();
-                match x.data_type().to_physical_type() {
-                    PhysicalType::Primitive(PrimitiveType::Int32) => x
-                        .as_any()
-                        .downcast_ref::<PrimitiveArray<i32>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Primitive(PrimitiveType::Float64) => x
-                        .as_any()
-                        .downcast_ref::<PrimitiveArray<f64>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Utf8 => x
-                        .as_any()
-                        .downcast_ref::<Utf8Array<i32>>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    PhysicalType::Boolean => x
-                        .as_any()
-                        .downcast_ref::<BooleanArray>()
-                        .map(|y| ColumnarValue::Array(Arc::new(y.clone()) as Arc<dyn Array>)),
-                    _ => None,
-                }
-            })
-            .ok_or(Error::PrimitiveTypeNotSuported(format!(
-                "{:?}",
-                PrimitiveType::Int32
-            )))
+            .ok_or(Error::ColumnIndexError(self.index))?
+            .borrow();
+        let column = match x.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => x
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            PhysicalType::Primitive(PrimitiveType::Int64) => x
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            PhysicalType::Primitive(PrimitiveType::Float32) => x
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f32>>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            PhysicalType::Primitive(PrimitiveType::Float64) => x
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            PhysicalType::Utf8 => x
+                .as_any()
+                .downcast_ref::<Utf8Array<i32>>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            PhysicalType::Boolean => x
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .map(|y| Arc::new(y.clone()) as Arc<dyn Array>),
+            _ => return Err(Error::UnsupportedColumnType(x.data_type().clone())),
+        };
+        column.map(ColumnarValue::Array).ok_or(Error::DowncastError)
+    }
+
+    fn column_index(&self) -> Option<usize> {
+        Some(self.index)
     }
 }
 
@@ -67,6 +291,45 @@ impl fmt::Display for ColumnExpression {
     }
 }
 
+/// A column referred to by name, as a frontend writes it. It has to be
+/// resolved against the input schema into a [`ColumnExpression`] before it
+/// can be evaluated.
+#[derive(Clone, Debug)]
+pub struct ColumnByNameExpression {
+    pub name: String,
+}
+
+impl ColumnByNameExpression {
+    pub fn new(name: &str) -> Self {
+        ColumnByNameExpression {
+            name: name.to_string(),
+        }
+    }
+
+    /// Errors if no field of `schema` has the name, or more than one does.
+    pub fn resolve(&self, schema: &Schema) -> Result<ColumnExpression, Error> {
+        let mut matches = schema
+            .fields
+            .iter()
+            .enumerate()
+            .filter(|(_, field)| field.name == self.name)
+            .map(|(index, _)| index);
+        let index = matches
+            .next()
+            .ok_or_else(|| Error::NoFieldInSchema(self.name.clone()))?;
+        if matches.next().is_some() {
+            return Err(Error::AmbiguousColumn(self.name.clone()));
+        }
+        Ok(ColumnExpression { index })
+    }
+}
+
+impl fmt::Display for ColumnByNameExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.name)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LiteralBoolExpression {
     pub value: BooleanScalar,
@@ -84,6 +347,10 @@ impl PhysicalExpression for LiteralBoolExpression {
     fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
 }
 
 impl fmt::Display for LiteralBoolExpression {
@@ -109,6 +376,10 @@ impl PhysicalExpression for LiteralStringExpression {
     fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
 }
 
 impl fmt::Display for LiteralStringExpression {
@@ -134,6 +405,10 @@ impl PhysicalExpression for LiteralIntegerExpression {
     fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
 }
 
 impl fmt::Display for LiteralIntegerExpression {
@@ -159,6 +434,10 @@ impl PhysicalExpression for LiteralFloatExpression {
     fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
         Ok(ColumnarValue::Scalar(Box::new(self.value.clone())))
     }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
 }
 
 impl fmt::Display for LiteralFloatExpression {
@@ -167,18 +446,124 @@ impl fmt::Display for LiteralFloatExpression {
     }
 }
 
+/// A null of a given type.
+#[derive(Clone, Debug)]
+pub struct LiteralNullExpression {
+    pub data_type: DataType,
+}
+
+impl LiteralNullExpression {
+    pub fn new(data_type: DataType) -> Self {
+        LiteralNullExpression { data_type }
+    }
+}
+
+impl PhysicalExpression for LiteralNullExpression {
+    fn evaluate(&self, _input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(new_scalar(
+            &*new_null_array(self.data_type.clone(), 1),
+            0,
+        )))
+    }
+
+    fn is_constant(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Display for LiteralNullExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NULL::{:?}", self.data_type)
+    }
+}
+
+/// Marks every empty string in `array` as null, keeping existing nulls.
+pub fn empty_string_to_null(array: &Utf8Array<i32>) -> Utf8Array<i32> {
+    let validity = Bitmap::from_trusted_len_iter(
+        array
+            .iter()
+            .map(|value| matches!(value, Some(value) if !value.is_empty())),
+    );
+    array.with_validity(Some(validity))
+}
+
+pub struct EmptyStringToNullExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl EmptyStringToNullExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        EmptyStringToNullExpression { expr }
+    }
+}
+
+impl PhysicalExpression for EmptyStringToNullExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => {
+                Ok(ColumnarValue::Array(Arc::new(empty_string_to_null(
+                    array
+                        .as_any()
+                        .downcast_ref::<Utf8Array<i32>>()
+                        .ok_or(Error::DowncastError)?,
+                ))))
+            }
+            ColumnarValue::Scalar(scalar) => {
+                let value = scalar
+                    .as_any()
+                    .downcast_ref::<Utf8Scalar<i32>>()
+                    .ok_or(Error::DowncastError)?
+                    .value()
+                    .filter(|value| !value.is_empty());
+                Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))))
+            }
+        }
+    }
+}
+
+impl fmt::Display for EmptyStringToNullExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "empty_to_null({})", self.expr)
+    }
+}
+
+/// Materializes `scalar` to `length` rows when that is shorter than
+/// `threshold`, so that the array-array kernels are used, see
+/// [`scalar_broadcast_threshold`]. Null scalars are left to the scalar kernels.
+fn broadcast(
+    scalar: &dyn Scalar,
+    length: usize,
+    threshold: usize,
+) -> Result<Option<Arc<dyn Array>>, Error> {
+    if length < threshold && scalar.is_valid() {
+        scalar_to_array(scalar, length).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
 macro_rules! comparisonExpression {
-    ($i: ident, $name1: ident, $name2: ident, $op: ident, $op_name: expr) => {
+    ($i: ident, $name1: ident, $name2: ident, $swapped: ident, $op: expr, $operator: expr) => {
         pub struct $i {
             left: Box<dyn PhysicalExpression>,
             right: Box<dyn PhysicalExpression>,
+            broadcast_threshold: usize,
         }
 
-        impl PhysicalExpression for $i {
-            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-                let l = self.left.evaluate(input)?;
-                let r = self.right.evaluate(input)?;
-                match (l, r) {
+        impl $i {
+            /// Compares `l` with `r`, broadcasting a scalar operand against
+            /// arrays shorter than `broadcast_threshold`, see [`broadcast`].
+            fn compare(
+                l: ColumnarValue,
+                r: ColumnarValue,
+                broadcast_threshold: usize,
+            ) -> Result<ColumnarValue, Error> {
+                if let Some(result) = null_operand_result(&l, &r, $operator)? {
+                    return Ok(result);
+                }
+                match coerce_operands(l, r, $operator)? {
                     (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
                         if left.len() == right.len() {
                             Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name1(
@@ -192,48 +577,202 @@ macro_rules! comparisonExpression {
                         }
                     }
                     (ColumnarValue::Array(left), ColumnarValue::Scalar(right)) => {
-                        Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name2(
-                            &*left, &*right,
-                        ))))
+                        Ok(ColumnarValue::Array(Arc::new(
+                            match broadcast(&*right, left.len(), broadcast_threshold)? {
+                                Some(right) => compute::comparison::$name1(&*left, &*right),
+                                None => compute::comparison::$name2(&*left, &*right),
+                            },
+                        )))
                     }
                     (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
-                        Ok(ColumnarValue::Array(Arc::new(compute::comparison::$name2(
-                            &*right, &*left,
-                        ))))
+                        Ok(ColumnarValue::Array(Arc::new(
+                            match broadcast(&*left, right.len(), broadcast_threshold)? {
+                                Some(left) => compute::comparison::$name1(&*left, &*right),
+                                None => compute::comparison::$swapped(&*right, &*left),
+                            },
+                        )))
                     }
                     (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
-                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(Some(
-                            left.$op(&*right),
-                        )))))
+                        let value = (left.is_valid() && right.is_valid())
+                            .then(|| $op(&*left, &*right))
+                            .transpose()?;
+                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))))
                     }
                 }
             }
         }
 
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                Self::compare(
+                    self.left.evaluate(input)?,
+                    self.right.evaluate(input)?,
+                    self.broadcast_threshold,
+                )
+            }
+
+            fn is_constant(&self) -> bool {
+                self.left.is_constant() && self.right.is_constant()
+            }
+        }
+
         impl $i {
             pub fn new(
                 left: Box<dyn PhysicalExpression>,
                 right: Box<dyn PhysicalExpression>,
             ) -> Self {
                 $i {
-                    left: left,
-                    right: right,
+                    left,
+                    right,
+                    broadcast_threshold: scalar_broadcast_threshold(),
                 }
             }
+
+            /// Overrides [`scalar_broadcast_threshold`] for this expression.
+            pub fn with_broadcast_threshold(mut self, threshold: usize) -> Self {
+                self.broadcast_threshold = threshold;
+                self
+            }
         }
 
         impl fmt::Display for $i {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "{} {} {}", self.left, $op_name, self.right)
+                write!(f, "{} {} {}", self.left, $operator, self.right)
             }
         }
     };
 }
-comparisonExpression!(EqExpression, eq, eq_scalar, eq, "==".to_string());
-comparisonExpression!(NeqExpression, neq, neq_scalar, ne, "!=".to_string());
+/// Orders two valid scalars of the same type, `None` when either is NaN.
+fn compare_scalars(left: &dyn Scalar, right: &dyn Scalar) -> Result<Option<Ordering>, Error> {
+    fn values<'a, T: 'static>(
+        left: &'a dyn Scalar,
+        right: &'a dyn Scalar,
+    ) -> Result<(&'a T, &'a T), Error> {
+        Ok((
+            left.as_any()
+                .downcast_ref::<T>()
+                .ok_or(Error::DowncastError)?,
+            right
+                .as_any()
+                .downcast_ref::<T>()
+                .ok_or(Error::DowncastError)?,
+        ))
+    }
+    match left.data_type().to_physical_type() {
+        PhysicalType::Boolean => {
+            let (left, right) = values::<BooleanScalar>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        PhysicalType::Primitive(PrimitiveType::Int32) => {
+            let (left, right) = values::<PrimitiveScalar<i32>>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        PhysicalType::Primitive(PrimitiveType::Int64) => {
+            let (left, right) = values::<PrimitiveScalar<i64>>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float32) => {
+            let (left, right) = values::<PrimitiveScalar<f32>>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            let (left, right) = values::<PrimitiveScalar<f64>>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        PhysicalType::Utf8 => {
+            let (left, right) = values::<Utf8Scalar<i32>>(left, right)?;
+            Ok(left.value().partial_cmp(&right.value()))
+        }
+        t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    }
+}
+
+/// Builds the scalar-scalar comparison for an ordering operator. NaN compares
+/// false, like in the array kernels.
+macro_rules! orderingComparison {
+    ($is: path) => {
+        |left: &dyn Scalar, right: &dyn Scalar| -> Result<bool, Error> {
+            Ok(compare_scalars(left, right)?.map_or(false, $is))
+        }
+    };
+}
 
-macro_rules! mathExpression {
-    ($i: ident, $name1: ident, $name2: ident, $op: ident, $op_name: expr) => {
+comparisonExpression!(
+    EqExpression,
+    eq,
+    eq_scalar,
+    eq_scalar,
+    |left: &dyn Scalar, right: &dyn Scalar| -> Result<bool, Error> { Ok(left == right) },
+    Operator::Eq
+);
+comparisonExpression!(
+    NeqExpression,
+    neq,
+    neq_scalar,
+    neq_scalar,
+    |left: &dyn Scalar, right: &dyn Scalar| -> Result<bool, Error> { Ok(left != right) },
+    Operator::Neq
+);
+comparisonExpression!(
+    LtExpression,
+    lt,
+    lt_scalar,
+    gt_scalar,
+    orderingComparison!(Ordering::is_lt),
+    Operator::Lt
+);
+comparisonExpression!(
+    LtEqExpression,
+    lt_eq,
+    lt_eq_scalar,
+    gt_eq_scalar,
+    orderingComparison!(Ordering::is_le),
+    Operator::LtEq
+);
+comparisonExpression!(
+    GtExpression,
+    gt,
+    gt_scalar,
+    lt_scalar,
+    orderingComparison!(Ordering::is_gt),
+    Operator::Gt
+);
+comparisonExpression!(
+    GtEqExpression,
+    gt_eq,
+    gt_eq_scalar,
+    lt_eq_scalar,
+    orderingComparison!(Ordering::is_ge),
+    Operator::GtEq
+);
+
+fn boolean_array(array: &dyn Array) -> Result<&BooleanArray, Error> {
+    array
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .ok_or(Error::PhysicalTypeNotSuported(format!(
+            "{:?}",
+            array.data_type()
+        )))
+}
+
+/// Reads a scalar operand of a logical operator, an untyped null being an
+/// unknown boolean.
+fn boolean_scalar(scalar: &dyn Scalar) -> Result<BooleanScalar, Error> {
+    match scalar.data_type() {
+        DataType::Null => Ok(BooleanScalar::new(None)),
+        data_type => scalar
+            .as_any()
+            .downcast_ref::<BooleanScalar>()
+            .cloned()
+            .ok_or(Error::PhysicalTypeNotSuported(format!("{:?}", data_type))),
+    }
+}
+
+/// `AND` and `OR` with SQL's three-valued logic: a null operand only makes
+/// the result null when the other operand doesn't decide it on its own.
+macro_rules! logicalExpression {
+    ($i: ident, $kernel: ident, $kernel_scalar: ident, $op: expr, $operator: expr) => {
         pub struct $i {
             left: Box<dyn PhysicalExpression>,
             right: Box<dyn PhysicalExpression>,
@@ -245,82 +784,34 @@ macro_rules! mathExpression {
                 let right = self.right.evaluate(input)?;
                 match (left, right) {
                     (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
-                        if left.len() == right.len() {
-                            Ok(ColumnarValue::Array(Arc::from(
-                                compute::arithmetics::$name1(&*left, &*right),
-                            )))
-                        } else {
-                            Err(Error::DifferentSizes(
-                                format!("{:?}", left),
-                                format!("{:?}", right),
-                            ))
-                        }
-                    }
-                    (ColumnarValue::Array(left), ColumnarValue::Scalar(right)) => {
-                        Ok(ColumnarValue::Array(Arc::from(
-                            compute::arithmetics::$name2(&*left, &*right),
-                        )))
-                    }
-                    (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
-                        Ok(ColumnarValue::Array(Arc::from(
-                            compute::arithmetics::$name2(&*right, &*left),
-                        )))
+                        let result = compute::boolean_kleene::$kernel(
+                            boolean_array(&*left)?,
+                            boolean_array(&*right)?,
+                        )
+                        .map_err(Error::ArrowError)?;
+                        Ok(ColumnarValue::Array(Arc::new(result)))
                     }
+                    // Both operators commute, so the scalar can go either side.
+                    (ColumnarValue::Array(array), ColumnarValue::Scalar(scalar))
+                    | (ColumnarValue::Scalar(scalar), ColumnarValue::Array(array)) => Ok(
+                        ColumnarValue::Array(Arc::new(compute::boolean_kleene::$kernel_scalar(
+                            boolean_array(&*array)?,
+                            &boolean_scalar(&*scalar)?,
+                        ))),
+                    ),
                     (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
-                        match (
-                            left.data_type().to_physical_type(),
-                            right.data_type().to_physical_type(),
-                        ) {
-                            (
-                                PhysicalType::Primitive(PrimitiveType::Float64),
-                                PhysicalType::Primitive(PrimitiveType::Float64),
-                            ) => {
-                                let (left, right) = (
-                                    left.as_any()
-                                        .downcast_ref::<PrimitiveScalar<f64>>()
-                                        .ok_or(Error::DowncastError)?,
-                                    right
-                                        .as_any()
-                                        .downcast_ref::<PrimitiveScalar<f64>>()
-                                        .ok_or(Error::DowncastError)?,
-                                );
-                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
-                                    DataType::Float64,
-                                    match (left.value(), right.value()) {
-                                        (Some(left), Some(right)) => Some(left.$op(right)),
-                                        _ => None,
-                                    },
-                                ))))
-                            }
-                            (
-                                PhysicalType::Primitive(PrimitiveType::Int32),
-                                PhysicalType::Primitive(PrimitiveType::Int32),
-                            ) => {
-                                let (left, right) = (
-                                    left.as_any()
-                                        .downcast_ref::<PrimitiveScalar<i32>>()
-                                        .ok_or(Error::DowncastError)?,
-                                    right
-                                        .as_any()
-                                        .downcast_ref::<PrimitiveScalar<i32>>()
-                                        .ok_or(Error::DowncastError)?,
-                                );
-                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
-                                    DataType::Int32,
-                                    match (left.value(), right.value()) {
-                                        (Some(left), Some(right)) => Some(left.$op(right)),
-                                        _ => None,
-                                    },
-                                ))))
-                            }
-                            _ => Err(Error::PhysicalTypeNotSuported(format!(
-                                "{:?}",
-                                left.data_type()
-                            ))),
-                        }
+                        let value = $op(
+                            boolean_scalar(&*left)?.value(),
+                            boolean_scalar(&*right)?.value(),
+                        );
+                        Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))))
                     }
                 }
             }
+
+            fn is_constant(&self) -> bool {
+                self.left.is_constant() && self.right.is_constant()
+            }
         }
 
         impl $i {
@@ -328,178 +819,4052 @@ macro_rules! mathExpression {
                 left: Box<dyn PhysicalExpression>,
                 right: Box<dyn PhysicalExpression>,
             ) -> Self {
-                $i {
-                    left: left,
-                    right: right,
-                }
+                $i { left, right }
             }
         }
 
         impl fmt::Display for $i {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "{} {} {}", self.left, $op_name, self.right)
+                write!(f, "{} {} {}", self.left, $operator, self.right)
             }
         }
     };
 }
 
-mathExpression!(AddExpression, add, add_scalar, add, "+".to_string());
-mathExpression!(SubExpression, sub, sub_scalar, sub, "-".to_string());
-mathExpression!(MulExpression, mul, mul_scalar, mul, "*".to_string());
-mathExpression!(DivExpression, div, div_scalar, div, "/".to_string());
+logicalExpression!(
+    AndExpression,
+    and,
+    and_scalar,
+    |left: Option<bool>, right: Option<bool>| match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    },
+    Operator::And
+);
+logicalExpression!(
+    OrExpression,
+    or,
+    or_scalar,
+    |left: Option<bool>, right: Option<bool>| match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    },
+    Operator::Or
+);
 
-pub trait Accumulator {
-    fn accumulate(
-        &mut self,
-        input: &Vec<ColumnarValue>,
-        validity: Option<&Bitmap>,
-    ) -> Result<(), Error>;
-    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error>;
+/// Negates a boolean expression, leaving nulls null.
+pub struct NotExpression {
+    expr: Box<dyn PhysicalExpression>,
 }
 
-pub trait PhysicalAggregateExpression: PhysicalExpression {
-    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator>;
+impl NotExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        NotExpression { expr }
+    }
 }
 
-macro_rules! aggregateExpression {
-    ($acc: ident,$expr: ident, $name1: ident, $name2: ident, $op_name: expr) => {
-        pub struct $acc {
-            value: Box<dyn Scalar>,
-            index: usize,
+impl PhysicalExpression for NotExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                compute::boolean::not(boolean_array(&*array)?),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = boolean_scalar(&*scalar)?.value().map(|value| !value);
+                Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))))
+            }
         }
+    }
 
-        impl Accumulator for $acc {
-            fn accumulate(
-                &mut self,
-                input: &Vec<ColumnarValue>,
-                validity: Option<&Bitmap>,
-            ) -> Result<(), Error> {
-                let expr = &input[self.index];
-                let new = match expr {
-                    ColumnarValue::Array(expr) => {
-                        let val = match (expr.validity(), validity) {
-                            (Some(val1), Some(val2)) => Some(val1.bitand(val2)),
-                            (Some(val), None) => Some(val.clone()),
-                            (None, Some(val)) => Some(val.clone()),
-                            (None, None) => None,
-                        };
-                        compute::aggregate::$name1(
-                            &*(expr.borrow() as &dyn Array).with_validity(val),
-                        )
-                        .map_err(|err| Error::ArrowError(err))
-                    }
-                    ColumnarValue::Scalar(scalar) => match scalar.data_type().to_physical_type() {
-                        PhysicalType::Primitive(PrimitiveType::Float64) => Ok(Box::new(
-                            scalar
-                                .as_any()
-                                .downcast_ref::<PrimitiveScalar<f64>>()
-                                .ok_or(Error::DowncastError)?
-                                .clone(),
-                        )
-                            as Box<dyn Scalar>),
-                        PhysicalType::Primitive(PrimitiveType::Int32) => Ok(Box::new(
-                            scalar
-                                .as_any()
-                                .downcast_ref::<PrimitiveScalar<i32>>()
-                                .ok_or(Error::DowncastError)?
-                                .clone(),
-                        )
-                            as Box<dyn Scalar>),
-                        x => Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for NotExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NOT {}", self.expr)
+    }
+}
+
+/// Copies `value` into `out`, which must be Int32 or Float64.
+fn copy_value_into(value: Arc<dyn Array>, out: &mut dyn MutableArray) -> Result<(), Error> {
+    match out.data_type().to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => {
+            copy_into::<i32>(&*value, downcast_mut(out)?)
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            copy_into::<f64>(&*value, downcast_mut(out)?)
+        }
+        t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    }
+}
+
+/// How integer arithmetic treats results that don't fit the type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowHandling {
+    /// Results wrap around in two's complement. This is the fastest.
+    #[default]
+    Wrap,
+    /// Any overflowing row makes the expression fail with
+    /// [`Error::ArithmeticOverflow`].
+    Error,
+}
+
+/// Applies `kernel` to Int32 or Int64 operands of the same type, or
+/// `scalar_kernel` for a valid scalar on the right that isn't broadcast under
+/// `broadcast_threshold`, see [`broadcast`]. A scalar on the left is materialized, as the scalar kernels
+/// only take it on the right and not every operator commutes.
+fn integer_kernel<T: NativeType>(
+    left: &ColumnarValue,
+    right: &ColumnarValue,
+    kernel: impl Fn(&PrimitiveArray<T>, &PrimitiveArray<T>) -> PrimitiveArray<T>,
+    scalar_kernel: impl Fn(&PrimitiveArray<T>, &T) -> PrimitiveArray<T>,
+    broadcast_threshold: usize,
+) -> Result<ColumnarValue, Error> {
+    let to_array = |scalar: &dyn Scalar, length: usize| {
+        if scalar.is_valid() {
+            scalar_to_array(scalar, length)
+        } else {
+            Ok(Arc::from(new_null_array(
+                scalar.data_type().clone(),
+                length,
+            )))
+        }
+    };
+    let result = match (left, right) {
+        (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
+            if left.len() != right.len() {
+                return Err(Error::DifferentSizes(
+                    format!("{:?}", left),
+                    format!("{:?}", right),
+                ));
+            }
+            kernel(downcast(&**left)?, downcast(&**right)?)
+        }
+        (ColumnarValue::Array(left), ColumnarValue::Scalar(right)) => {
+            let left = downcast::<PrimitiveArray<T>>(&**left)?;
+            let value = right
+                .as_any()
+                .downcast_ref::<PrimitiveScalar<T>>()
+                .ok_or(Error::DowncastError)?
+                .value();
+            match (value, broadcast(&**right, left.len(), broadcast_threshold)?) {
+                (_, Some(right)) => kernel(left, downcast(&*right)?),
+                (Some(value), None) => scalar_kernel(left, &value),
+                (None, None) => return Ok(ColumnarValue::Array(to_array(&**right, left.len())?)),
+            }
+        }
+        (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
+            let left = to_array(&**left, right.len())?;
+            kernel(downcast(&*left)?, downcast(&**right)?)
+        }
+        (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
+            let (left, right) = (to_array(&**left, 1)?, to_array(&**right, 1)?);
+            let result = kernel(downcast(&*left)?, downcast(&*right)?);
+            return Ok(ColumnarValue::Scalar(new_scalar(&result, 0)));
+        }
+    };
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+/// Applies the Int32 or Int64 instance of an integer kernel and its scalar
+/// variant, see [`integer_kernel`]. Evaluates to `None` for other types.
+macro_rules! integerKernel {
+    ($left: expr, $right: expr, $kernel: expr, $scalar_kernel: expr, $threshold: expr) => {
+        match value_data_type($left).to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => Some(integer_kernel::<i32>(
+                $left,
+                $right,
+                $kernel,
+                $scalar_kernel,
+                $threshold,
+            )?),
+            PhysicalType::Primitive(PrimitiveType::Int64) => Some(integer_kernel::<i64>(
+                $left,
+                $right,
+                $kernel,
+                $scalar_kernel,
+                $threshold,
+            )?),
+            _ => None,
+        }
+    };
+}
+
+/// Whether a checked kernel nulled rows where both operands are valid, which
+/// it does exactly where the result overflowed. Zero divisors are already null
+/// by then, see [`checked_divisor`].
+fn overflowed(left: &ColumnarValue, right: &ColumnarValue, result: &ColumnarValue) -> bool {
+    // `None` when every row is null, `Some(None)` when none is.
+    fn validity(value: &ColumnarValue) -> Option<Option<&Bitmap>> {
+        match value {
+            ColumnarValue::Array(array) => Some(array.validity()),
+            ColumnarValue::Scalar(scalar) => scalar.is_valid().then_some(None),
+        }
+    }
+    let operand_nulls = match (validity(left), validity(right)) {
+        (Some(Some(left)), Some(Some(right))) => left.bitand(right).null_count(),
+        (Some(Some(validity)), Some(None)) | (Some(None), Some(Some(validity))) => {
+            validity.null_count()
+        }
+        (Some(None), Some(None)) => 0,
+        _ => return false,
+    };
+    let result_nulls = match result {
+        ColumnarValue::Array(array) => array.null_count(),
+        ColumnarValue::Scalar(scalar) => usize::from(!scalar.is_valid()),
+    };
+    result_nulls > operand_nulls
+}
+
+/// Integer division and remainder that wrap around on `MIN / -1`, which
+/// arrow has no kernels for.
+trait WrappingDivRem: NativeType {
+    fn wrapping_div(self, rhs: Self) -> Self;
+    fn wrapping_rem(self, rhs: Self) -> Self;
+}
+
+macro_rules! wrappingDivRem {
+    ($t: ty) => {
+        impl WrappingDivRem for $t {
+            fn wrapping_div(self, rhs: Self) -> Self {
+                <$t>::wrapping_div(self, rhs)
+            }
+
+            fn wrapping_rem(self, rhs: Self) -> Self {
+                <$t>::wrapping_rem(self, rhs)
+            }
+        }
+    };
+}
+
+wrappingDivRem!(i32);
+wrappingDivRem!(i64);
+
+// Valid divisors are never zero, see `checked_divisor`, but null slots may
+// hold zeros, so the array-array kernels skip them.
+fn wrapping_div<T: WrappingDivRem>(
+    lhs: &PrimitiveArray<T>,
+    rhs: &PrimitiveArray<T>,
+) -> PrimitiveArray<T> {
+    compute::arity::binary_checked(lhs, rhs, lhs.data_type().clone(), |l: T, r: T| {
+        (r != T::default()).then(|| l.wrapping_div(r))
+    })
+}
+
+fn wrapping_div_scalar<T: WrappingDivRem>(lhs: &PrimitiveArray<T>, rhs: &T) -> PrimitiveArray<T> {
+    compute::arity::unary(lhs, |l| l.wrapping_div(*rhs), lhs.data_type().clone())
+}
+
+fn wrapping_rem<T: WrappingDivRem>(
+    lhs: &PrimitiveArray<T>,
+    rhs: &PrimitiveArray<T>,
+) -> PrimitiveArray<T> {
+    compute::arity::binary_checked(lhs, rhs, lhs.data_type().clone(), |l: T, r: T| {
+        (r != T::default()).then(|| l.wrapping_rem(r))
+    })
+}
+
+fn wrapping_rem_scalar<T: WrappingDivRem>(lhs: &PrimitiveArray<T>, rhs: &T) -> PrimitiveArray<T> {
+    compute::arity::unary(lhs, |l| l.wrapping_rem(*rhs), lhs.data_type().clone())
+}
+
+/// Negates a numeric array. Integers wrap, so negating the minimum value
+/// gives the minimum value back.
+fn negate(array: &dyn Array) -> Result<Box<dyn Array>, Error> {
+    use compute::arithmetics::basic;
+    match array.data_type().to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => {
+            Ok(Box::new(basic::wrapping_negate(downcast::<
+                PrimitiveArray<i32>,
+            >(array)?)))
+        }
+        PhysicalType::Primitive(PrimitiveType::Int64) => {
+            Ok(Box::new(basic::wrapping_negate(downcast::<
+                PrimitiveArray<i64>,
+            >(array)?)))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float32) => {
+            Ok(Box::new(basic::negate(downcast::<PrimitiveArray<f32>>(
+                array,
+            )?)))
+        }
+        PhysicalType::Primitive(PrimitiveType::Float64) => {
+            Ok(Box::new(basic::negate(downcast::<PrimitiveArray<f64>>(
+                array,
+            )?)))
+        }
+        _ => Err(Error::PhysicalTypeNotSuported(format!(
+            "{:?}",
+            array.data_type()
+        ))),
+    }
+}
+
+/// Negates a numeric expression, leaving nulls null.
+pub struct NegExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl NegExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        NegExpression { expr }
+    }
+}
+
+impl PhysicalExpression for NegExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::from(negate(&*array)?))),
+            ColumnarValue::Scalar(scalar) if scalar.data_type() == &DataType::Null => {
+                Ok(ColumnarValue::Scalar(scalar))
+            }
+            ColumnarValue::Scalar(scalar) => {
+                let array = negate(&*scalars_to_array(&[scalar])?)?;
+                Ok(ColumnarValue::Scalar(new_scalar(&*array, 0)))
+            }
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for NegExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "-{}", self.expr)
+    }
+}
+
+macro_rules! nullTestExpression {
+    ($i: ident, $kernel: ident, $is_valid: expr, $name: expr) => {
+        /// Tests its child for nulls. The result is never null.
+        pub struct $i {
+            expr: Box<dyn PhysicalExpression>,
+        }
+
+        impl $i {
+            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+                $i { expr }
+            }
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                match self.expr.evaluate(input)? {
+                    ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                        compute::boolean::$kernel(&*array),
+                    ))),
+                    ColumnarValue::Scalar(scalar) => Ok(ColumnarValue::Scalar(Box::new(
+                        BooleanScalar::new(Some(scalar.is_valid() == $is_valid)),
+                    ))),
+                }
+            }
+
+            fn is_constant(&self) -> bool {
+                self.expr.is_constant()
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {}", self.expr, $name)
+            }
+        }
+    };
+}
+
+nullTestExpression!(IsNullExpression, is_null, false, "IS NULL");
+nullTestExpression!(IsNotNullExpression, is_not_null, true, "IS NOT NULL");
+
+/// Casts `values` to the type they all coerce to, see [`coerce_types`].
+fn coerce_values(values: Vec<ColumnarValue>, op: Operator) -> Result<Vec<ColumnarValue>, Error> {
+    let data_type = common_type(values.iter().map(value_data_type), op)?;
+    cast_values(values, &data_type)
+}
+
+/// The type that all of `data_types` coerce to, see [`coerce_types`].
+fn common_type<'a>(
+    data_types: impl IntoIterator<Item = &'a DataType>,
+    op: Operator,
+) -> Result<DataType, Error> {
+    data_types
+        .into_iter()
+        .try_fold(DataType::Null, |common, data_type| {
+            coerce_types(&common, data_type, op)
+        })
+}
+
+/// Casts the `values` that aren't of `data_type` yet.
+fn cast_values(
+    values: Vec<ColumnarValue>,
+    data_type: &DataType,
+) -> Result<Vec<ColumnarValue>, Error> {
+    values
+        .into_iter()
+        .map(|value| {
+            if value_data_type(&value) == data_type {
+                Ok(value)
+            } else {
+                cast_value(value, data_type)
+            }
+        })
+        .collect()
+}
+
+/// Builds an array whose row `i` is row `i` of `values[choices[i]]`. Scalars
+/// count as the same value in every row.
+fn choose_rows(values: Vec<ColumnarValue>, choices: &[usize]) -> Result<Arc<dyn Array>, Error> {
+    let arrays = values
+        .into_iter()
+        .map(|value| match value {
+            ColumnarValue::Array(array) => Ok((array, false)),
+            ColumnarValue::Scalar(scalar) => Ok((scalars_to_array(&[scalar])?, true)),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let offsets = arrays
+        .iter()
+        .scan(0, |offset, (array, _)| {
+            let start = *offset;
+            *offset += array.len();
+            Some(start)
+        })
+        .collect::<Vec<_>>();
+    let indices = choices
+        .iter()
+        .enumerate()
+        .map(|(row, &i)| {
+            let row = if arrays[i].1 { 0 } else { row };
+            (offsets[i] + row) as u32
+        })
+        .collect::<Vec<_>>();
+    let indices = PrimitiveArray::from_vec(indices);
+    let values = compute::concatenate::concatenate(
+        &arrays.iter().map(|(array, _)| &**array).collect::<Vec<_>>(),
+    )
+    .map_err(Error::ArrowError)?;
+    Ok(Arc::from(
+        compute::take::take(&*values, &indices).map_err(Error::ArrowError)?,
+    ))
+}
+
+/// Whether a CASE condition holds, for a condition that is the same in every
+/// row or one that varies by row.
+enum Condition<'a> {
+    Always(bool),
+    Rows(&'a BooleanArray),
+}
+
+impl Condition<'_> {
+    fn holds(&self, row: usize) -> bool {
+        match self {
+            Condition::Always(holds) => *holds,
+            Condition::Rows(array) => array.is_valid(row) && array.value(row),
+        }
+    }
+}
+
+/// SQL's searched `CASE WHEN condition THEN result ... ELSE result END`. Each
+/// row takes the result of the first branch whose condition is true, a null
+/// condition counting as false, and otherwise the ELSE result or null. The
+/// results are coerced to a common type like the operands of a comparison.
+pub struct CaseExpression {
+    branches: Vec<(Box<dyn PhysicalExpression>, Box<dyn PhysicalExpression>)>,
+    else_expr: Option<Box<dyn PhysicalExpression>>,
+}
+
+impl CaseExpression {
+    pub fn new(
+        branches: Vec<(Box<dyn PhysicalExpression>, Box<dyn PhysicalExpression>)>,
+        else_expr: Option<Box<dyn PhysicalExpression>>,
+    ) -> Self {
+        CaseExpression {
+            branches,
+            else_expr,
+        }
+    }
+}
+
+impl PhysicalExpression for CaseExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let conditions = self
+            .branches
+            .iter()
+            .map(|(condition, _)| condition.evaluate(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut results = self
+            .branches
+            .iter()
+            .map(|(_, result)| result.evaluate(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        results.push(match &self.else_expr {
+            Some(else_expr) => else_expr.evaluate(input)?,
+            None => ColumnarValue::Scalar(Box::new(NullScalar::new())),
+        });
+        let mut results = coerce_values(results, Operator::Eq)?;
+
+        let conditions = conditions
+            .iter()
+            .map(|condition| match condition {
+                ColumnarValue::Array(array) => Ok(Condition::Rows(boolean_array(&**array)?)),
+                ColumnarValue::Scalar(scalar) => Ok(Condition::Always(
+                    boolean_scalar(&**scalar)?.value() == Some(true),
+                )),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let branch = |row| {
+            conditions
+                .iter()
+                .position(|condition| condition.holds(row))
+                .unwrap_or(self.branches.len())
+        };
+        let rows_differ = conditions
+            .iter()
+            .any(|condition| matches!(condition, Condition::Rows(_)))
+            || results
+                .iter()
+                .any(|result| matches!(result, ColumnarValue::Array(_)));
+        if !rows_differ {
+            return Ok(results.swap_remove(branch(0)));
+        }
+        let choices = (0..input.len()).map(branch).collect::<Vec<_>>();
+        Ok(ColumnarValue::Array(choose_rows(results, &choices)?))
+    }
+
+    fn is_constant(&self) -> bool {
+        self.branches
+            .iter()
+            .all(|(condition, result)| condition.is_constant() && result.is_constant())
+            && self
+                .else_expr
+                .as_ref()
+                .is_none_or(|else_expr| else_expr.is_constant())
+    }
+}
+
+impl fmt::Display for CaseExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CASE")?;
+        for (condition, result) in &self.branches {
+            write!(f, " WHEN {} THEN {}", condition, result)?;
+        }
+        if let Some(else_expr) = &self.else_expr {
+            write!(f, " ELSE {}", else_expr)?;
+        }
+        write!(f, " END")
+    }
+}
+
+/// Returns the first non-null value of its children per row, or null if
+/// they are all null. The children are coerced to a common type like the
+/// operands of a comparison.
+pub struct CoalesceExpression {
+    exprs: Vec<Box<dyn PhysicalExpression>>,
+    /// The children after the first constant one that isn't null. Their values
+    /// are never used, but their types still take part in the coercion.
+    unreached: Vec<Box<dyn PhysicalExpression>>,
+}
+
+impl CoalesceExpression {
+    pub fn new(mut exprs: Vec<Box<dyn PhysicalExpression>>) -> Result<Self, Error> {
+        let mut unreached = vec![];
+        for (i, expr) in exprs.iter().enumerate() {
+            if let Some(scalar) = expr.evaluate_scalar()? {
+                if scalar.is_valid() {
+                    unreached = exprs.split_off(i + 1);
+                    break;
+                }
+            }
+        }
+        Ok(CoalesceExpression { exprs, unreached })
+    }
+}
+
+impl PhysicalExpression for CoalesceExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let values = self
+            .exprs
+            .iter()
+            .map(|expr| expr.evaluate(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        // The unreached children only have to be typed, which an empty batch
+        // does as well as the whole input.
+        let empty = Chunk::new(
+            input
+                .arrays()
+                .iter()
+                .map(|array| Arc::from(array.slice(0, 0)))
+                .collect::<Vec<_>>(),
+        );
+        let unreached = self
+            .unreached
+            .iter()
+            .map(|expr| expr.evaluate(&empty).map(|value| value.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let data_type = common_type(
+            values.iter().map(value_data_type).chain(&unreached),
+            Operator::Eq,
+        )?;
+        let mut values = cast_values(values, &data_type)?;
+        let last = values.len().saturating_sub(1);
+        let is_valid = |value: &ColumnarValue, row| match value {
+            ColumnarValue::Array(array) => array.is_valid(row),
+            ColumnarValue::Scalar(scalar) => scalar.is_valid(),
+        };
+        let choice = |row| {
+            values
+                .iter()
+                .position(|value| is_valid(value, row))
+                .unwrap_or(last)
+        };
+        if values
+            .iter()
+            .all(|value| matches!(value, ColumnarValue::Scalar(_)))
+        {
+            if values.is_empty() {
+                return Ok(ColumnarValue::Scalar(Box::new(NullScalar::new())));
+            }
+            return Ok(values.swap_remove(choice(0)));
+        }
+        let choices = (0..input.len()).map(choice).collect::<Vec<_>>();
+        Ok(ColumnarValue::Array(choose_rows(values, &choices)?))
+    }
+
+    fn is_constant(&self) -> bool {
+        self.exprs.iter().all(|expr| expr.is_constant())
+    }
+}
+
+impl fmt::Display for CoalesceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exprs = self
+            .exprs
+            .iter()
+            .chain(&self.unreached)
+            .map(|expr| expr.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "COALESCE({})", exprs.join(", "))
+    }
+}
+
+/// SQL's `NULLIF(expr, value)`: null where `expr = value` and `expr`
+/// otherwise, for turning sentinel values into nulls. Both sides are coerced
+/// to a common type like the operands of a comparison.
+pub struct NullIfExpression {
+    expr: Box<dyn PhysicalExpression>,
+    value: Box<dyn PhysicalExpression>,
+}
+
+impl NullIfExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, value: Box<dyn PhysicalExpression>) -> Self {
+        NullIfExpression { expr, value }
+    }
+}
+
+impl PhysicalExpression for NullIfExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let (expr, value) = coerce_operands(
+            self.expr.evaluate(input)?,
+            self.value.evaluate(input)?,
+            Operator::Eq,
+        )?;
+        let length = match (&expr, &value) {
+            (ColumnarValue::Scalar(expr), ColumnarValue::Scalar(value)) => {
+                return Ok(ColumnarValue::Scalar(
+                    if expr.is_valid() && value.is_valid() && **expr == **value {
+                        new_scalar(&*new_null_array(expr.data_type().clone(), 1), 0)
+                    } else {
+                        new_scalar(&*scalar_to_array(&**expr, 1)?, 0)
                     },
-                }?;
-                let bool = match (
-                    new.data_type().to_physical_type(),
-                    self.value.data_type().to_physical_type(),
-                ) {
-                    (
-                        PhysicalType::Primitive(PrimitiveType::Float64),
-                        PhysicalType::Primitive(PrimitiveType::Float64),
-                    ) => {
-                        let (left, right) = (
-                            new.as_any()
-                                .downcast_ref::<PrimitiveScalar<f64>>()
-                                .ok_or(Error::DowncastError)?,
-                            self.value
-                                .as_any()
-                                .downcast_ref::<PrimitiveScalar<f64>>()
-                                .ok_or(Error::DowncastError)?,
-                        );
-                        match (left.value(), right.value()) {
-                            (Some(left), Some(right)) => Ok(left.$name2(&right)),
-                            _ => Err(Error::DowncastError),
-                        }
+                ));
+            }
+            (ColumnarValue::Array(array), _) | (_, ColumnarValue::Array(array)) => array.len(),
+        };
+        let expr = expr.into_array(length)?;
+        let equal = EqExpression::compare(
+            ColumnarValue::Array(expr.clone()),
+            value,
+            scalar_broadcast_threshold(),
+        )?;
+        let equal = equal.into_array(length)?;
+        let equal = boolean_array(&*equal)?;
+        let validity = (0..length)
+            .map(|row| expr.is_valid(row) && !(equal.is_valid(row) && equal.value(row)))
+            .collect::<Bitmap>();
+        Ok(ColumnarValue::Array(Arc::from(
+            expr.with_validity(Some(validity)),
+        )))
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant() && self.value.is_constant()
+    }
+}
+
+impl fmt::Display for NullIfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NULLIF({}, {})", self.expr, self.value)
+    }
+}
+
+/// For every row, the position of the array with the valid value that
+/// compares as `wanted` against the others, the first one on ties. Rows where
+/// every array is null take the last array.
+fn pick_extreme<T: NativeType>(
+    arrays: &[Arc<dyn Array>],
+    length: usize,
+    cmp: fn(&T, &T) -> Ordering,
+    wanted: Ordering,
+) -> Result<Vec<usize>, Error> {
+    let arrays = arrays
+        .iter()
+        .map(|array| downcast::<PrimitiveArray<T>>(&**array))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((0..length)
+        .map(|row| {
+            let mut choice: Option<usize> = None;
+            for (i, array) in arrays.iter().enumerate() {
+                let wins =
+                    |chosen: usize| cmp(&array.value(row), &arrays[chosen].value(row)) == wanted;
+                if array.is_valid(row) && choice.is_none_or(wins) {
+                    choice = Some(i);
+                }
+            }
+            choice.unwrap_or(arrays.len() - 1)
+        })
+        .collect())
+}
+
+macro_rules! extremeExpression {
+    ($i: ident, $wanted: expr, $name: expr) => {
+        /// Compares its children across each row, ignoring nulls, and is null
+        /// only where all of them are. The children are coerced to a common
+        /// numeric type like the operands of arithmetic. Float NaN sorts above
+        /// every other value.
+        pub struct $i {
+            exprs: Vec<Box<dyn PhysicalExpression>>,
+        }
+
+        impl $i {
+            pub fn new(exprs: Vec<Box<dyn PhysicalExpression>>) -> Self {
+                $i { exprs }
+            }
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                let values = self
+                    .exprs
+                    .iter()
+                    .map(|expr| expr.evaluate(input))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let values = coerce_values(values, Operator::Plus)?;
+                let data_type = match values.first() {
+                    Some(value) => value_data_type(value).clone(),
+                    None => return Ok(ColumnarValue::Scalar(Box::new(NullScalar::new()))),
+                };
+                let scalar = values
+                    .iter()
+                    .all(|value| matches!(value, ColumnarValue::Scalar(_)));
+                let length = if scalar { 1 } else { input.len() };
+                let arrays = values
+                    .into_iter()
+                    .map(|value| value.into_array(length))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let choices = match data_type.to_physical_type() {
+                    PhysicalType::Primitive(PrimitiveType::Int32) => {
+                        pick_extreme::<i32>(&arrays, length, Ord::cmp, $wanted)?
                     }
-                    (
-                        PhysicalType::Primitive(PrimitiveType::Int32),
-                        PhysicalType::Primitive(PrimitiveType::Int32),
-                    ) => {
-                        let (left, right) = (
-                            new.as_any()
-                                .downcast_ref::<PrimitiveScalar<i32>>()
-                                .ok_or(Error::DowncastError)?,
-                            self.value
-                                .as_any()
-                                .downcast_ref::<PrimitiveScalar<i32>>()
-                                .ok_or(Error::DowncastError)?,
-                        );
-                        match (left.value(), right.value()) {
-                            (Some(left), Some(right)) => Ok(left.$name2(&right)),
-                            _ => Err(Error::DowncastError),
-                        }
+                    PhysicalType::Primitive(PrimitiveType::Int64) => {
+                        pick_extreme::<i64>(&arrays, length, Ord::cmp, $wanted)?
                     }
-                    (PhysicalType::Primitive(PrimitiveType::Float64), PhysicalType::Null) => {
-                        Ok(true)
+                    PhysicalType::Primitive(PrimitiveType::Float32) => {
+                        pick_extreme::<f32>(&arrays, length, f32::total_cmp, $wanted)?
+                    }
+                    PhysicalType::Primitive(PrimitiveType::Float64) => {
+                        pick_extreme::<f64>(&arrays, length, f64::total_cmp, $wanted)?
+                    }
+                    // Every child is a null literal.
+                    PhysicalType::Null => vec![0; length],
+                    t => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+                };
+                let values = arrays.into_iter().map(ColumnarValue::Array).collect();
+                let result = choose_rows(values, &choices)?;
+                Ok(if scalar {
+                    ColumnarValue::Scalar(new_scalar(&*result, 0))
+                } else {
+                    ColumnarValue::Array(result)
+                })
+            }
+
+            fn is_constant(&self) -> bool {
+                self.exprs.iter().all(|expr| expr.is_constant())
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let exprs = self
+                    .exprs
+                    .iter()
+                    .map(|expr| expr.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "{}({})", $name, exprs.join(", "))
+            }
+        }
+    };
+}
+
+extremeExpression!(GreatestExpression, Ordering::Greater, "GREATEST");
+extremeExpression!(LeastExpression, Ordering::Less, "LEAST");
+
+/// SQL's `expr IN (value, ...)`. A null `expr` makes the result null, and
+/// so does a null in the list when `expr` matches no other value.
+pub struct InListExpression {
+    expr: Box<dyn PhysicalExpression>,
+    list: Arc<dyn Array>,
+    negated: bool,
+}
+
+impl InListExpression {
+    /// Errors if the values don't share a type.
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        list: Vec<Box<dyn Scalar>>,
+    ) -> Result<Self, Error> {
+        Ok(InListExpression {
+            expr,
+            list: scalars_to_array(&list)?,
+            negated: false,
+        })
+    }
+
+    /// Turns `IN` into `NOT IN`. Nulls stay null.
+    pub fn negated(mut self) -> Self {
+        self.negated = !self.negated;
+        self
+    }
+}
+
+impl PhysicalExpression for InListExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let value = self.expr.evaluate(input)?;
+        let is_scalar = matches!(value, ColumnarValue::Scalar(_));
+        let list = ColumnarValue::Array(self.list.clone());
+        let (value, list) = coerce_operands(value, list, Operator::Eq)?;
+        let array = match value {
+            ColumnarValue::Array(array) => array,
+            ColumnarValue::Scalar(scalar) => scalars_to_array(&[scalar])?,
+        };
+        let list = list.into_array(0)?;
+        let mut result = BooleanArray::from_slice(vec![false; array.len()]);
+        for i in 0..list.len() {
+            let equal = compute::comparison::eq_scalar(&*array, &*new_scalar(&*list, i));
+            result = compute::boolean_kleene::or(&result, &equal).map_err(Error::ArrowError)?;
+        }
+        if self.negated {
+            result = compute::boolean::not(&result);
+        }
+        Ok(if is_scalar {
+            ColumnarValue::Scalar(new_scalar(&result, 0))
+        } else {
+            ColumnarValue::Array(Arc::new(result))
+        })
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for InListExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not = if self.negated { "NOT " } else { "" };
+        let list = (0..self.list.len())
+            .map(|i| format!("{:?}", new_scalar(&*self.list, i)))
+            .collect::<Vec<_>>();
+        write!(f, "{} {}IN ({})", self.expr, not, list.join(", "))
+    }
+}
+
+/// SQL's `expr BETWEEN low AND high`, which includes both bounds. Each of
+/// the three is evaluated once.
+pub struct BetweenExpression {
+    expr: Box<dyn PhysicalExpression>,
+    low: Box<dyn PhysicalExpression>,
+    high: Box<dyn PhysicalExpression>,
+}
+
+impl BetweenExpression {
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        low: Box<dyn PhysicalExpression>,
+        high: Box<dyn PhysicalExpression>,
+    ) -> Self {
+        BetweenExpression { expr, low, high }
+    }
+}
+
+impl PhysicalExpression for BetweenExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let value = self.expr.evaluate(input)?;
+        let low = self.low.evaluate(input)?;
+        let high = self.high.evaluate(input)?;
+        let is_scalar = [&value, &low, &high]
+            .iter()
+            .all(|value| matches!(value, ColumnarValue::Scalar(_)));
+        let length = if is_scalar { 1 } else { input.len() };
+        // The value is compared twice, so a scalar is made an array once.
+        let value = match value {
+            ColumnarValue::Array(array) => array,
+            scalar => choose_rows(vec![scalar], &vec![0; length])?,
+        };
+        let threshold = scalar_broadcast_threshold();
+        let low = GtEqExpression::compare(ColumnarValue::Array(value.clone()), low, threshold)?;
+        let high = LtEqExpression::compare(ColumnarValue::Array(value), high, threshold)?;
+        let (low, high) = (low.into_array(length)?, high.into_array(length)?);
+        let result = compute::boolean_kleene::and(boolean_array(&*low)?, boolean_array(&*high)?)
+            .map_err(Error::ArrowError)?;
+        Ok(if is_scalar {
+            ColumnarValue::Scalar(new_scalar(&result, 0))
+        } else {
+            ColumnarValue::Array(Arc::new(result))
+        })
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant() && self.low.is_constant() && self.high.is_constant()
+    }
+}
+
+impl fmt::Display for BetweenExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} BETWEEN {} AND {}", self.expr, self.low, self.high)
+    }
+}
+
+/// Evaluates `left op right` into `out`. A left child that evaluates in place
+/// builds on `out` directly, any other is read once and combined into `out`.
+fn math_into<T: NativeType>(
+    left: &dyn PhysicalExpression,
+    right: ColumnarValue,
+    input: &Chunk<Arc<dyn Array>>,
+    out: &mut MutablePrimitiveArray<T>,
+    op: impl Fn(T, T) -> T,
+) -> Result<(), Error> {
+    if left.evaluates_in_place() {
+        left.evaluate_into(input, out)?;
+        apply_in_place(out, &right, op)
+    } else {
+        let left = left.evaluate(input)?.into_array(input.len())?;
+        let left = left
+            .as_any()
+            .downcast_ref::<PrimitiveArray<T>>()
+            .ok_or(Error::DowncastError)?;
+        binary_into(out, left, &right, op)
+    }
+}
+
+/// Integer division by zero panics in the kernels, so a zero divisor or
+/// modulus is handled before dividing: a zero scalar is an
+/// [`Error::DivisionByZero`], and zeros in an array divide into nulls. Other
+/// operators and float divisors are returned as they are.
+fn checked_divisor(op: Operator, right: ColumnarValue) -> Result<ColumnarValue, Error> {
+    if !matches!(op, Operator::Divide | Operator::Modulo) {
+        return Ok(right);
+    }
+    match value_data_type(&right).to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => null_zeros::<i32>(right),
+        PhysicalType::Primitive(PrimitiveType::Int64) => null_zeros::<i64>(right),
+        _ => Ok(right),
+    }
+}
+
+fn null_zeros<T: NativeType>(right: ColumnarValue) -> Result<ColumnarValue, Error> {
+    let zero = T::default();
+    match right {
+        ColumnarValue::Scalar(scalar) => {
+            let value = scalar
+                .as_any()
+                .downcast_ref::<PrimitiveScalar<T>>()
+                .ok_or(Error::DowncastError)?
+                .value();
+            if value == Some(zero) {
+                return Err(Error::DivisionByZero);
+            }
+            Ok(ColumnarValue::Scalar(scalar))
+        }
+        ColumnarValue::Array(array) => {
+            let values = downcast::<PrimitiveArray<T>>(&*array)?;
+            if !values.iter().any(|value| value == Some(&zero)) {
+                return Ok(ColumnarValue::Array(array));
+            }
+            let validity = values.iter().map(|value| value.is_some_and(|x| *x != zero));
+            Ok(ColumnarValue::Array(Arc::from(array.with_validity(Some(
+                Bitmap::from_trusted_len_iter(validity),
+            )))))
+        }
+    }
+}
+
+macro_rules! mathExpression {
+    (
+        $i: ident,
+        $name1: ident,
+        $name2: expr,
+        $wrapping: ident,
+        [$wrapping_kernel: path, $wrapping_scalar: path],
+        [$checked_kernel: path, $checked_scalar: path],
+        $op: ident,
+        $operator: expr
+    ) => {
+        pub struct $i {
+            left: Box<dyn PhysicalExpression>,
+            right: Box<dyn PhysicalExpression>,
+            overflow: OverflowHandling,
+            broadcast_threshold: usize,
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                let left = self.left.evaluate(input)?;
+                let right = self.right.evaluate(input)?;
+                if let Some(result) = null_operand_result(&left, &right, $operator)? {
+                    return Ok(result);
+                }
+                let (left, right) = coerce_operands(left, right, $operator)?;
+                let right = checked_divisor($operator, right)?;
+                let result = match self.overflow {
+                    OverflowHandling::Wrap => {
+                        integerKernel!(
+                            &left,
+                            &right,
+                            $wrapping_kernel,
+                            $wrapping_scalar,
+                            self.broadcast_threshold
+                        )
+                    }
+                    OverflowHandling::Error => {
+                        integerKernel!(
+                            &left,
+                            &right,
+                            $checked_kernel,
+                            $checked_scalar,
+                            self.broadcast_threshold
+                        )
+                    }
+                };
+                if let Some(result) = result {
+                    if self.overflow == OverflowHandling::Error
+                        && overflowed(&left, &right, &result)
+                    {
+                        return Err(Error::ArithmeticOverflow);
+                    }
+                    return Ok(result);
+                }
+                match (left, right) {
+                    (ColumnarValue::Array(left), ColumnarValue::Array(right)) => {
+                        if left.len() == right.len() {
+                            Ok(ColumnarValue::Array(Arc::from(
+                                compute::arithmetics::$name1(&*left, &*right),
+                            )))
+                        } else {
+                            Err(Error::DifferentSizes(
+                                format!("{:?}", left),
+                                format!("{:?}", right),
+                            ))
+                        }
+                    }
+                    (ColumnarValue::Array(left), ColumnarValue::Scalar(right)) => {
+                        Ok(ColumnarValue::Array(Arc::from(
+                            match broadcast(&*right, left.len(), self.broadcast_threshold)? {
+                                Some(right) => compute::arithmetics::$name1(&*left, &*right),
+                                None => $name2(&*left, &*right)?,
+                            },
+                        )))
+                    }
+                    (ColumnarValue::Scalar(left), ColumnarValue::Array(right)) => {
+                        // The scalar kernels only take the scalar on the right, and
+                        // not every operator commutes.
+                        let left: Arc<dyn Array> = if left.is_valid() {
+                            scalar_to_array(&*left, right.len())?
+                        } else {
+                            Arc::from(new_null_array(left.data_type().clone(), right.len()))
+                        };
+                        Ok(ColumnarValue::Array(Arc::from(
+                            compute::arithmetics::$name1(&*left, &*right),
+                        )))
+                    }
+                    (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
+                        match (
+                            left.data_type().to_physical_type(),
+                            right.data_type().to_physical_type(),
+                        ) {
+                            (
+                                PhysicalType::Primitive(PrimitiveType::Float64),
+                                PhysicalType::Primitive(PrimitiveType::Float64),
+                            ) => {
+                                let (left, right) = (
+                                    left.as_any()
+                                        .downcast_ref::<PrimitiveScalar<f64>>()
+                                        .ok_or(Error::DowncastError)?,
+                                    right
+                                        .as_any()
+                                        .downcast_ref::<PrimitiveScalar<f64>>()
+                                        .ok_or(Error::DowncastError)?,
+                                );
+                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                                    DataType::Float64,
+                                    match (left.value(), right.value()) {
+                                        (Some(left), Some(right)) => Some(left.$op(right)),
+                                        _ => None,
+                                    },
+                                ))))
+                            }
+                            (
+                                PhysicalType::Primitive(PrimitiveType::Float32),
+                                PhysicalType::Primitive(PrimitiveType::Float32),
+                            ) => {
+                                let (left, right) = (
+                                    left.as_any()
+                                        .downcast_ref::<PrimitiveScalar<f32>>()
+                                        .ok_or(Error::DowncastError)?,
+                                    right
+                                        .as_any()
+                                        .downcast_ref::<PrimitiveScalar<f32>>()
+                                        .ok_or(Error::DowncastError)?,
+                                );
+                                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                                    DataType::Float32,
+                                    match (left.value(), right.value()) {
+                                        (Some(left), Some(right)) => Some(left.$op(right)),
+                                        _ => None,
+                                    },
+                                ))))
+                            }
+                            _ => Err(Error::PhysicalTypeNotSuported(format!(
+                                "{:?}",
+                                left.data_type()
+                            ))),
+                        }
+                    }
+                }
+            }
+
+            fn evaluate_into(
+                &self,
+                input: &Chunk<Arc<dyn Array>>,
+                out: &mut dyn MutableArray,
+            ) -> Result<(), Error> {
+                if self.overflow == OverflowHandling::Error {
+                    return copy_value_into(self.evaluate(input)?.into_array(input.len())?, out);
+                }
+                let right = checked_divisor($operator, self.right.evaluate(input)?)?;
+                match out.data_type().to_physical_type() {
+                    PhysicalType::Primitive(PrimitiveType::Int32) => {
+                        math_into::<i32>(&*self.left, right, input, downcast_mut(out)?, |l, r| {
+                            l.$wrapping(r)
+                        })
+                    }
+                    PhysicalType::Primitive(PrimitiveType::Float64) => {
+                        math_into::<f64>(&*self.left, right, input, downcast_mut(out)?, |l, r| {
+                            l.$op(r)
+                        })
+                    }
+                    t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+                }
+            }
+
+            fn evaluates_in_place(&self) -> bool {
+                self.overflow == OverflowHandling::Wrap
+            }
+
+            fn is_constant(&self) -> bool {
+                self.left.is_constant() && self.right.is_constant()
+            }
+        }
+
+        impl $i {
+            pub fn new(
+                left: Box<dyn PhysicalExpression>,
+                right: Box<dyn PhysicalExpression>,
+            ) -> Self {
+                $i {
+                    left,
+                    right,
+                    overflow: OverflowHandling::default(),
+                    broadcast_threshold: scalar_broadcast_threshold(),
+                }
+            }
+
+            pub fn with_overflow_handling(mut self, overflow: OverflowHandling) -> Self {
+                self.overflow = overflow;
+                self
+            }
+
+            /// Overrides [`scalar_broadcast_threshold`] for this expression.
+            pub fn with_broadcast_threshold(mut self, threshold: usize) -> Self {
+                self.broadcast_threshold = threshold;
+                self
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {} {}", self.left, $operator, self.right)
+            }
+        }
+    };
+}
+
+mathExpression!(
+    AddExpression,
+    add,
+    infallible(compute::arithmetics::add_scalar),
+    wrapping_add,
+    [
+        compute::arithmetics::basic::wrapping_add,
+        compute::arithmetics::basic::wrapping_add_scalar
+    ],
+    [
+        compute::arithmetics::basic::checked_add,
+        compute::arithmetics::basic::checked_add_scalar
+    ],
+    add,
+    Operator::Plus
+);
+mathExpression!(
+    SubExpression,
+    sub,
+    infallible(compute::arithmetics::sub_scalar),
+    wrapping_sub,
+    [
+        compute::arithmetics::basic::wrapping_sub,
+        compute::arithmetics::basic::wrapping_sub_scalar
+    ],
+    [
+        compute::arithmetics::basic::checked_sub,
+        compute::arithmetics::basic::checked_sub_scalar
+    ],
+    sub,
+    Operator::Minus
+);
+mathExpression!(
+    MulExpression,
+    mul,
+    infallible(compute::arithmetics::mul_scalar),
+    wrapping_mul,
+    [
+        compute::arithmetics::basic::wrapping_mul,
+        compute::arithmetics::basic::wrapping_mul_scalar
+    ],
+    [
+        compute::arithmetics::basic::checked_mul,
+        compute::arithmetics::basic::checked_mul_scalar
+    ],
+    mul,
+    Operator::Multiply
+);
+mathExpression!(
+    DivExpression,
+    div,
+    infallible(compute::arithmetics::div_scalar),
+    wrapping_div,
+    [wrapping_div, wrapping_div_scalar],
+    [
+        compute::arithmetics::basic::checked_div,
+        compute::arithmetics::basic::checked_div_scalar
+    ],
+    div,
+    Operator::Divide
+);
+/// Adapts one of arrow's array-scalar kernels, which can't fail, to the
+/// signature of [`rem_scalar`].
+fn infallible(
+    kernel: fn(&dyn Array, &dyn Scalar) -> Box<dyn Array>,
+) -> impl Fn(&dyn Array, &dyn Scalar) -> Result<Box<dyn Array>, Error> {
+    move |lhs, rhs| Ok(kernel(lhs, rhs))
+}
+
+/// The array-scalar remainder of a float array. Integers don't get here, see
+/// [`integer_kernel`].
+fn rem_scalar(lhs: &dyn Array, rhs: &dyn Scalar) -> Result<Box<dyn Array>, Error> {
+    fn rem<T>(lhs: &dyn Array, rhs: &dyn Scalar) -> Result<Box<dyn Array>, Error>
+    where
+        T: NativeType + Rem<Output = T>,
+    {
+        let lhs = lhs
+            .as_any()
+            .downcast_ref::<PrimitiveArray<T>>()
+            .ok_or(Error::DowncastError)?;
+        let rhs = rhs
+            .as_any()
+            .downcast_ref::<PrimitiveScalar<T>>()
+            .ok_or(Error::DowncastError)?;
+        Ok(match rhs.value() {
+            Some(rhs) => Box::new(compute::arity::unary(
+                lhs,
+                |value| value % rhs,
+                lhs.data_type().clone(),
+            )),
+            None => new_null_array(lhs.data_type().clone(), lhs.len()),
+        })
+    }
+
+    match (
+        lhs.data_type().to_physical_type(),
+        rhs.data_type().to_physical_type(),
+    ) {
+        (
+            PhysicalType::Primitive(PrimitiveType::Float32),
+            PhysicalType::Primitive(PrimitiveType::Float32),
+        ) => rem::<f32>(lhs, rhs),
+        (
+            PhysicalType::Primitive(PrimitiveType::Float64),
+            PhysicalType::Primitive(PrimitiveType::Float64),
+        ) => rem::<f64>(lhs, rhs),
+        (l, r) => Err(Error::PhysicalTypeNotSuported(format!("{:?} % {:?}", l, r))),
+    }
+}
+
+mathExpression!(
+    ModExpression,
+    rem,
+    rem_scalar,
+    wrapping_rem,
+    [wrapping_rem, wrapping_rem_scalar],
+    [
+        compute::arithmetics::basic::checked_rem,
+        compute::arithmetics::basic::checked_rem_scalar
+    ],
+    rem,
+    Operator::Modulo
+);
+
+/// Casts `value` to `data_type`. Null scalars stay null.
+fn cast_value(value: ColumnarValue, data_type: &DataType) -> Result<ColumnarValue, Error> {
+    let cast = |array: &dyn Array| {
+        compute::cast::cast(array, data_type, CastOptions::default()).map_err(Error::ArrowError)
+    };
+    match value {
+        ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::from(cast(&*array)?))),
+        ColumnarValue::Scalar(scalar) if !scalar.is_valid() => Ok(ColumnarValue::Scalar(
+            new_scalar(&*new_null_array(data_type.clone(), 1), 0),
+        )),
+        ColumnarValue::Scalar(scalar) => {
+            let array = cast(&*scalar_to_array(&*scalar, 1)?)?;
+            Ok(ColumnarValue::Scalar(new_scalar(&*array, 0)))
+        }
+    }
+}
+
+fn promote_to_float64(value: ColumnarValue) -> Result<ColumnarValue, Error> {
+    cast_value(value, &DataType::Float64)
+}
+
+fn value_data_type(value: &ColumnarValue) -> &DataType {
+    match value {
+        ColumnarValue::Array(array) => array.data_type(),
+        ColumnarValue::Scalar(scalar) => scalar.data_type(),
+    }
+}
+
+/// When one operand is a null scalar and the other an array, every row of
+/// `left op right` is null, so the result is built without running a kernel.
+fn null_operand_result(
+    left: &ColumnarValue,
+    right: &ColumnarValue,
+    op: Operator,
+) -> Result<Option<ColumnarValue>, Error> {
+    let length = match (left, right) {
+        (ColumnarValue::Scalar(scalar), ColumnarValue::Array(array))
+        | (ColumnarValue::Array(array), ColumnarValue::Scalar(scalar))
+            if !scalar.is_valid() =>
+        {
+            array.len()
+        }
+        _ => return Ok(None),
+    };
+    let data_type = coerce_types(value_data_type(left), value_data_type(right), op)?;
+    let data_type = if op.is_comparison() {
+        DataType::Boolean
+    } else {
+        data_type
+    };
+    Ok(Some(ColumnarValue::Array(Arc::from(new_null_array(
+        data_type, length,
+    )))))
+}
+
+/// Casts the operands of `op` to their common type, see [`coerce_types`].
+fn coerce_operands(
+    left: ColumnarValue,
+    right: ColumnarValue,
+    op: Operator,
+) -> Result<(ColumnarValue, ColumnarValue), Error> {
+    let (left_type, right_type) = (value_data_type(&left), value_data_type(&right));
+    if left_type == right_type {
+        return Ok((left, right));
+    }
+    let data_type = coerce_types(left_type, right_type, op)?;
+    let cast = |value: ColumnarValue| {
+        if value_data_type(&value) == &data_type {
+            Ok(value)
+        } else {
+            cast_value(value, &data_type)
+        }
+    };
+    Ok((cast(left)?, cast(right)?))
+}
+
+/// Applies `op` with every value of `array` on the left and `scalar` on the
+/// right.
+fn scalar_operand<T: NativeType>(
+    array: &PrimitiveArray<T>,
+    scalar: T,
+    op: impl Fn(T, T) -> T,
+) -> PrimitiveArray<T> {
+    compute::arity::unary(array, |value| op(value, scalar), array.data_type().clone())
+}
+
+macro_rules! bitwiseExpression {
+    ($i: ident, $operator: expr, $op: expr) => {
+        /// A bitwise operator on Int32 and Int64 operands, which are coerced
+        /// like arithmetic ones. Other types are an
+        /// [`Error::IncompatibleTypes`]. The result is null where either
+        /// operand is.
+        pub struct $i {
+            left: Box<dyn PhysicalExpression>,
+            right: Box<dyn PhysicalExpression>,
+            broadcast_threshold: usize,
+        }
+
+        impl $i {
+            pub fn new(
+                left: Box<dyn PhysicalExpression>,
+                right: Box<dyn PhysicalExpression>,
+            ) -> Self {
+                $i {
+                    left,
+                    right,
+                    broadcast_threshold: scalar_broadcast_threshold(),
+                }
+            }
+
+            /// Overrides [`scalar_broadcast_threshold`] for this expression.
+            pub fn with_broadcast_threshold(mut self, threshold: usize) -> Self {
+                self.broadcast_threshold = threshold;
+                self
+            }
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                let left = self.left.evaluate(input)?;
+                let right = self.right.evaluate(input)?;
+                if let Some(result) = null_operand_result(&left, &right, $operator)? {
+                    return Ok(result);
+                }
+                let (left, right) = coerce_operands(left, right, $operator)?;
+                let result = integerKernel!(
+                    &left,
+                    &right,
+                    |l, r| compute::arity::binary(l, r, l.data_type().clone(), $op),
+                    |l, r| scalar_operand(l, *r, $op),
+                    self.broadcast_threshold
+                );
+                match result {
+                    Some(result) => Ok(result),
+                    None if value_data_type(&left) == &DataType::Null => Ok(left),
+                    None => Err(Error::IncompatibleTypes(
+                        format!("{:?}", value_data_type(&left)),
+                        format!("{:?}", value_data_type(&right)),
+                        $operator,
+                    )),
+                }
+            }
+
+            fn is_constant(&self) -> bool {
+                self.left.is_constant() && self.right.is_constant()
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {} {}", self.left, $operator, self.right)
+            }
+        }
+    };
+}
+
+bitwiseExpression!(BitAndExpression, Operator::BitwiseAnd, |l, r| l & r);
+bitwiseExpression!(BitOrExpression, Operator::BitwiseOr, |l, r| l | r);
+bitwiseExpression!(BitXorExpression, Operator::BitwiseXor, |l, r| l ^ r);
+// Shift amounts are taken modulo the width of the type, and shifting right
+// keeps the sign.
+bitwiseExpression!(ShiftLeftExpression, Operator::ShiftLeft, |l, r| l
+    .wrapping_shl(r as u32));
+bitwiseExpression!(ShiftRightExpression, Operator::ShiftRight, |l, r| l
+    .wrapping_shr(r as u32));
+
+/// Evaluates its child and casts the result to Float64.
+struct Float64Expression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl PhysicalExpression for Float64Expression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        promote_to_float64(self.expr.evaluate(input)?)
+    }
+}
+
+impl fmt::Display for Float64Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CAST({} AS {:?})", self.expr, DataType::Float64)
+    }
+}
+
+/// Converts its child to another type with arrow's cast kernel, for example
+/// between Int32, Int64 and Float64 or between numbers and strings. Strings
+/// that don't parse as the target type become null.
+pub struct CastExpression {
+    expr: Box<dyn PhysicalExpression>,
+    data_type: DataType,
+}
+
+impl CastExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, data_type: DataType) -> Self {
+        CastExpression { expr, data_type }
+    }
+}
+
+impl PhysicalExpression for CastExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        cast_value(self.expr.evaluate(input)?, &self.data_type)
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for CastExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CAST({} AS {:?})", self.expr, self.data_type)
+    }
+}
+
+/// Division that always produces Float64, so `7 / 2` is `3.5`.
+///
+/// Integer operands are promoted before dividing. The planner uses
+/// [`DivExpression`] by default, which keeps the operands' type and therefore
+/// truncates integer division (`7 / 2` is `3`).
+pub struct FloatDivExpression {
+    div: DivExpression,
+}
+
+impl FloatDivExpression {
+    pub fn new(left: Box<dyn PhysicalExpression>, right: Box<dyn PhysicalExpression>) -> Self {
+        FloatDivExpression {
+            div: DivExpression::new(
+                Box::new(Float64Expression { expr: left }),
+                Box::new(Float64Expression { expr: right }),
+            ),
+        }
+    }
+}
+
+impl PhysicalExpression for FloatDivExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.div.evaluate(input)
+    }
+}
+
+impl fmt::Display for FloatDivExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.div)
+    }
+}
+
+pub trait Accumulator: AsAnyBox {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error>;
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error>;
+    /// Adds the state of `other`, an accumulator created by the same
+    /// expression over other rows of the group, so that the rows of a group
+    /// can be accumulated in parts, e.g. on several threads, and combined.
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error>;
+}
+
+/// Lets `merge` recover the concrete type of its argument. Every accumulator
+/// gets it from the blanket implementation.
+pub trait AsAnyBox {
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Accumulator + 'static> AsAnyBox for T {
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// Recovers the concrete type of an accumulator passed to `merge`.
+fn downcast_accumulator<A: Accumulator + 'static>(
+    accumulator: Box<dyn Accumulator>,
+) -> Result<Box<A>, Error> {
+    accumulator
+        .into_any()
+        .downcast::<A>()
+        .map_err(|_| Error::DowncastError)
+}
+
+pub trait PhysicalAggregateExpression: PhysicalExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator>;
+
+    /// The input column whose smallest or largest value is the result, for
+    /// MIN and MAX of a bare column. Input sorted on that column only has to
+    /// be read at its first and last non-null rows.
+    fn extremum_column(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// How MIN/MAX treat NaN values in Float32 and Float64 input.
+///
+/// Comparisons against NaN are always false, so without a rule the result
+/// would depend on the order in which values arrive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanHandling {
+    /// NaN values are ignored, like nulls. A group containing only NaN and
+    /// null values finalizes to null.
+    #[default]
+    Skip,
+    /// Any NaN in the group makes the result NaN.
+    Propagate,
+}
+
+fn is_nan(array: &dyn Array) -> Vec<bool> {
+    if let Some(array) = array.as_any().downcast_ref::<PrimitiveArray<f32>>() {
+        return array
+            .iter()
+            .map(|value| value.is_some_and(|value| value.is_nan()))
+            .collect();
+    }
+    array
+        .as_any()
+        .downcast_ref::<PrimitiveArray<f64>>()
+        .map(|array| {
+            array
+                .iter()
+                .map(|value| value.is_some_and(|value| value.is_nan()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+type AggregateKernel = fn(&dyn Array) -> Result<Box<dyn Scalar>, ArrowError>;
+
+/// Reduces `array` with `aggregate` after applying `nan` to its NaN values.
+fn aggregate_with_nan_handling(
+    array: Box<dyn Array>,
+    nan: NanHandling,
+    aggregate: AggregateKernel,
+) -> Result<Box<dyn Scalar>, Error> {
+    let is_nan = is_nan(&*array);
+    if !is_nan.contains(&true) {
+        return aggregate(&*array).map_err(Error::ArrowError);
+    }
+    match nan {
+        NanHandling::Skip => {
+            let not_nan = Bitmap::from_trusted_len_iter(is_nan.into_iter().map(|x| !x));
+            let validity = match array.validity() {
+                Some(validity) => validity.bitand(&not_nan),
+                None => not_nan,
+            };
+            aggregate(&*array.with_validity(Some(validity))).map_err(Error::ArrowError)
+        }
+        NanHandling::Propagate => Ok(match array.data_type() {
+            DataType::Float32 => Box::new(PrimitiveScalar::new(DataType::Float32, Some(f32::NAN))),
+            _ => Box::new(PrimitiveScalar::new(DataType::Float64, Some(f64::NAN))),
+        }),
+    }
+}
+
+macro_rules! aggregateExpression {
+    ($acc: ident,$expr: ident, $name1: ident, $name2: ident, $op_name: expr) => {
+        pub struct $acc {
+            value: Box<dyn Scalar>,
+            index: usize,
+            nan: NanHandling,
+        }
+
+        impl Accumulator for $acc {
+            fn accumulate(
+                &mut self,
+                input: &[ColumnarValue],
+                validity: Option<&Bitmap>,
+            ) -> Result<(), Error> {
+                let expr = &input[self.index];
+                let new = match expr {
+                    ColumnarValue::Array(expr) => {
+                        let val = match (expr.validity(), validity) {
+                            (Some(val1), Some(val2)) => Some(val1.bitand(val2)),
+                            (Some(val), None) => Some(val.clone()),
+                            (None, Some(val)) => Some(val.clone()),
+                            (None, None) => None,
+                        };
+                        aggregate_with_nan_handling(
+                            (expr.borrow() as &dyn Array).with_validity(val),
+                            self.nan,
+                            compute::aggregate::$name1,
+                        )
+                    }
+                    ColumnarValue::Scalar(scalar) => match scalar.data_type().to_physical_type() {
+                        PhysicalType::Primitive(PrimitiveType::Float64) => {
+                            let scalar = scalar
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f64>>()
+                                .ok_or(Error::DowncastError)?;
+                            let value = scalar
+                                .value()
+                                .filter(|value| !(value.is_nan() && self.nan == NanHandling::Skip));
+                            Ok(Box::new(PrimitiveScalar::new(DataType::Float64, value))
+                                as Box<dyn Scalar>)
+                        }
+                        PhysicalType::Primitive(PrimitiveType::Float32) => {
+                            let scalar = scalar
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?;
+                            let value = scalar
+                                .value()
+                                .filter(|value| !(value.is_nan() && self.nan == NanHandling::Skip));
+                            Ok(Box::new(PrimitiveScalar::new(DataType::Float32, value))
+                                as Box<dyn Scalar>)
+                        }
+                        PhysicalType::Primitive(PrimitiveType::Int32) => Ok(Box::new(
+                            scalar
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<i32>>()
+                                .ok_or(Error::DowncastError)?
+                                .clone(),
+                        )
+                            as Box<dyn Scalar>),
+                        PhysicalType::Utf8 => Ok(Box::new(
+                            scalar
+                                .as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?
+                                .clone(),
+                        ) as Box<dyn Scalar>),
+                        PhysicalType::Boolean => Ok(Box::new(
+                            scalar
+                                .as_any()
+                                .downcast_ref::<BooleanScalar>()
+                                .ok_or(Error::DowncastError)?
+                                .clone(),
+                        ) as Box<dyn Scalar>),
+                        x => Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+                    },
+                }?;
+                self.update(new)
+            }
+            fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+                Ok(ColumnarValue::Scalar(self.value))
+            }
+            fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+                let other = downcast_accumulator::<$acc>(other)?;
+                // The other accumulator may not have seen any values.
+                if other.value.data_type() == &DataType::Null {
+                    return Ok(());
+                }
+                self.update(other.value)
+            }
+        }
+
+        impl $acc {
+            /// Keeps `new` if it beats the current value.
+            fn update(&mut self, new: Box<dyn Scalar>) -> Result<(), Error> {
+                let bool = match (
+                    new.data_type().to_physical_type(),
+                    self.value.data_type().to_physical_type(),
+                ) {
+                    (
+                        PhysicalType::Primitive(PrimitiveType::Float64),
+                        PhysicalType::Primitive(PrimitiveType::Float64),
+                    ) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<PrimitiveScalar<f64>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f64>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        // A NaN only gets here when it propagates, and then it wins.
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => {
+                                Ok(left.is_nan() || (!right.is_nan() && left.$name2(&right)))
+                            }
+                            (Some(_), None) => Ok(true),
+                            (None, _) => Ok(false),
+                        }
+                    }
+                    (
+                        PhysicalType::Primitive(PrimitiveType::Float32),
+                        PhysicalType::Primitive(PrimitiveType::Float32),
+                    ) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<f32>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => {
+                                Ok(left.is_nan() || (!right.is_nan() && left.$name2(&right)))
+                            }
+                            (Some(_), None) => Ok(true),
+                            (None, _) => Ok(false),
+                        }
+                    }
+                    (
+                        PhysicalType::Primitive(PrimitiveType::Int32),
+                        PhysicalType::Primitive(PrimitiveType::Int32),
+                    ) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<PrimitiveScalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<PrimitiveScalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => Ok(left.$name2(&right)),
+                            (Some(_), None) => Ok(true),
+                            (None, _) => Ok(false),
+                        }
+                    }
+                    (PhysicalType::Primitive(PrimitiveType::Float64), PhysicalType::Null) => {
+                        Ok(true)
+                    }
+                    (PhysicalType::Primitive(PrimitiveType::Float32), PhysicalType::Null) => {
+                        Ok(true)
+                    }
+                    (PhysicalType::Utf8, PhysicalType::Utf8) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<Utf8Scalar<i32>>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => Ok(left.$name2(right)),
+                            (Some(_), None) => Ok(true),
+                            (None, _) => Ok(false),
+                        }
+                    }
+                    (PhysicalType::Boolean, PhysicalType::Boolean) => {
+                        let (left, right) = (
+                            new.as_any()
+                                .downcast_ref::<BooleanScalar>()
+                                .ok_or(Error::DowncastError)?,
+                            self.value
+                                .as_any()
+                                .downcast_ref::<BooleanScalar>()
+                                .ok_or(Error::DowncastError)?,
+                        );
+                        match (left.value(), right.value()) {
+                            (Some(left), Some(right)) => Ok(left.$name2(&right)),
+                            (Some(_), None) => Ok(true),
+                            (None, _) => Ok(false),
+                        }
+                    }
+                    (PhysicalType::Primitive(PrimitiveType::Int32), PhysicalType::Null) => Ok(true),
+                    (PhysicalType::Utf8 | PhysicalType::Boolean, PhysicalType::Null) => Ok(true),
+                    _ => Err(Error::PhysicalTypeNotSuported(format!(
+                        "{:?}, {:?}",
+                        new.data_type(),
+                        self.value.data_type()
+                    ))),
+                }?;
+                if bool {
+                    self.value = new;
+                };
+                Ok(())
+            }
+        }
+        pub struct $expr {
+            expr: Box<dyn PhysicalExpression>,
+            nan: NanHandling,
+        }
+
+        impl PhysicalExpression for $expr {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                self.expr.evaluate(input)
+            }
+        }
+
+        impl PhysicalAggregateExpression for $expr {
+            fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+                Box::new($acc {
+                    value: Box::new(NullScalar::new()),
+                    index,
+                    nan: self.nan,
+                })
+            }
+
+            fn extremum_column(&self) -> Option<usize> {
+                self.expr.column_index()
+            }
+        }
+
+        impl $expr {
+            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+                $expr {
+                    expr,
+                    nan: NanHandling::default(),
+                }
+            }
+
+            pub fn with_nan_handling(mut self, nan: NanHandling) -> Self {
+                self.nan = nan;
+                self
+            }
+        }
+
+        impl fmt::Display for $expr {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{} {}", $op_name, self.expr)
+            }
+        }
+    };
+}
+
+aggregateExpression!(MaxAccumulator, MaxExpression, max, gt, "max".to_string());
+aggregateExpression!(MinAccumulator, MinExpression, min, lt, "min".to_string());
+
+/// Masks out the rows of `array` that are not part of the group being
+/// accumulated, on top of the array's own nulls.
+fn with_group_validity(array: &dyn Array, validity: Option<&Bitmap>) -> Box<dyn Array> {
+    let validity = match (array.validity(), validity) {
+        (Some(val1), Some(val2)) => Some(val1.bitand(val2)),
+        (Some(val), None) => Some(val.clone()),
+        (None, Some(val)) => Some(val.clone()),
+        (None, None) => None,
+    };
+    array.with_validity(validity)
+}
+
+/// The values of an aggregate input that belong to the group being
+/// accumulated. A scalar input counts as a single value.
+fn group_values(value: &ColumnarValue, validity: Option<&Bitmap>) -> Result<Box<dyn Array>, Error> {
+    match value {
+        ColumnarValue::Array(array) => Ok(with_group_validity(&**array, validity)),
+        ColumnarValue::Scalar(scalar) => Ok(scalar_to_array(&**scalar, 1)?.slice(0, 1)),
+    }
+}
+
+fn downcast<A: Array + 'static>(array: &dyn Array) -> Result<&A, Error> {
+    array
+        .as_any()
+        .downcast_ref::<A>()
+        .ok_or(Error::DowncastError)
+}
+
+// Sum
+
+enum Total {
+    Int64(i64),
+    Float32(f32),
+    Float64(f64),
+}
+
+/// Adds up the non-null values of a group, starting from zero, one batch sum
+/// at a time. Int32 and Int64 are summed as Int64, which wraps on overflow like
+/// the arithmetic kernels, and Boolean as the Int64 number of true values, see
+/// [`count_true`]. A group without any values finalizes to null, like SQL's
+/// `SUM`.
+pub struct SumAccumulator {
+    total: Option<Total>,
+    index: usize,
+}
+
+impl SumAccumulator {
+    fn add_int64(&mut self, sum: Option<i64>) -> Result<(), Error> {
+        let total = match self.total {
+            None => 0,
+            Some(Total::Int64(total)) => total,
+            Some(_) => return Err(Error::DowncastError),
+        };
+        if let Some(sum) = sum {
+            self.total = Some(Total::Int64(total.wrapping_add(sum)));
+        }
+        Ok(())
+    }
+
+    fn add_float32(&mut self, sum: Option<f32>) -> Result<(), Error> {
+        let total = match self.total {
+            None => 0.0,
+            Some(Total::Float32(total)) => total,
+            Some(_) => return Err(Error::DowncastError),
+        };
+        if let Some(sum) = sum {
+            self.total = Some(Total::Float32(total + sum));
+        }
+        Ok(())
+    }
+
+    fn add_float64(&mut self, sum: Option<f64>) -> Result<(), Error> {
+        let total = match self.total {
+            None => 0.0,
+            Some(Total::Float64(total)) => total,
+            Some(_) => return Err(Error::DowncastError),
+        };
+        if let Some(sum) = sum {
+            self.total = Some(Total::Float64(total + sum));
+        }
+        Ok(())
+    }
+}
+
+impl Accumulator for SumAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        match array.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => {
+                // Widened before summing, so that a single batch can't overflow.
+                let array = compute::cast::primitive_to_primitive::<i32, i64>(
+                    downcast::<PrimitiveArray<i32>>(&*array)?,
+                    &DataType::Int64,
+                );
+                self.add_int64(compute::aggregate::sum_primitive(&array))
+            }
+            PhysicalType::Primitive(PrimitiveType::Int64) => {
+                self.add_int64(compute::aggregate::sum_primitive(downcast::<
+                    PrimitiveArray<i64>,
+                >(
+                    &*array
+                )?))
+            }
+            PhysicalType::Primitive(PrimitiveType::Float32) => {
+                self.add_float32(compute::aggregate::sum_primitive(downcast::<
+                    PrimitiveArray<f32>,
+                >(
+                    &*array
+                )?))
+            }
+            PhysicalType::Primitive(PrimitiveType::Float64) => {
+                self.add_float64(compute::aggregate::sum_primitive(downcast::<
+                    PrimitiveArray<f64>,
+                >(
+                    &*array
+                )?))
+            }
+            PhysicalType::Boolean => {
+                let sum = (array.null_count() < array.len())
+                    .then(|| downcast::<BooleanArray>(&*array).map(count_true))
+                    .transpose()?;
+                self.add_int64(sum.map(|count| count as i64))
+            }
+            t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+        }
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(match self.total {
+            Some(Total::Int64(total)) => {
+                Box::new(PrimitiveScalar::new(DataType::Int64, Some(total)))
+            }
+            Some(Total::Float32(total)) => {
+                Box::new(PrimitiveScalar::new(DataType::Float32, Some(total)))
+            }
+            Some(Total::Float64(total)) => {
+                Box::new(PrimitiveScalar::new(DataType::Float64, Some(total)))
+            }
+            None => Box::new(NullScalar::new()),
+        }))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        match downcast_accumulator::<Self>(other)?.total {
+            Some(Total::Int64(total)) => self.add_int64(Some(total)),
+            Some(Total::Float32(total)) => self.add_float32(Some(total)),
+            Some(Total::Float64(total)) => self.add_float64(Some(total)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct SumExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl SumExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        SumExpression { expr }
+    }
+}
+
+impl PhysicalExpression for SumExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for SumExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(SumAccumulator { total: None, index })
+    }
+}
+
+impl fmt::Display for SumExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sum {}", self.expr)
+    }
+}
+
+// Avg
+
+/// Averages the non-null values of a group as Float64. A group without any
+/// values finalizes to null.
+pub struct AvgAccumulator {
+    sum: f64,
+    count: i64,
+    index: usize,
+}
+
+impl AvgAccumulator {
+    fn add(&mut self, values: impl Iterator<Item = Option<f64>>) {
+        for value in values.flatten() {
+            self.sum += value;
+            self.count += 1;
+        }
+    }
+}
+
+impl Accumulator for AvgAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        match array.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => self.add(
+                downcast::<PrimitiveArray<i32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| *x as f64)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Int64) => self.add(
+                downcast::<PrimitiveArray<i64>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| *x as f64)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float32) => self.add(
+                downcast::<PrimitiveArray<f32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| *x as f64)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float64) => self.add(
+                downcast::<PrimitiveArray<f64>>(&*array)?
+                    .iter()
+                    .map(|x| x.copied()),
+            ),
+            t => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+        }
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(if self.count == 0 {
+            Box::new(NullScalar::new())
+        } else {
+            Box::new(PrimitiveScalar::new(
+                DataType::Float64,
+                Some(self.sum / self.count as f64),
+            ))
+        }))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        let other = downcast_accumulator::<Self>(other)?;
+        self.sum += other.sum;
+        self.count += other.count;
+        Ok(())
+    }
+}
+
+pub struct AvgExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl AvgExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        AvgExpression { expr }
+    }
+}
+
+impl PhysicalExpression for AvgExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for AvgExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(AvgAccumulator {
+            sum: 0.0,
+            count: 0,
+            index,
+        })
+    }
+}
+
+impl fmt::Display for AvgExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "avg {}", self.expr)
+    }
+}
+
+// Product
+
+enum Product {
+    Int(i64),
+    Float(f64),
+}
+
+/// Multiplies the non-null values of a group. Integers are widened to Int64
+/// and, like the arithmetic kernels, wrap on overflow. Integer and float
+/// values in the same group are an [`Error::IncompatibleTypes`].
+pub struct ProductAccumulator {
+    product: Option<Product>,
+    index: usize,
+}
+
+impl ProductAccumulator {
+    /// Multiplies in `values`, which come from an array of `data_type`.
+    fn multiply_int(
+        &mut self,
+        values: impl Iterator<Item = Option<i64>>,
+        data_type: &DataType,
+    ) -> Result<(), Error> {
+        for value in values.flatten() {
+            self.product = match self.product {
+                None => Some(Product::Int(value)),
+                Some(Product::Int(product)) => Some(Product::Int(product.wrapping_mul(value))),
+                Some(Product::Float(_)) => return Err(incompatible_product(data_type)),
+            };
+        }
+        Ok(())
+    }
+
+    /// Multiplies in `values`, which come from an array of `data_type`.
+    fn multiply_float(
+        &mut self,
+        values: impl Iterator<Item = Option<f64>>,
+        data_type: &DataType,
+    ) -> Result<(), Error> {
+        for value in values.flatten() {
+            self.product = match self.product {
+                None => Some(Product::Float(value)),
+                Some(Product::Float(product)) => Some(Product::Float(product * value)),
+                Some(Product::Int(_)) => return Err(incompatible_product(data_type)),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Values of `data_type` can't be multiplied into a product of the other kind,
+/// which is Int64 for floats and Float64 for integers.
+fn incompatible_product(data_type: &DataType) -> Error {
+    let product = match data_type {
+        DataType::Float32 | DataType::Float64 => DataType::Int64,
+        _ => DataType::Float64,
+    };
+    Error::IncompatibleTypes(
+        format!("{:?}", product),
+        format!("{:?}", data_type),
+        Operator::Multiply,
+    )
+}
+
+impl Accumulator for ProductAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        let data_type = array.data_type();
+        match data_type.to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => self.multiply_int(
+                downcast::<PrimitiveArray<i32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| *x as i64)),
+                data_type,
+            ),
+            PhysicalType::Primitive(PrimitiveType::Int64) => self.multiply_int(
+                downcast::<PrimitiveArray<i64>>(&*array)?
+                    .iter()
+                    .map(|x| x.copied()),
+                data_type,
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float32) => self.multiply_float(
+                downcast::<PrimitiveArray<f32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| *x as f64)),
+                data_type,
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float64) => self.multiply_float(
+                downcast::<PrimitiveArray<f64>>(&*array)?
+                    .iter()
+                    .map(|x| x.copied()),
+                data_type,
+            ),
+            t => Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+        }
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(match self.product {
+            Some(Product::Int(product)) => {
+                Box::new(PrimitiveScalar::new(DataType::Int64, Some(product)))
+            }
+            Some(Product::Float(product)) => {
+                Box::new(PrimitiveScalar::new(DataType::Float64, Some(product)))
+            }
+            None => Box::new(NullScalar::new()),
+        }))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        match downcast_accumulator::<Self>(other)?.product {
+            Some(Product::Int(product)) => {
+                self.multiply_int(std::iter::once(Some(product)), &DataType::Int64)
+            }
+            Some(Product::Float(product)) => {
+                self.multiply_float(std::iter::once(Some(product)), &DataType::Float64)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct ProductExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl ProductExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        ProductExpression { expr }
+    }
+}
+
+impl PhysicalExpression for ProductExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for ProductExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(ProductAccumulator {
+            product: None,
+            index,
+        })
+    }
+}
+
+impl fmt::Display for ProductExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "product {}", self.expr)
+    }
+}
+
+// Mode
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum ModeKey {
+    Int32(i32),
+    // Floats are keyed by their bits, with -0.0 folded into 0.0.
+    Float64(u64),
+    Utf8(String),
+}
+
+/// Finds the most frequent non-null value of a group. Ties go to the value
+/// seen first.
+///
+/// Every distinct value of the group is kept in memory together with its count.
+pub struct ModeAccumulator {
+    positions: HashMap<ModeKey, usize>,
+    counts: Vec<(ModeKey, usize)>,
+    index: usize,
+}
+
+impl ModeAccumulator {
+    fn count(&mut self, keys: impl Iterator<Item = Option<ModeKey>>) {
+        for key in keys.flatten() {
+            self.add(key, 1);
+        }
+    }
+
+    fn add(&mut self, key: ModeKey, count: usize) {
+        match self.positions.get(&key) {
+            Some(position) => self.counts[*position].1 += count,
+            None => {
+                self.positions.insert(key.clone(), self.counts.len());
+                self.counts.push((key, count));
+            }
+        }
+    }
+}
+
+impl Accumulator for ModeAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        match array.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => self.count(
+                downcast::<PrimitiveArray<i32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| ModeKey::Int32(*x))),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float64) => self.count(
+                downcast::<PrimitiveArray<f64>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| ModeKey::Float64((*x + 0.0).to_bits()))),
+            ),
+            PhysicalType::Utf8 => self.count(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|x| x.map(|x| ModeKey::Utf8(x.to_string()))),
+            ),
+            t => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+        };
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        let mode =
+            self.counts
+                .into_iter()
+                .fold(
+                    None,
+                    |mode: Option<(ModeKey, usize)>, (key, count)| match mode {
+                        Some(mode) if mode.1 >= count => Some(mode),
+                        _ => Some((key, count)),
+                    },
+                );
+        Ok(ColumnarValue::Scalar(match mode {
+            Some((ModeKey::Int32(value), _)) => {
+                Box::new(PrimitiveScalar::new(DataType::Int32, Some(value)))
+            }
+            Some((ModeKey::Float64(value), _)) => Box::new(PrimitiveScalar::new(
+                DataType::Float64,
+                Some(f64::from_bits(value)),
+            )),
+            Some((ModeKey::Utf8(value), _)) => Box::new(Utf8Scalar::<i32>::new(Some(value))),
+            None => Box::new(NullScalar::new()),
+        }))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        for (key, count) in downcast_accumulator::<Self>(other)?.counts {
+            self.add(key, count);
+        }
+        Ok(())
+    }
+}
+
+pub struct ModeExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl ModeExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        ModeExpression { expr }
+    }
+}
+
+impl PhysicalExpression for ModeExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for ModeExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(ModeAccumulator {
+            positions: HashMap::new(),
+            counts: vec![],
+            index,
+        })
+    }
+}
+
+impl fmt::Display for ModeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "mode {}", self.expr)
+    }
+}
+
+// Two-input statistics
+
+/// Evaluates both inputs of a two-input aggregate and packs them, cast to
+/// Float64, into a struct array with fields `x` (left) and `y` (right).
+fn evaluate_pair(
+    x: &dyn PhysicalExpression,
+    y: &dyn PhysicalExpression,
+    input: &Chunk<Arc<dyn Array>>,
+) -> Result<ColumnarValue, Error> {
+    let length = input.len();
+    let x = promote_to_float64(x.evaluate(input)?)?.into_array(length)?;
+    let y = promote_to_float64(y.evaluate(input)?)?.into_array(length)?;
+    let fields = vec![
+        Field::new("x", DataType::Float64, true),
+        Field::new("y", DataType::Float64, true),
+    ];
+    Ok(ColumnarValue::Array(Arc::new(StructArray::new(
+        DataType::Struct(fields),
+        vec![x, y],
+        None,
+    ))))
+}
+
+/// Running sums over the rows of a group where both inputs are non-null.
+#[derive(Default)]
+struct Moments {
+    count: f64,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xx: f64,
+    sum_yy: f64,
+    sum_xy: f64,
+}
+
+impl Moments {
+    fn update(&mut self, value: &ColumnarValue, validity: Option<&Bitmap>) -> Result<(), Error> {
+        let array = group_values(value, validity)?;
+        let pair = downcast::<StructArray>(&*array)?;
+        let x = downcast::<PrimitiveArray<f64>>(&*pair.values()[0])?;
+        let y = downcast::<PrimitiveArray<f64>>(&*pair.values()[1])?;
+        for (i, (x, y)) in x.iter().zip(y.iter()).enumerate() {
+            if let (true, Some(x), Some(y)) = (pair.is_valid(i), x, y) {
+                self.count += 1.0;
+                self.sum_x += x;
+                self.sum_y += y;
+                self.sum_xx += x * x;
+                self.sum_yy += y * y;
+                self.sum_xy += x * y;
+            }
+        }
+        Ok(())
+    }
+
+    fn merge(&mut self, other: &Moments) {
+        self.count += other.count;
+        self.sum_x += other.sum_x;
+        self.sum_y += other.sum_y;
+        self.sum_xx += other.sum_xx;
+        self.sum_yy += other.sum_yy;
+        self.sum_xy += other.sum_xy;
+    }
+
+    /// `n` times the variance of x.
+    fn sxx(&self) -> f64 {
+        self.sum_xx - self.sum_x * self.sum_x / self.count
+    }
+
+    fn syy(&self) -> f64 {
+        self.sum_yy - self.sum_y * self.sum_y / self.count
+    }
+
+    fn sxy(&self) -> f64 {
+        self.sum_xy - self.sum_x * self.sum_y / self.count
+    }
+
+    /// Whether `deviation`, `n` times a variance, is within the rounding
+    /// error of the `sum_squares` it was computed from, as it is for a
+    /// constant column.
+    fn within_rounding(&self, deviation: f64, sum_squares: f64) -> bool {
+        deviation <= self.count * f64::EPSILON * sum_squares
+    }
+}
+
+/// Sample covariance. Groups with fewer than two rows have none.
+fn covar(moments: &Moments) -> Option<f64> {
+    (moments.count > 1.0).then(|| moments.sxy() / (moments.count - 1.0))
+}
+
+/// Pearson correlation. Undefined when either input has zero variance, up
+/// to rounding.
+fn corr(moments: &Moments) -> Option<f64> {
+    let (sxx, syy) = (moments.sxx(), moments.syy());
+    let varies = !moments.within_rounding(sxx, moments.sum_xx)
+        && !moments.within_rounding(syy, moments.sum_yy);
+    (moments.count > 0.0 && varies).then(|| moments.sxy() / (sxx * syy).sqrt())
+}
+
+macro_rules! momentsExpression {
+    ($accumulator_name: ident, $expression_name: ident, $fn: ident, $op_name: expr) => {
+        pub struct $accumulator_name {
+            moments: Moments,
+            index: usize,
+        }
+
+        impl Accumulator for $accumulator_name {
+            fn accumulate(
+                &mut self,
+                input: &[ColumnarValue],
+                validity: Option<&Bitmap>,
+            ) -> Result<(), Error> {
+                self.moments.update(&input[self.index], validity)
+            }
+            fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+                Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+                    DataType::Float64,
+                    $fn(&self.moments),
+                ))))
+            }
+            fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+                let other = downcast_accumulator::<Self>(other)?;
+                self.moments.merge(&other.moments);
+                Ok(())
+            }
+        }
+
+        pub struct $expression_name {
+            left: Box<dyn PhysicalExpression>,
+            right: Box<dyn PhysicalExpression>,
+        }
+
+        impl $expression_name {
+            pub fn new(
+                left: Box<dyn PhysicalExpression>,
+                right: Box<dyn PhysicalExpression>,
+            ) -> Self {
+                $expression_name { left, right }
+            }
+        }
+
+        impl PhysicalExpression for $expression_name {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                evaluate_pair(&*self.left, &*self.right, input)
+            }
+        }
+
+        impl PhysicalAggregateExpression for $expression_name {
+            fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+                Box::new($accumulator_name {
+                    moments: Moments::default(),
+                    index,
+                })
+            }
+        }
+
+        impl fmt::Display for $expression_name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({}, {})", $op_name, self.left, self.right)
+            }
+        }
+    };
+}
+
+momentsExpression!(CovarAccumulator, CovarExpression, covar, "covar");
+momentsExpression!(CorrAccumulator, CorrExpression, corr, "corr");
+
+// `REGR_SLOPE(y, x)` and `REGR_INTERCEPT(y, x)` take the dependent variable
+// first, so the moments' `x` holds y and their `y` holds x here.
+
+/// Least-squares slope. Undefined when x has zero variance, up to rounding.
+fn regr_slope(moments: &Moments) -> Option<f64> {
+    let syy = moments.syy();
+    let varies = !moments.within_rounding(syy, moments.sum_yy);
+    (moments.count > 0.0 && varies).then(|| moments.sxy() / syy)
+}
+
+/// Least-squares intercept. Undefined when x has zero variance.
+fn regr_intercept(moments: &Moments) -> Option<f64> {
+    regr_slope(moments).map(|slope| (moments.sum_x - slope * moments.sum_y) / moments.count)
+}
+
+momentsExpression!(
+    RegrSlopeAccumulator,
+    RegrSlopeExpression,
+    regr_slope,
+    "regr_slope"
+);
+momentsExpression!(
+    RegrInterceptAccumulator,
+    RegrInterceptExpression,
+    regr_intercept,
+    "regr_intercept"
+);
+
+// Width bucket
+
+/// Assigns numeric values to `count` equal-width buckets over `[low, high)`,
+/// numbered from 1 like SQL `WIDTH_BUCKET`. Values below `low` go to bucket 0,
+/// values at or above `high` to bucket `count + 1`. Nulls stay null.
+pub struct WidthBucketExpression {
+    expr: Box<dyn PhysicalExpression>,
+    low: f64,
+    high: f64,
+    count: i32,
+}
+
+impl WidthBucketExpression {
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        low: f64,
+        high: f64,
+        count: i32,
+    ) -> Result<Self, Error> {
+        if count <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "width_bucket count must be positive, got {}",
+                count
+            )));
+        }
+        if low.is_nan() || high.is_nan() || low >= high {
+            return Err(Error::InvalidArgument(format!(
+                "width_bucket needs low < high, got [{}, {}]",
+                low, high
+            )));
+        }
+        Ok(WidthBucketExpression {
+            expr,
+            low,
+            high,
+            count,
+        })
+    }
+
+    fn bucket(&self, value: f64) -> i32 {
+        if value < self.low {
+            0
+        } else if value >= self.high {
+            self.count + 1
+        } else {
+            let width = (self.high - self.low) / self.count as f64;
+            // Rounding can push values just below `high` past the last bucket.
+            (((value - self.low) / width) as i32 + 1).min(self.count)
+        }
+    }
+
+    fn buckets(&self, array: &dyn Array) -> Result<PrimitiveArray<i32>, Error> {
+        Ok(downcast::<PrimitiveArray<f64>>(array)?
+            .iter()
+            .map(|value| value.map(|value| self.bucket(*value)))
+            .collect::<PrimitiveArray<i32>>())
+    }
+}
+
+impl PhysicalExpression for WidthBucketExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match promote_to_float64(self.expr.evaluate(input)?)? {
+            ColumnarValue::Array(array) => {
+                Ok(ColumnarValue::Array(Arc::new(self.buckets(&*array)?)))
+            }
+            ColumnarValue::Scalar(scalar) => {
+                let array = self.buckets(&*scalar_to_array(&*scalar, 1)?)?;
+                Ok(ColumnarValue::Scalar(new_scalar(&array, 0)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for WidthBucketExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "width_bucket({}, {}, {}, {})",
+            self.expr, self.low, self.high, self.count
+        )
+    }
+}
+
+// Rounding
+
+/// Applies `f` to every non-null value of `value`, promoted to Float64. Rows
+/// where `f` returns `None` become null.
+fn map_float64(
+    value: ColumnarValue,
+    f: impl Fn(f64) -> Option<f64>,
+) -> Result<ColumnarValue, Error> {
+    let map = |array: &dyn Array| -> Result<PrimitiveArray<f64>, Error> {
+        Ok(downcast::<PrimitiveArray<f64>>(array)?
+            .iter()
+            .map(|value| value.and_then(|value| f(*value)))
+            .collect())
+    };
+    match promote_to_float64(value)? {
+        ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(map(&*array)?))),
+        ColumnarValue::Scalar(scalar) => {
+            let array = map(&*scalar_to_array(&*scalar, 1)?)?;
+            Ok(ColumnarValue::Scalar(new_scalar(&array, 0)))
+        }
+    }
+}
+
+macro_rules! floatFunctionExpression {
+    ($i: ident, $fn: expr, $name: expr) => {
+        /// A Float64 function of a numeric expression. Integers are promoted
+        /// first and nulls stay null.
+        pub struct $i {
+            expr: Box<dyn PhysicalExpression>,
+        }
+
+        impl $i {
+            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+                $i { expr }
+            }
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                map_float64(self.expr.evaluate(input)?, $fn)
+            }
+
+            fn is_constant(&self) -> bool {
+                self.expr.is_constant()
+            }
+        }
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", $name, self.expr)
+            }
+        }
+    };
+}
+
+floatFunctionExpression!(FloorExpression, |x: f64| Some(x.floor()), "floor");
+floatFunctionExpression!(CeilExpression, |x: f64| Some(x.ceil()), "ceil");
+
+// The square root of a negative number is null rather than NaN.
+floatFunctionExpression!(
+    SqrtExpression,
+    |x: f64| (x >= 0.0).then(|| x.sqrt()),
+    "sqrt"
+);
+
+/// Rounds a numeric expression to a number of decimal places, 0 unless set
+/// with [`RoundExpression::with_decimals`], as Float64. Halves round away from
+/// zero and nulls stay null.
+pub struct RoundExpression {
+    expr: Box<dyn PhysicalExpression>,
+    decimals: i32,
+}
+
+impl RoundExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        RoundExpression { expr, decimals: 0 }
+    }
+
+    /// Negative values round to the left of the decimal point, so `-1`
+    /// rounds to tens.
+    pub fn with_decimals(mut self, decimals: i32) -> Self {
+        self.decimals = decimals;
+        self
+    }
+}
+
+impl PhysicalExpression for RoundExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let scale = 10f64.powi(self.decimals);
+        map_float64(self.expr.evaluate(input)?, |x| {
+            Some((x * scale).round() / scale)
+        })
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for RoundExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "round({}, {})", self.expr, self.decimals)
+    }
+}
+
+// Abs and Pow
+
+fn abs(array: &dyn Array) -> Result<Box<dyn Array>, Error> {
+    let data_type = array.data_type().clone();
+    Ok(match data_type.to_physical_type() {
+        PhysicalType::Primitive(PrimitiveType::Int32) => Box::new(compute::arity::unary(
+            downcast::<PrimitiveArray<i32>>(array)?,
+            i32::wrapping_abs,
+            data_type,
+        )),
+        PhysicalType::Primitive(PrimitiveType::Int64) => Box::new(compute::arity::unary(
+            downcast::<PrimitiveArray<i64>>(array)?,
+            i64::wrapping_abs,
+            data_type,
+        )),
+        PhysicalType::Primitive(PrimitiveType::Float32) => Box::new(compute::arity::unary(
+            downcast::<PrimitiveArray<f32>>(array)?,
+            f32::abs,
+            data_type,
+        )),
+        PhysicalType::Primitive(PrimitiveType::Float64) => Box::new(compute::arity::unary(
+            downcast::<PrimitiveArray<f64>>(array)?,
+            f64::abs,
+            data_type,
+        )),
+        t => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", t))),
+    })
+}
+
+/// The absolute value of a numeric expression, in the same type. Like the
+/// arithmetic kernels, the smallest integer wraps to itself. Nulls stay null.
+pub struct AbsExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl AbsExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        AbsExpression { expr }
+    }
+}
+
+impl PhysicalExpression for AbsExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::from(abs(&*array)?))),
+            ColumnarValue::Scalar(scalar) if scalar.data_type() == &DataType::Null => {
+                Ok(ColumnarValue::Scalar(scalar))
+            }
+            ColumnarValue::Scalar(scalar) => {
+                let array = abs(&*scalars_to_array(&[scalar])?)?;
+                Ok(ColumnarValue::Scalar(new_scalar(&*array, 0)))
+            }
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for AbsExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "abs({})", self.expr)
+    }
+}
+
+/// Raises a numeric base to a numeric exponent, both promoted to Float64.
+/// The result is null where either input is.
+pub struct PowExpression {
+    base: Box<dyn PhysicalExpression>,
+    exponent: Box<dyn PhysicalExpression>,
+}
+
+impl PowExpression {
+    pub fn new(base: Box<dyn PhysicalExpression>, exponent: Box<dyn PhysicalExpression>) -> Self {
+        PowExpression { base, exponent }
+    }
+}
+
+impl PhysicalExpression for PowExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let base = promote_to_float64(self.base.evaluate(input)?)?;
+        let exponent = promote_to_float64(self.exponent.evaluate(input)?)?;
+        let scalar = matches!(
+            (&base, &exponent),
+            (ColumnarValue::Scalar(_), ColumnarValue::Scalar(_))
+        );
+        let length = if scalar { 1 } else { input.len() };
+        let (base, exponent) = (base.into_array(length)?, exponent.into_array(length)?);
+        let result = compute::arity::binary(
+            downcast::<PrimitiveArray<f64>>(&*base)?,
+            downcast::<PrimitiveArray<f64>>(&*exponent)?,
+            DataType::Float64,
+            f64::powf,
+        );
+        Ok(if scalar {
+            ColumnarValue::Scalar(new_scalar(&result, 0))
+        } else {
+            ColumnarValue::Array(Arc::new(result))
+        })
+    }
+
+    fn is_constant(&self) -> bool {
+        self.base.is_constant() && self.exponent.is_constant()
+    }
+}
+
+impl fmt::Display for PowExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pow({}, {})", self.base, self.exponent)
+    }
+}
+
+/// A function evaluated over the rows of one window partition, which are
+/// passed already ordered. Returns one value per row.
+pub trait WindowFunction: Display {
+    fn evaluate_partition(
+        &self,
+        partition: &Chunk<Arc<dyn Array>>,
+    ) -> Result<Arc<dyn Array>, Error>;
+}
+
+/// Splits the ordered rows of a partition into `buckets` groups whose sizes
+/// differ by at most one, larger groups first, and labels each row with its
+/// group number starting at 1. Partitions with fewer rows than `buckets` fill
+/// only the first buckets.
+pub struct NtileFunction {
+    buckets: i32,
+}
+
+impl NtileFunction {
+    pub fn new(buckets: i32) -> Result<Self, Error> {
+        if buckets <= 0 {
+            return Err(Error::InvalidArgument(format!(
+                "ntile needs a positive number of buckets, got {}",
+                buckets
+            )));
+        }
+        Ok(NtileFunction { buckets })
+    }
+}
+
+impl WindowFunction for NtileFunction {
+    fn evaluate_partition(
+        &self,
+        partition: &Chunk<Arc<dyn Array>>,
+    ) -> Result<Arc<dyn Array>, Error> {
+        let rows = partition.len();
+        let buckets = self.buckets as usize;
+        let (size, larger) = (rows / buckets, rows % buckets);
+        // The first `larger` buckets hold one extra row.
+        let split = larger * (size + 1);
+        let values = (0..rows)
+            .map(|row| {
+                if row < split {
+                    row / (size + 1) + 1
+                } else {
+                    (row - split) / size + larger + 1
+                }
+            })
+            .map(|bucket| bucket as i32)
+            .collect::<Vec<_>>();
+        Ok(Arc::new(PrimitiveArray::<i32>::from_vec(values)))
+    }
+}
+
+impl fmt::Display for NtileFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ntile({})", self.buckets)
+    }
+}
+
+/// Shifts `array` by `offset` rows, down or with `up` set up, filling the
+/// rows left at the edge with `default`, or nulls without one.
+fn shift(
+    array: &dyn Array,
+    offset: usize,
+    up: bool,
+    default: Option<&dyn Scalar>,
+) -> Result<Arc<dyn Array>, Error> {
+    let length = array.len();
+    let filled = offset.min(length);
+    let fill: Arc<dyn Array> = match default {
+        Some(default) => Arc::from(scalar_to_array(default, filled)?.slice(0, filled)),
+        None => Arc::from(new_null_array(array.data_type().clone(), filled)),
+    };
+    let values: Arc<dyn Array> = if !up {
+        Arc::from(array.slice(0, length - filled))
+    } else {
+        Arc::from(array.slice(filled, length - filled))
+    };
+    let parts = if !up {
+        [fill.as_ref(), values.as_ref()]
+    } else {
+        [values.as_ref(), fill.as_ref()]
+    };
+    compute::concatenate::concatenate(&parts)
+        .map(Arc::from)
+        .map_err(Error::ArrowError)
+}
+
+macro_rules! offsetFunction {
+    ($name: ident, $up: expr, $op_name: expr) => {
+        pub struct $name {
+            expr: Box<dyn PhysicalExpression>,
+            offset: usize,
+            default: Option<Box<dyn Scalar>>,
+        }
+
+        impl $name {
+            pub fn new(
+                expr: Box<dyn PhysicalExpression>,
+                offset: usize,
+                default: Option<Box<dyn Scalar>>,
+            ) -> Self {
+                $name {
+                    expr,
+                    offset,
+                    default,
+                }
+            }
+        }
+
+        impl WindowFunction for $name {
+            fn evaluate_partition(
+                &self,
+                partition: &Chunk<Arc<dyn Array>>,
+            ) -> Result<Arc<dyn Array>, Error> {
+                let values = self.expr.evaluate(partition)?.into_array(partition.len())?;
+                shift(&*values, self.offset, $up, self.default.as_deref())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({}, {}", $op_name, self.expr, self.offset)?;
+                match &self.default {
+                    Some(default) => write!(f, ", {:?})", default),
+                    None => write!(f, ")"),
+                }
+            }
+        }
+    };
+}
+
+// The value `offset` rows before the current row in its partition.
+offsetFunction!(LagFunction, false, "lag");
+// The value `offset` rows after the current row in its partition.
+offsetFunction!(LeadFunction, true, "lead");
+
+// Like
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LikeToken {
+    Char(char),
+    // `_`
+    One,
+    // `%`
+    Many,
+}
+
+/// Splits a LIKE pattern into tokens. The `escape` character makes the next
+/// character match literally, including `%`, `_` and the escape itself.
+/// Escaping any other character matches it as is, a trailing escape is an
+/// error.
+fn parse_like_pattern(pattern: &str, escape: Option<char>) -> Result<Vec<LikeToken>, Error> {
+    let mut tokens = vec![];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            c if Some(c) == escape => LikeToken::Char(chars.next().ok_or_else(|| {
+                Error::InvalidArgument(format!(
+                    "LIKE pattern `{}` ends with the escape character",
+                    pattern
+                ))
+            })?),
+            '%' => LikeToken::Many,
+            '_' => LikeToken::One,
+            c => LikeToken::Char(c),
+        });
+    }
+    Ok(tokens)
+}
+
+/// Matches `text` against the tokens of a LIKE pattern. When a later token
+/// fails, only the most recent `%` is retried one character further, which
+/// keeps matching at O(pattern * text) in the worst case.
+fn like_match(tokens: &[LikeToken], text: &str) -> bool {
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut t, mut p) = (0, 0);
+    let mut retry = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(LikeToken::Many) => {
+                retry = Some((p, t));
+                p += 1;
+            }
+            Some(LikeToken::One) => {
+                p += 1;
+                t += 1;
+            }
+            Some(LikeToken::Char(c)) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match retry {
+                Some((many, start)) => {
+                    p = many + 1;
+                    t = start + 1;
+                    retry = Some((many, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..].iter().all(|token| *token == LikeToken::Many)
+}
+
+/// The string of a scalar, with nulls of any type as `None`.
+fn utf8_scalar(scalar: &dyn Scalar) -> Result<Option<&str>, Error> {
+    match scalar.data_type() {
+        DataType::Null => Ok(None),
+        _ => Ok(scalar
+            .as_any()
+            .downcast_ref::<Utf8Scalar<i32>>()
+            .ok_or(Error::DowncastError)?
+            .value()),
+    }
+}
+
+/// The strings of `value` row by row, repeating a scalar `length` times.
+fn utf8_rows(
+    value: &ColumnarValue,
+    length: usize,
+) -> Result<Box<dyn Iterator<Item = Option<&str>> + '_>, Error> {
+    match value {
+        ColumnarValue::Array(array) => Ok(Box::new(downcast::<Utf8Array<i32>>(&**array)?.iter())),
+        ColumnarValue::Scalar(scalar) => Ok(Box::new(std::iter::repeat_n(
+            utf8_scalar(&**scalar)?,
+            length,
+        ))),
+    }
+}
+
+/// Concatenates two strings, like SQL's `||`. A null on either side makes
+/// the result null.
+pub struct ConcatExpression {
+    left: Box<dyn PhysicalExpression>,
+    right: Box<dyn PhysicalExpression>,
+}
+
+impl ConcatExpression {
+    pub fn new(left: Box<dyn PhysicalExpression>, right: Box<dyn PhysicalExpression>) -> Self {
+        ConcatExpression { left, right }
+    }
+}
+
+impl PhysicalExpression for ConcatExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let left = self.left.evaluate(input)?;
+        let right = self.right.evaluate(input)?;
+        let concat = |(left, right): (Option<&str>, Option<&str>)| {
+            left.zip(right)
+                .map(|(left, right)| format!("{}{}", left, right))
+        };
+        let length = match (&left, &right) {
+            (ColumnarValue::Array(left), ColumnarValue::Array(right))
+                if left.len() != right.len() =>
+            {
+                return Err(Error::DifferentSizes(
+                    format!("{:?}", left),
+                    format!("{:?}", right),
+                ))
+            }
+            (ColumnarValue::Array(array), _) | (_, ColumnarValue::Array(array)) => array.len(),
+            (ColumnarValue::Scalar(left), ColumnarValue::Scalar(right)) => {
+                let value = concat((utf8_scalar(&**left)?, utf8_scalar(&**right)?));
+                return Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))));
+            }
+        };
+        let array = utf8_rows(&left, length)?
+            .zip(utf8_rows(&right, length)?)
+            .map(concat)
+            .collect::<Utf8Array<i32>>();
+        Ok(ColumnarValue::Array(Arc::new(array)))
+    }
+
+    fn is_constant(&self) -> bool {
+        self.left.is_constant() && self.right.is_constant()
+    }
+}
+
+impl fmt::Display for ConcatExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} || {}", self.left, self.right)
+    }
+}
+
+macro_rules! stringPredicateExpression {
+    ($i: ident, $method: ident, $name: expr) => {
+        /// Tests each string against a pattern that is either constant or
+        /// varies by row, without the parsing of LIKE. A null on either side
+        /// makes the result null.
+        pub struct $i {
+            expr: Box<dyn PhysicalExpression>,
+            pattern: Box<dyn PhysicalExpression>,
+        }
+
+        impl $i {
+            pub fn new(
+                expr: Box<dyn PhysicalExpression>,
+                pattern: Box<dyn PhysicalExpression>,
+            ) -> Self {
+                $i { expr, pattern }
+            }
+        }
+
+        impl PhysicalExpression for $i {
+            fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+                let expr = self.expr.evaluate(input)?;
+                let pattern = self.pattern.evaluate(input)?;
+                let test = |(value, pattern): (Option<&str>, Option<&str>)| {
+                    value
+                        .zip(pattern)
+                        .map(|(value, pattern)| value.$method(pattern))
+                };
+                let length = match (&expr, &pattern) {
+                    (ColumnarValue::Array(expr), ColumnarValue::Array(pattern))
+                        if expr.len() != pattern.len() =>
+                    {
+                        return Err(Error::DifferentSizes(
+                            format!("{:?}", expr),
+                            format!("{:?}", pattern),
+                        ))
+                    }
+                    (ColumnarValue::Array(array), _) | (_, ColumnarValue::Array(array)) => {
+                        array.len()
+                    }
+                    (ColumnarValue::Scalar(expr), ColumnarValue::Scalar(pattern)) => {
+                        let value = test((utf8_scalar(&**expr)?, utf8_scalar(&**pattern)?));
+                        return Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))));
                     }
-                    (PhysicalType::Primitive(PrimitiveType::Int32), PhysicalType::Null) => Ok(true),
-                    _ => Err(Error::PhysicalTypeNotSuported(format!(
-                        "{:?}, {:?}",
-                        new.data_type(),
-                        self.value.data_type()
-                    ))),
-                }?;
-                if bool {
-                    self.value = new;
                 };
-                Ok(())
+                let array = utf8_rows(&expr, length)?
+                    .zip(utf8_rows(&pattern, length)?)
+                    .map(test)
+                    .collect::<BooleanArray>();
+                Ok(ColumnarValue::Array(Arc::new(array)))
             }
-            fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
-                Ok(ColumnarValue::Scalar(self.value))
+
+            fn is_constant(&self) -> bool {
+                self.expr.is_constant() && self.pattern.is_constant()
             }
         }
-        pub struct $expr {
+
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({}, {})", $name, self.expr, self.pattern)
+            }
+        }
+    };
+}
+
+stringPredicateExpression!(StartsWithExpression, starts_with, "starts_with");
+stringPredicateExpression!(EndsWithExpression, ends_with, "ends_with");
+stringPredicateExpression!(ContainsExpression, contains, "contains");
+
+/// SQL's `SUBSTRING(expr FROM start FOR length)`, counting characters from 1.
+/// Like in SQL, the result is the part of the string inside the window
+/// `[start, start + length)`, so a start before the first character shortens
+/// the result, and a window past the end or a negative length is clamped to
+/// the string. Nulls stay null.
+pub struct SubstringExpression {
+    expr: Box<dyn PhysicalExpression>,
+    start: i64,
+    length: Option<i64>,
+}
+
+impl SubstringExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, start: i64, length: Option<i64>) -> Self {
+        SubstringExpression {
+            expr,
+            start,
+            length,
+        }
+    }
+
+    fn substring<'a>(&self, value: &'a str) -> &'a str {
+        // The window in characters from 0.
+        let begin = (self.start - 1).max(0);
+        let end = match self.length {
+            Some(length) => (self.start - 1).saturating_add(length).max(begin),
+            None => i64::MAX,
+        };
+        let offset = |position: i64| {
+            value
+                .char_indices()
+                .nth(position as usize)
+                .map_or(value.len(), |(i, _)| i)
+        };
+        &value[offset(begin)..offset(end)]
+    }
+}
+
+impl PhysicalExpression for SubstringExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|value| value.map(|value| self.substring(value)))
+                    .collect::<Utf8Array<i32>>(),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = utf8_scalar(&*scalar)?.map(|value| self.substring(value));
+                Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))))
+            }
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for SubstringExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SUBSTRING({} FROM {}", self.expr, self.start)?;
+        match self.length {
+            Some(length) => write!(f, " FOR {})", length),
+            None => write!(f, ")"),
+        }
+    }
+}
+
+macro_rules! caseExpression {
+    ($i: ident, $method: ident, $name: expr) => {
+        /// Maps each string with Rust's Unicode case mapping, which may change
+        /// its length, as `ß` uppercases to `SS`. Nulls stay null.
+        pub struct $i {
             expr: Box<dyn PhysicalExpression>,
         }
 
-        impl PhysicalExpression for $expr {
+        impl $i {
+            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+                $i { expr }
+            }
+        }
+
+        impl PhysicalExpression for $i {
             fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
-                self.expr.evaluate(input)
+                match self.expr.evaluate(input)? {
+                    ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                        downcast::<Utf8Array<i32>>(&*array)?
+                            .iter()
+                            .map(|value| value.map(str::$method))
+                            .collect::<Utf8Array<i32>>(),
+                    ))),
+                    ColumnarValue::Scalar(scalar) => {
+                        let value = utf8_scalar(&*scalar)?.map(str::$method);
+                        Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                            value,
+                        ))))
+                    }
+                }
+            }
+
+            fn is_constant(&self) -> bool {
+                self.expr.is_constant()
             }
         }
 
-        impl PhysicalAggregateExpression for $expr {
-            fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
-                Box::new($acc {
-                    value: Box::new(NullScalar::new()),
-                    index: index,
-                })
+        impl fmt::Display for $i {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}({})", $name, self.expr)
             }
         }
+    };
+}
 
-        impl $expr {
-            pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
-                $expr { expr: expr }
+caseExpression!(UpperExpression, to_uppercase, "UPPER");
+caseExpression!(LowerExpression, to_lowercase, "LOWER");
+
+/// Which ends of a string TRIM removes characters from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrimMode {
+    #[default]
+    Both,
+    Left,
+    Right,
+}
+
+/// SQL's `TRIM`, `LTRIM` and `RTRIM`. Removes whitespace from the ends of each
+/// string, or any of a given set of characters. Nulls stay null.
+pub struct TrimExpression {
+    expr: Box<dyn PhysicalExpression>,
+    mode: TrimMode,
+    characters: Option<Vec<char>>,
+}
+
+impl TrimExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, mode: TrimMode) -> Self {
+        TrimExpression {
+            expr,
+            mode,
+            characters: None,
+        }
+    }
+
+    /// Trims the characters of `characters` instead of whitespace.
+    pub fn with_characters(mut self, characters: &str) -> Self {
+        self.characters = Some(characters.chars().collect());
+        self
+    }
+
+    fn trim<'a>(&self, value: &'a str) -> &'a str {
+        let trimmed = |c: char| match &self.characters {
+            Some(characters) => characters.contains(&c),
+            None => c.is_whitespace(),
+        };
+        match self.mode {
+            TrimMode::Both => value.trim_matches(trimmed),
+            TrimMode::Left => value.trim_start_matches(trimmed),
+            TrimMode::Right => value.trim_end_matches(trimmed),
+        }
+    }
+}
+
+impl PhysicalExpression for TrimExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|value| value.map(|value| self.trim(value)))
+                    .collect::<Utf8Array<i32>>(),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = utf8_scalar(&*scalar)?.map(|value| self.trim(value));
+                Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))))
             }
         }
+    }
 
-        impl fmt::Display for $expr {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(f, "{} {}", $op_name, self.expr)
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for TrimExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.mode {
+            TrimMode::Both => "TRIM",
+            TrimMode::Left => "LTRIM",
+            TrimMode::Right => "RTRIM",
+        };
+        match &self.characters {
+            Some(characters) => write!(
+                f,
+                "{}({}, '{}')",
+                name,
+                self.expr,
+                characters.iter().collect::<String>()
+            ),
+            None => write!(f, "{}({})", name, self.expr),
+        }
+    }
+}
+
+/// SQL's `REPLACE(expr, from, to)`, replacing every occurrence of the
+/// substring `from`. An empty `from` leaves the strings as they are. Nulls
+/// stay null.
+pub struct ReplaceExpression {
+    expr: Box<dyn PhysicalExpression>,
+    from: String,
+    to: String,
+}
+
+impl ReplaceExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>, from: String, to: String) -> Self {
+        ReplaceExpression { expr, from, to }
+    }
+
+    fn replace<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        if self.from.is_empty() {
+            Cow::Borrowed(value)
+        } else {
+            Cow::Owned(value.replace(&self.from, &self.to))
+        }
+    }
+}
+
+impl PhysicalExpression for ReplaceExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|value| value.map(|value| self.replace(value)))
+                    .collect::<Utf8Array<i32>>(),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = utf8_scalar(&*scalar)?.map(|value| self.replace(value));
+                Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))))
+            }
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for ReplaceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "REPLACE({}, '{}', '{}')", self.expr, self.from, self.to)
+    }
+}
+
+/// Replaces every match of a regular expression, with the syntax of the
+/// `regex` crate. The replacement can refer to capture groups as `$1` or
+/// `${name}`. Nulls stay null.
+pub struct RegexpReplaceExpression {
+    expr: Box<dyn PhysicalExpression>,
+    regex: Regex,
+    replacement: String,
+}
+
+impl RegexpReplaceExpression {
+    /// Fails with [`Error::RegexError`] when `pattern` is not a valid regular
+    /// expression.
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        pattern: &str,
+        replacement: String,
+    ) -> Result<Self, Error> {
+        Ok(RegexpReplaceExpression {
+            expr,
+            regex: Regex::new(pattern).map_err(Error::RegexError)?,
+            replacement,
+        })
+    }
+}
+
+impl PhysicalExpression for RegexpReplaceExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        let replace = |value| self.regex.replace_all(value, self.replacement.as_str());
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|value| value.map(replace))
+                    .collect::<Utf8Array<i32>>(),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = utf8_scalar(&*scalar)?.map(replace);
+                Ok(ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(
+                    value,
+                ))))
+            }
+        }
+    }
+
+    fn is_constant(&self) -> bool {
+        self.expr.is_constant()
+    }
+}
+
+impl fmt::Display for RegexpReplaceExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "regexp_replace({}, '{}', '{}')",
+            self.expr,
+            self.regex.as_str(),
+            self.replacement
+        )
+    }
+}
+
+/// Matches strings against a SQL LIKE pattern, where `%` matches any sequence
+/// of characters and `_` exactly one. Nulls stay null.
+pub struct LikeExpression {
+    expr: Box<dyn PhysicalExpression>,
+    pattern: String,
+    escape: Option<char>,
+    tokens: Vec<LikeToken>,
+    case_insensitive: bool,
+    negated: bool,
+}
+
+impl LikeExpression {
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        pattern: String,
+        escape: Option<char>,
+    ) -> Result<Self, Error> {
+        let tokens = parse_like_pattern(&pattern, escape)?;
+        Ok(LikeExpression {
+            expr,
+            pattern,
+            escape,
+            tokens,
+            case_insensitive: false,
+            negated: false,
+        })
+    }
+
+    /// Turns `LIKE` into `NOT LIKE`. Nulls stay null.
+    pub fn negated(mut self) -> Self {
+        self.negated = !self.negated;
+        self
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        let matched = if self.case_insensitive {
+            like_match(&self.tokens, &value.to_lowercase())
+        } else {
+            like_match(&self.tokens, value)
+        };
+        matched != self.negated
+    }
+}
+
+impl PhysicalExpression for LikeExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        match self.expr.evaluate(input)? {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .map(|value| value.map(|value| self.matches(value)))
+                    .collect::<BooleanArray>(),
+            ))),
+            ColumnarValue::Scalar(scalar) => {
+                let value = scalar
+                    .as_any()
+                    .downcast_ref::<Utf8Scalar<i32>>()
+                    .ok_or(Error::DowncastError)?
+                    .value()
+                    .map(|value| self.matches(value));
+                Ok(ColumnarValue::Scalar(Box::new(BooleanScalar::new(value))))
             }
         }
+    }
+}
+
+impl fmt::Display for LikeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not = if self.negated { "NOT " } else { "" };
+        let like = if self.case_insensitive {
+            "ILIKE"
+        } else {
+            "LIKE"
+        };
+        write!(f, "{} {}{} '{}'", self.expr, not, like, self.pattern)?;
+        match self.escape {
+            Some(escape) => write!(f, " ESCAPE '{}'", escape),
+            None => Ok(()),
+        }
+    }
+}
+
+/// LIKE ignoring case. Both the pattern and the input are lowercased with
+/// Rust's Unicode lowercase mapping, so non-ASCII letters work as long as they
+/// have a single lowercase form. This is not full case folding: `ß` doesn't
+/// match `SS`.
+pub struct ILikeExpression {
+    like: LikeExpression,
+}
+
+impl ILikeExpression {
+    pub fn new(
+        expr: Box<dyn PhysicalExpression>,
+        pattern: String,
+        escape: Option<char>,
+    ) -> Result<Self, Error> {
+        let mut like = LikeExpression::new(expr, pattern, escape)?;
+        like.tokens = like
+            .tokens
+            .into_iter()
+            .flat_map(|token| match token {
+                LikeToken::Char(c) => c.to_lowercase().map(LikeToken::Char).collect(),
+                token => vec![token],
+            })
+            .collect();
+        like.case_insensitive = true;
+        Ok(ILikeExpression { like })
+    }
+
+    /// Turns `ILIKE` into `NOT ILIKE`. Nulls stay null.
+    pub fn negated(self) -> Self {
+        ILikeExpression {
+            like: self.like.negated(),
+        }
+    }
+}
+
+impl PhysicalExpression for ILikeExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.like.evaluate(input)
+    }
+}
+
+impl fmt::Display for ILikeExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.like.fmt(f)
+    }
+}
+
+// Count if
+
+/// Counts the true values of `array`. This intersects the values with the
+/// validity bitmap and counts bits a word at a time instead of visiting rows.
+pub fn count_true(array: &BooleanArray) -> usize {
+    let values = match array.validity() {
+        Some(validity) => array.values().bitand(validity),
+        None => array.values().clone(),
     };
+    values.len() - values.null_count()
 }
 
-aggregateExpression!(MaxAccumulator, MaxExpression, max, gt, "max".to_string());
-aggregateExpression!(MinAccumulator, MinExpression, min, lt, "min".to_string());
-// aggregateExpression!(SumAccumulator, SumExpression, sum, add, "sum".to_string());
+/// Counts the rows of a group where the input is not null, whatever its type.
+/// That is the set bits of the validity bitmap, so Boolean input is counted
+/// without visiting rows too. Use [`CountIfAccumulator`] or SUM to count true
+/// values instead.
+pub struct CountAccumulator {
+    count: i64,
+    index: usize,
+}
+
+impl Accumulator for CountAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        self.count += (array.len() - array.null_count()) as i64;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Int64,
+            Some(self.count),
+        ))))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        self.count += downcast_accumulator::<Self>(other)?.count;
+        Ok(())
+    }
+}
+
+pub struct CountExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl CountExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        CountExpression { expr }
+    }
+}
+
+impl PhysicalExpression for CountExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for CountExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(CountAccumulator { count: 0, index })
+    }
+}
+
+impl fmt::Display for CountExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count {}", self.expr)
+    }
+}
+
+/// Counts the rows of a group where a boolean input is true.
+pub struct CountIfAccumulator {
+    count: i64,
+    index: usize,
+}
+
+impl Accumulator for CountIfAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        self.count += count_true(downcast::<BooleanArray>(&*array)?) as i64;
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Int64,
+            Some(self.count),
+        ))))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        self.count += downcast_accumulator::<Self>(other)?.count;
+        Ok(())
+    }
+}
+
+pub struct CountIfExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl CountIfExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        CountIfExpression { expr }
+    }
+}
+
+impl PhysicalExpression for CountIfExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for CountIfExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(CountIfAccumulator { count: 0, index })
+    }
+}
+
+impl fmt::Display for CountIfExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count_if {}", self.expr)
+    }
+}
+
+// Count distinct
+
+/// A value seen by COUNT DISTINCT. Floats are keyed by their bits, with both
+/// zeros and all NaNs counting as one value each.
+#[derive(PartialEq, Eq, Hash)]
+enum DistinctValue {
+    Integer(i64),
+    Float(u64),
+    Utf8(String),
+}
+
+fn float_value(value: f64) -> DistinctValue {
+    let value = if value == 0.0 {
+        0.0
+    } else if value.is_nan() {
+        f64::NAN
+    } else {
+        value
+    };
+    DistinctValue::Float(value.to_bits())
+}
+
+/// Counts the distinct non-null values of a group.
+///
+/// Every distinct value is kept in memory until the group is finalized, so
+/// memory grows with the cardinality of the input.
+pub struct CountDistinctAccumulator {
+    values: HashSet<DistinctValue>,
+    index: usize,
+}
+
+impl Accumulator for CountDistinctAccumulator {
+    fn accumulate(
+        &mut self,
+        input: &[ColumnarValue],
+        validity: Option<&Bitmap>,
+    ) -> Result<(), Error> {
+        let array = group_values(&input[self.index], validity)?;
+        let values = &mut self.values;
+        match array.data_type().to_physical_type() {
+            PhysicalType::Primitive(PrimitiveType::Int32) => values.extend(
+                downcast::<PrimitiveArray<i32>>(&*array)?
+                    .iter()
+                    .flatten()
+                    .map(|x| DistinctValue::Integer(*x as i64)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Int64) => values.extend(
+                downcast::<PrimitiveArray<i64>>(&*array)?
+                    .iter()
+                    .flatten()
+                    .map(|x| DistinctValue::Integer(*x)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float32) => values.extend(
+                downcast::<PrimitiveArray<f32>>(&*array)?
+                    .iter()
+                    .flatten()
+                    .map(|x| float_value(*x as f64)),
+            ),
+            PhysicalType::Primitive(PrimitiveType::Float64) => values.extend(
+                downcast::<PrimitiveArray<f64>>(&*array)?
+                    .iter()
+                    .flatten()
+                    .map(|x| float_value(*x)),
+            ),
+            PhysicalType::Utf8 => values.extend(
+                downcast::<Utf8Array<i32>>(&*array)?
+                    .iter()
+                    .flatten()
+                    .map(|x| DistinctValue::Utf8(x.to_string())),
+            ),
+            PhysicalType::Null => {}
+            x => return Err(Error::PhysicalTypeNotSuported(format!("{:?}", x))),
+        }
+        Ok(())
+    }
+    fn final_value(self: Box<Self>) -> Result<ColumnarValue, Error> {
+        Ok(ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(
+            DataType::Int64,
+            Some(self.values.len() as i64),
+        ))))
+    }
+    fn merge(&mut self, other: Box<dyn Accumulator>) -> Result<(), Error> {
+        self.values
+            .extend(downcast_accumulator::<Self>(other)?.values);
+        Ok(())
+    }
+}
+
+pub struct CountDistinctExpression {
+    expr: Box<dyn PhysicalExpression>,
+}
+
+impl CountDistinctExpression {
+    pub fn new(expr: Box<dyn PhysicalExpression>) -> Self {
+        CountDistinctExpression { expr }
+    }
+}
+
+impl PhysicalExpression for CountDistinctExpression {
+    fn evaluate(&self, input: &Chunk<Arc<dyn Array>>) -> Result<ColumnarValue, Error> {
+        self.expr.evaluate(input)
+    }
+}
+
+impl PhysicalAggregateExpression for CountDistinctExpression {
+    fn create_accumulator(&self, index: usize) -> Box<dyn Accumulator> {
+        Box::new(CountDistinctAccumulator {
+            values: HashSet::new(),
+            index,
+        })
+    }
+}
+
+impl fmt::Display for CountDistinctExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "count_distinct {}", self.expr)
+    }
+}
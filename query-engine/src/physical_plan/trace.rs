@@ -0,0 +1,149 @@
+//! `tracing` instrumentation of plan execution, behind the `tracing` feature.
+
+use std::cell::RefCell;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+use tracing::{field, span::EnteredSpan, Span};
+
+use super::Batch;
+
+thread_local! {
+    /// The operators running on this thread, innermost last. An operator
+    /// executed now reads its input for the last one.
+    static CONSUMERS: RefCell<Vec<Consumer>> = const { RefCell::new(vec![]) };
+}
+
+/// An operator's span, and the rows all of its inputs have produced so far.
+#[derive(Clone)]
+struct Consumer {
+    span: Span,
+    rows_in: Arc<AtomicU64>,
+}
+
+impl Consumer {
+    /// Makes this the consumer of the operators executed until the returned
+    /// guard is dropped.
+    fn enter(&self) -> Running {
+        CONSUMERS.with(|consumers| consumers.borrow_mut().push(self.clone()));
+        Running
+    }
+}
+
+/// Keeps a [`Consumer`] on [`CONSUMERS`] until dropped.
+struct Running;
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        CONSUMERS.with(|consumers| consumers.borrow_mut().pop());
+    }
+}
+
+/// An operator's `execute` span, entered while the operator sets up, so that
+/// the inputs it executes open their spans inside it.
+pub(crate) struct Execution {
+    span: EnteredSpan,
+    consumer: Option<Consumer>,
+    inputs: Consumer,
+    start: Instant,
+    _running: Running,
+}
+
+impl Execution {
+    pub(crate) fn start(operator: &'static str) -> Self {
+        let consumer = CONSUMERS.with(|consumers| consumers.borrow().last().cloned());
+        let span = tracing::info_span!(
+            "execute",
+            operator,
+            rows_in = field::Empty,
+            rows_out = field::Empty,
+            batches = field::Empty,
+            elapsed_us = field::Empty,
+        )
+        .entered();
+        let inputs = Consumer {
+            span: span.clone(),
+            rows_in: Arc::new(AtomicU64::new(0)),
+        };
+        Execution {
+            span,
+            consumer,
+            _running: inputs.enter(),
+            inputs,
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn finish(
+        self,
+        input: Box<dyn Iterator<Item = Batch>>,
+    ) -> Box<dyn Iterator<Item = Batch>> {
+        Box::new(TracedIterator {
+            input,
+            elapsed: self.start.elapsed(),
+            span: self.span.exit(),
+            consumer: self.consumer,
+            inputs: self.inputs,
+            rows: 0,
+            batches: 0,
+        })
+    }
+}
+
+/// Produces each batch in a `batch` span and records the totals on the
+/// operator's span, and adds its row count to the consumer's `rows_in`, once
+/// dropped, so they are right even when the consumer stops early. The
+/// elapsed time includes the operator's inputs, which may also start
+/// executing while a batch is produced.
+struct TracedIterator {
+    input: Box<dyn Iterator<Item = Batch>>,
+    span: Span,
+    consumer: Option<Consumer>,
+    inputs: Consumer,
+    elapsed: Duration,
+    rows: u64,
+    batches: u64,
+}
+
+impl Iterator for TracedIterator {
+    type Item = Batch;
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch_span = tracing::debug_span!(
+            parent: &self.span,
+            "batch",
+            rows = field::Empty,
+            elapsed_us = field::Empty,
+        );
+        let _entered = batch_span.enter();
+        let _running = self.inputs.enter();
+        let start = Instant::now();
+        let batch = self.input.next();
+        let elapsed = start.elapsed();
+        self.elapsed += elapsed;
+        if let Some(Ok(chunk)) = &batch {
+            self.rows += chunk.len() as u64;
+            self.batches += 1;
+            batch_span.record("rows", chunk.len() as u64);
+            batch_span.record("elapsed_us", elapsed.as_micros() as u64);
+        }
+        batch
+    }
+}
+
+impl Drop for TracedIterator {
+    fn drop(&mut self) {
+        self.span.record("rows_out", self.rows);
+        self.span.record("batches", self.batches);
+        self.span
+            .record("elapsed_us", self.elapsed.as_micros() as u64);
+        if let Some(consumer) = &self.consumer {
+            // Every input adds its rows, so the last one dropped records the
+            // total over all of them.
+            let rows_in = consumer.rows_in.fetch_add(self.rows, Ordering::Relaxed) + self.rows;
+            consumer.span.record("rows_in", rows_in);
+        }
+    }
+}
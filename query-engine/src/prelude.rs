@@ -1,13 +1,18 @@
 use crate::logical_plan::logical_expression::{
-    Column, LiteralBool, LiteralFloat, LiteralInteger, LiteralString, LogicalExpression, Max, Min,
+    Avg, Column, Count, LiteralBool, LiteralFloat, LiteralInteger, LiteralString,
+    LogicalExpression, Max, Min, Sum,
 };
 
-pub use crate::logical_plan::logical_expression::LogicalExpressionMethods;
+pub use crate::logical_plan::logical_expression::{Literal, LogicalExpressionMethods};
 
 pub fn col(name: &str) -> LogicalExpression {
     LogicalExpression::Column(Column::new(name.to_string()))
 }
 
+pub fn lit<T: Literal>(value: T) -> LogicalExpression {
+    value.lit()
+}
+
 pub fn lit_string(name: &str) -> LogicalExpression {
     LogicalExpression::LiteralString(LiteralString::new(name.to_string()))
 }
@@ -31,3 +36,15 @@ pub fn max(expr: LogicalExpression) -> LogicalExpression {
 pub fn min(expr: LogicalExpression) -> LogicalExpression {
     LogicalExpression::Min(Box::new(Min::new(expr)))
 }
+
+pub fn sum(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Sum(Box::new(Sum::new(expr)))
+}
+
+pub fn count(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Count(Box::new(Count::new(expr)))
+}
+
+pub fn avg(expr: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Avg(Box::new(Avg::new(expr)))
+}
@@ -5,26 +5,26 @@ use arrow2::{
 
 use crate::{
     error::Error,
-    logical_plan::{logical_expression::LogicalExpression, LogicalPlan},
+    logical_plan::{
+        check_expression_depth, logical_expression::LogicalExpression, LogicalPlan, Selection,
+    },
     physical_plan::{
-        physical_expressions::*, AggregateExec, PhysicalPlan, ProjectionExec, ScanExec,
-        SelectionExec,
+        ordering::SortExpression, physical_expressions::*, AggregateExec, LimitExec, PhysicalPlan,
+        ProjectionExec, ScanExec, SelectionExec, SortExec,
     },
+    schema::SchemaExt,
 };
 
+#[allow(clippy::wrong_self_convention)]
 impl LogicalExpression {
     pub(crate) fn to_physical_expression(
         self,
         input: &LogicalPlan,
     ) -> Result<Box<dyn PhysicalExpression>, Error> {
         match self {
-            LogicalExpression::Column(col) => input
-                .schema()?
-                .fields
-                .iter()
-                .position(|x| x.name == col.name)
-                .ok_or(Error::NoFieldInLogicalPlan(format!("{}", col)))
-                .map(|index| Box::new(ColumnExpression { index }) as Box<dyn PhysicalExpression>),
+            LogicalExpression::Column(col) => ColumnByNameExpression::new(&col.name)
+                .resolve(input.schema()?)
+                .map(|column| Box::new(column) as Box<dyn PhysicalExpression>),
             LogicalExpression::LiteralBool(bool) => Ok(Box::new(LiteralBoolExpression {
                 value: BooleanScalar::new(Some(bool.value)),
             })
@@ -51,6 +51,36 @@ impl LogicalExpression {
                 let right = neq.right.to_physical_expression(input)?;
                 Ok(Box::new(NeqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
+            LogicalExpression::Lt(lt) => {
+                let left = lt.left.to_physical_expression(input)?;
+                let right = lt.right.to_physical_expression(input)?;
+                Ok(Box::new(LtExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::LtEq(lteq) => {
+                let left = lteq.left.to_physical_expression(input)?;
+                let right = lteq.right.to_physical_expression(input)?;
+                Ok(Box::new(LtEqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Gt(gt) => {
+                let left = gt.left.to_physical_expression(input)?;
+                let right = gt.right.to_physical_expression(input)?;
+                Ok(Box::new(GtExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::GtEq(gteq) => {
+                let left = gteq.left.to_physical_expression(input)?;
+                let right = gteq.right.to_physical_expression(input)?;
+                Ok(Box::new(GtEqExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::And(and) => {
+                let left = and.left.to_physical_expression(input)?;
+                let right = and.right.to_physical_expression(input)?;
+                Ok(Box::new(AndExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
+            LogicalExpression::Or(or) => {
+                let left = or.left.to_physical_expression(input)?;
+                let right = or.right.to_physical_expression(input)?;
+                Ok(Box::new(OrExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::Add(add) => {
                 let left = add.left.to_physical_expression(input)?;
                 let right = add.right.to_physical_expression(input)?;
@@ -71,6 +101,11 @@ impl LogicalExpression {
                 let right = div.right.to_physical_expression(input)?;
                 Ok(Box::new(DivExpression::new(left, right)) as Box<dyn PhysicalExpression>)
             }
+            LogicalExpression::Mod(modu) => {
+                let left = modu.left.to_physical_expression(input)?;
+                let right = modu.right.to_physical_expression(input)?;
+                Ok(Box::new(ModExpression::new(left, right)) as Box<dyn PhysicalExpression>)
+            }
             LogicalExpression::Max(max) => {
                 let expr = max.expr.to_physical_expression(input)?;
                 Ok(Box::new(MaxExpression::new(expr)) as Box<dyn PhysicalExpression>)
@@ -88,6 +123,18 @@ impl LogicalExpression {
         input: &LogicalPlan,
     ) -> Result<Box<dyn PhysicalAggregateExpression>, Error> {
         match self {
+            LogicalExpression::Count(count) => {
+                let expr = count.expr.to_physical_expression(input)?;
+                Ok(Box::new(CountExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::Avg(avg) => {
+                let expr = avg.expr.to_physical_expression(input)?;
+                Ok(Box::new(AvgExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
+            }
+            LogicalExpression::Sum(sum) => {
+                let expr = sum.expr.to_physical_expression(input)?;
+                Ok(Box::new(SumExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
+            }
             LogicalExpression::Max(max) => {
                 let expr = max.expr.to_physical_expression(input)?;
                 Ok(Box::new(MaxExpression::new(expr)) as Box<dyn PhysicalAggregateExpression>)
@@ -101,14 +148,33 @@ impl LogicalExpression {
     }
 }
 
+/// Turns `plan` into the operators that execute it. Columns are looked up by
+/// name in the schema of the plan they read from, and a name that isn't there
+/// gives [`Error::NoFieldInSchema`].
+pub fn create_physical_plan(plan: LogicalPlan) -> Result<PhysicalPlan, Error> {
+    plan.to_physical_plan()
+}
+
+#[allow(clippy::wrong_self_convention)]
 impl LogicalPlan {
     pub(crate) fn to_physical_plan(self) -> Result<PhysicalPlan, Error> {
+        self.expressions()
+            .into_iter()
+            .try_for_each(check_expression_depth)?;
         match self {
-            LogicalPlan::Scan(scan) => Ok(PhysicalPlan::Scan(ScanExec::new(
-                scan.data_source,
-                scan.projection,
-                scan.schema,
-            ))),
+            LogicalPlan::Scan(mut scan) => match scan.filter.take() {
+                // No data source evaluates a filter, so it is applied by a
+                // selection right above the scan.
+                Some(filter) => {
+                    LogicalPlan::Selection(Selection::new(LogicalPlan::Scan(scan), *filter))
+                        .to_physical_plan()
+                }
+                None => Ok(PhysicalPlan::Scan(ScanExec::new(
+                    scan.data_source,
+                    scan.projection,
+                    scan.schema,
+                ))),
+            },
             LogicalPlan::Projection(proj) => {
                 let input = &proj.children[0];
                 let exprs = proj
@@ -128,6 +194,7 @@ impl LogicalPlan {
                 )))
             }
             LogicalPlan::Selection(sel) => {
+                check_expression_depth(&sel.expr)?;
                 let expr = sel.expr.to_physical_expression(&sel.children[0])?;
                 let children = sel
                     .children
@@ -139,6 +206,10 @@ impl LogicalPlan {
                 )))
             }
             LogicalPlan::Aggregate(agg) => {
+                agg.group_exprs
+                    .iter()
+                    .chain(agg.aggregate_exprs.iter())
+                    .try_for_each(check_expression_depth)?;
                 let input = &agg.children[0];
                 let group_exprs = agg
                     .group_exprs
@@ -162,6 +233,39 @@ impl LogicalPlan {
                     agg.schema,
                 )))
             }
+            LogicalPlan::Sort(sort) => {
+                let input = sort.children[0].schema()?;
+                let keys = sort
+                    .order_by
+                    .iter()
+                    .map(|key| {
+                        input
+                            .index_of(&key.name)
+                            .map(|column| SortExpression::column(column, key.options))
+                            .ok_or_else(|| Error::NoFieldInLogicalPlan(key.name.clone()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let children = sort
+                    .children
+                    .into_iter()
+                    .map(|x| x.to_physical_plan())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Sort(SortExec::new(
+                    children,
+                    keys,
+                    sort.schema,
+                )))
+            }
+            LogicalPlan::Limit(limit) => {
+                let children = limit
+                    .children
+                    .into_iter()
+                    .map(|x| x.to_physical_plan())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(PhysicalPlan::Limit(
+                    LimitExec::new(children, limit.fetch, limit.schema).with_skip(limit.skip),
+                ))
+            }
         }
     }
 }
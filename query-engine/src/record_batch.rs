@@ -0,0 +1,218 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use arrow2::array::{get_display, Array, BooleanArray, PrimitiveArray, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::compute::concatenate::concatenate;
+use arrow2::datatypes::Schema;
+use arrow2::types::NativeType;
+
+use crate::error::Error;
+
+/// A [`Chunk`] of columns together with the [`Schema`] describing them.
+pub struct RecordBatch {
+    schema: Schema,
+    columns: Chunk<Arc<dyn Array>>,
+}
+
+impl RecordBatch {
+    pub fn try_new(schema: Schema, columns: Chunk<Arc<dyn Array>>) -> Result<Self, Error> {
+        if schema.fields.len() != columns.arrays().len() {
+            return Err(Error::SchemaColumnMismatch(
+                schema.fields.len(),
+                columns.arrays().len(),
+            ));
+        }
+        Ok(RecordBatch { schema, columns })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn columns(&self) -> &Chunk<Arc<dyn Array>> {
+        &self.columns
+    }
+
+    pub fn into_chunk(self) -> Chunk<Arc<dyn Array>> {
+        self.columns
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The rows `[offset, offset + length)`, sharing the column buffers.
+    ///
+    /// Panics if the range exceeds the batch, like arrow's `Array::slice`.
+    pub fn slice(&self, offset: usize, length: usize) -> RecordBatch {
+        let columns = self
+            .columns
+            .arrays()
+            .iter()
+            .map(|array| Arc::from(array.slice(offset, length)))
+            .collect();
+        RecordBatch {
+            schema: self.schema.clone(),
+            columns: Chunk::new(columns),
+        }
+    }
+
+    /// Iterates over the rows of the batch.
+    ///
+    /// Every value is read through a dynamic downcast, so this is meant for
+    /// exporting small results into application types, not for bulk processing.
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        (0..self.columns.len()).map(move |index| Row { batch: self, index })
+    }
+}
+
+/// Concatenates the rows of `batches` into one batch. All batches must have
+/// the same schema, and there must be at least one to take it from.
+pub fn concat(batches: &[RecordBatch]) -> Result<RecordBatch, Error> {
+    let schema = &batches
+        .first()
+        .ok_or(Error::InvalidArgument(
+            "no batches to concatenate".to_string(),
+        ))?
+        .schema;
+    if let Some(batch) = batches.iter().find(|batch| &batch.schema != schema) {
+        return Err(Error::InvalidArgument(format!(
+            "batch schema {:?} differs from {:?}",
+            batch.schema, schema
+        )));
+    }
+    let columns = (0..schema.fields.len())
+        .map(|i| {
+            let arrays = batches
+                .iter()
+                .map(|batch| batch.columns[i].as_ref())
+                .collect::<Vec<&dyn Array>>();
+            concatenate(&arrays)
+                .map(Arc::from)
+                .map_err(Error::ArrowError)
+        })
+        .collect::<Result<Vec<Arc<dyn Array>>, Error>>()?;
+    RecordBatch::try_new(schema.clone(), Chunk::new(columns))
+}
+
+/// A view of a single row of a [`RecordBatch`].
+pub struct Row<'a> {
+    batch: &'a RecordBatch,
+    index: usize,
+}
+
+impl<'a> Row<'a> {
+    fn column<A: Array + 'static>(&self, column: usize) -> Result<&'a A, Error> {
+        self.batch
+            .columns
+            .arrays()
+            .get(column)
+            .ok_or(Error::ExceedingBoundsError(column))?
+            .as_any()
+            .downcast_ref::<A>()
+            .ok_or(Error::DowncastError)
+    }
+
+    fn primitive<T: NativeType>(&self, column: usize) -> Result<Option<T>, Error> {
+        let array = self.column::<PrimitiveArray<T>>(column)?;
+        Ok(array.is_valid(self.index).then(|| array.value(self.index)))
+    }
+
+    pub fn get_i32(&self, column: usize) -> Result<Option<i32>, Error> {
+        self.primitive(column)
+    }
+
+    pub fn get_i64(&self, column: usize) -> Result<Option<i64>, Error> {
+        self.primitive(column)
+    }
+
+    pub fn get_f32(&self, column: usize) -> Result<Option<f32>, Error> {
+        self.primitive(column)
+    }
+
+    pub fn get_f64(&self, column: usize) -> Result<Option<f64>, Error> {
+        self.primitive(column)
+    }
+
+    pub fn get_bool(&self, column: usize) -> Result<Option<bool>, Error> {
+        let array = self.column::<BooleanArray>(column)?;
+        Ok(array.is_valid(self.index).then(|| array.value(self.index)))
+    }
+
+    pub fn get_str(&self, column: usize) -> Result<Option<&'a str>, Error> {
+        let array = self.column::<Utf8Array<i32>>(column)?;
+        Ok(array.is_valid(self.index).then(|| array.value(self.index)))
+    }
+}
+
+/// Renders `batch` as a bordered table with the field names as headers and
+/// nulls shown as `NULL`, for debugging and examples.
+///
+/// Values longer than `max_width` characters are cut to that width, ending in
+/// `...`.
+pub fn pretty_format(batch: &RecordBatch, max_width: Option<usize>) -> String {
+    let truncate = |value: String| match max_width {
+        Some(width) if value.chars().count() > width => {
+            let mut value = value
+                .chars()
+                .take(width.saturating_sub(3))
+                .collect::<String>();
+            value.push_str(&"..."[..width.min(3)]);
+            value
+        }
+        _ => value,
+    };
+    let mut cells = vec![batch
+        .schema
+        .fields
+        .iter()
+        .map(|field| truncate(field.name.clone()))
+        .collect::<Vec<_>>()];
+    let displays = batch
+        .columns
+        .arrays()
+        .iter()
+        .map(|array| get_display(array.as_ref(), "NULL"))
+        .collect::<Vec<_>>();
+    for row in 0..batch.columns.len() {
+        cells.push(
+            displays
+                .iter()
+                .map(|display| {
+                    let mut value = String::new();
+                    display(&mut value, row).unwrap();
+                    truncate(value)
+                })
+                .collect(),
+        );
+    }
+    let widths = (0..displays.len())
+        .map(|column| {
+            cells
+                .iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let mut border = String::from("+");
+    for width in &widths {
+        border.push_str(&"-".repeat(width + 2));
+        border.push('+');
+    }
+    let mut result = String::new();
+    writeln!(result, "{}", border).unwrap();
+    for (i, row) in cells.iter().enumerate() {
+        result.push('|');
+        for (cell, width) in row.iter().zip(&widths) {
+            write!(result, " {:<width$} |", cell, width = width).unwrap();
+        }
+        result.push('\n');
+        if i == 0 {
+            writeln!(result, "{}", border).unwrap();
+        }
+    }
+    writeln!(result, "{}", border).unwrap();
+    result
+}
@@ -0,0 +1,88 @@
+use arrow2::datatypes::{Field, Schema};
+
+use crate::error::Error;
+
+/// Lookups and projections on arrow's [`Schema`].
+pub trait SchemaExt {
+    /// A schema with the fields at `indices`, in that order.
+    fn project(&self, indices: &[usize]) -> Result<Schema, Error>;
+    /// The position of the first field named `name`.
+    fn index_of(&self, name: &str) -> Option<usize>;
+    /// The first field named `name`.
+    fn field_by_name(&self, name: &str) -> Option<&Field>;
+    /// The fields of `self` followed by those of `other`, as a join outputs
+    /// them. A name in both schemas is an error, as columns are resolved by
+    /// name.
+    fn merge(&self, other: &Schema) -> Result<Schema, Error>;
+    /// The schema of a set operation over `self` and `other`: the fields of
+    /// `self`, each matched by name to a field of `other` with the same type,
+    /// nullable if either is.
+    fn union(&self, other: &Schema) -> Result<Schema, Error>;
+}
+
+impl SchemaExt for Schema {
+    fn project(&self, indices: &[usize]) -> Result<Schema, Error> {
+        let fields = indices
+            .iter()
+            .map(|&i| {
+                self.fields
+                    .get(i)
+                    .cloned()
+                    .ok_or(Error::ExceedingBoundsError(i))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Schema::from(fields).with_metadata(self.metadata.clone()))
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|x| x.name == name)
+    }
+
+    fn field_by_name(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|x| x.name == name)
+    }
+
+    fn merge(&self, other: &Schema) -> Result<Schema, Error> {
+        if let Some(field) = other
+            .fields
+            .iter()
+            .find(|x| self.index_of(&x.name).is_some())
+        {
+            return Err(Error::DuplicateField(field.name.clone()));
+        }
+        let fields = self.fields.iter().chain(&other.fields).cloned();
+        Ok(Schema::from(fields.collect::<Vec<_>>()))
+    }
+
+    fn union(&self, other: &Schema) -> Result<Schema, Error> {
+        if let Some(field) = other
+            .fields
+            .iter()
+            .find(|x| self.index_of(&x.name).is_none())
+        {
+            return Err(Error::NoFieldInSchema(field.name.clone()));
+        }
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let other = other
+                    .field_by_name(&field.name)
+                    .ok_or(Error::NoFieldInSchema(field.name.clone()))?;
+                if field.data_type() != other.data_type() {
+                    return Err(Error::ConflictingFieldTypes(
+                        field.name.clone(),
+                        format!("{:?}", field.data_type()),
+                        format!("{:?}", other.data_type()),
+                    ));
+                }
+                Ok(Field::new(
+                    &field.name,
+                    field.data_type().clone(),
+                    field.is_nullable || other.is_nullable,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(Schema::from(fields))
+    }
+}
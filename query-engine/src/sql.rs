@@ -0,0 +1,296 @@
+use std::collections::HashMap;
+
+use sqlparser::{
+    ast::{
+        BinaryOperator, Expr, FunctionArg, FunctionArgExpr, FunctionArguments, GroupByExpr,
+        LimitClause, ObjectNamePart, OrderByKind, OrderBySort, Query, Select, SelectItem, SetExpr,
+        Statement, TableFactor, UnaryOperator, Value,
+    },
+    dialect::GenericDialect,
+    parser::Parser,
+};
+
+use crate::{
+    data_source::{CsvDataSource, DataSource, ParquetDataSource},
+    error::Error,
+    logical_plan::{
+        builder::LogicalPlanBuilder,
+        logical_expression::{Add, Div, LogicalExpression, Mod, Mul, Sub},
+        LogicalPlan, OrderBy,
+    },
+    prelude::*,
+};
+
+enum TableFormat {
+    Parquet,
+    Csv,
+}
+
+/// Plans SQL queries over the tables registered with it. A table is opened
+/// again for every query that reads it.
+#[derive(Default)]
+pub struct SqlContext {
+    tables: HashMap<String, (String, TableFormat)>,
+}
+
+impl SqlContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register_parquet(&mut self, name: &str, path: &str) {
+        self.tables
+            .insert(name.to_string(), (path.to_string(), TableFormat::Parquet));
+    }
+
+    pub fn register_csv(&mut self, name: &str, path: &str) {
+        self.tables
+            .insert(name.to_string(), (path.to_string(), TableFormat::Csv));
+    }
+
+    /// Plans a single `SELECT ... FROM ... WHERE ... GROUP BY ... ORDER BY
+    /// ... LIMIT ... OFFSET` over one table. With aggregates, the select
+    /// list has to be the `GROUP BY` columns followed by the aggregates, as
+    /// that is the output of an aggregate plan.
+    pub fn create_logical_plan(&self, sql: &str) -> Result<LogicalPlan, Error> {
+        let mut statements = Parser::parse_sql(&GenericDialect {}, sql)
+            .map_err(|err| Error::SqlParseError(err.to_string()))?;
+        match (statements.pop(), statements.is_empty()) {
+            (Some(Statement::Query(query)), true) => self.query(*query),
+            (Some(statement), true) => Err(unsupported(statement)),
+            _ => Err(Error::UnsupportedSql(
+                "more or less than one statement".to_string(),
+            )),
+        }
+    }
+
+    fn query(&self, query: Query) -> Result<LogicalPlan, Error> {
+        let select = match *query.body {
+            SetExpr::Select(select) => *select,
+            body => return Err(unsupported(body)),
+        };
+        let mut builder = self.select(select)?;
+        if let Some(order_by) = query.order_by {
+            let exprs = match order_by.kind {
+                OrderByKind::Expressions(exprs) => exprs,
+                OrderByKind::All(_) => return Err(unsupported("ORDER BY ALL")),
+            };
+            let order_by = exprs
+                .into_iter()
+                .map(|key| {
+                    let order_by = match (key.expr, key.options.sort) {
+                        (Expr::Identifier(ident), None | Some(OrderBySort::Asc)) => {
+                            OrderBy::asc(&ident.value)
+                        }
+                        (Expr::Identifier(ident), Some(OrderBySort::Desc)) => {
+                            OrderBy::desc(&ident.value)
+                        }
+                        (expr, _) => return Err(unsupported(expr)),
+                    };
+                    Ok(match key.options.nulls_first {
+                        Some(nulls_first) => order_by.with_nulls_first(nulls_first),
+                        None => order_by,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            builder = builder.sort(order_by);
+        }
+        match query.limit_clause {
+            Some(LimitClause::LimitOffset {
+                limit,
+                offset,
+                limit_by,
+            }) if limit_by.is_empty() => {
+                let skip = offset.map(|offset| row_count(offset.value)).transpose()?;
+                let fetch = limit.map(row_count).transpose()?;
+                builder = builder.limit(skip.unwrap_or(0), fetch.unwrap_or(usize::MAX));
+            }
+            Some(limit_clause) => return Err(unsupported(limit_clause)),
+            None => {}
+        }
+        Ok(builder.build())
+    }
+
+    fn select(&self, select: Select) -> Result<LogicalPlanBuilder, Error> {
+        if select.distinct.is_some() {
+            return Err(Error::UnsupportedSql("DISTINCT".to_string()));
+        }
+        if let Some(having) = select.having {
+            return Err(unsupported(having));
+        }
+        let mut builder = match select.from.as_slice() {
+            [table] if table.joins.is_empty() => self.scan(&table.relation)?,
+            _ => {
+                return Err(Error::UnsupportedSql(
+                    "a FROM other than one table".to_string(),
+                ))
+            }
+        };
+        if let Some(selection) = select.selection {
+            builder = builder.filter(expression(selection)?);
+        }
+        let group_by = match select.group_by {
+            GroupByExpr::Expressions(exprs, modifiers) if modifiers.is_empty() => exprs
+                .into_iter()
+                .map(expression)
+                .collect::<Result<Vec<_>, _>>()?,
+            group_by => return Err(unsupported(group_by)),
+        };
+        let wildcard = select
+            .projection
+            .iter()
+            .any(|item| matches!(item, SelectItem::Wildcard(_)));
+        if wildcard && select.projection.len() > 1 {
+            return Err(Error::UnsupportedSql(
+                "a wildcard mixed with other select items".to_string(),
+            ));
+        }
+        let mut projection = Vec::new();
+        for item in select.projection {
+            match item {
+                SelectItem::UnnamedExpr(expr) => projection.push(expression(expr)?),
+                SelectItem::Wildcard(_) if group_by.is_empty() => {}
+                item => return Err(unsupported(item)),
+            }
+        }
+        let aggregates = projection.iter().filter(|expr| is_aggregate(expr)).count();
+        if aggregates == 0 && group_by.is_empty() {
+            if !projection.is_empty() {
+                builder = builder.project(projection)?;
+            }
+            return Ok(builder);
+        }
+        let aggregate_exprs = projection.split_off(projection.len() - aggregates);
+        let grouped = projection.iter().map(|expr| expr.to_string());
+        if aggregate_exprs.iter().any(|expr| !is_aggregate(expr))
+            || !grouped.eq(group_by.iter().map(|expr| expr.to_string()))
+        {
+            return Err(Error::UnsupportedSql(
+                "a select list other than the GROUP BY columns followed by aggregates".to_string(),
+            ));
+        }
+        builder.aggregate(group_by, aggregate_exprs)
+    }
+
+    fn scan(&self, relation: &TableFactor) -> Result<LogicalPlanBuilder, Error> {
+        let name = match relation {
+            TableFactor::Table { name, alias, .. } if alias.is_none() => name.to_string(),
+            relation => return Err(unsupported(relation)),
+        };
+        let (path, format) = self
+            .tables
+            .get(&name)
+            .ok_or_else(|| Error::UnknownTable(name.clone()))?;
+        let data_source = match format {
+            TableFormat::Parquet => DataSource::Parquet(ParquetDataSource::new(path)?),
+            TableFormat::Csv => DataSource::Csv(CsvDataSource::new(path)?),
+        };
+        Ok(LogicalPlanBuilder::scan(path, data_source))
+    }
+}
+
+fn unsupported(construct: impl ToString) -> Error {
+    Error::UnsupportedSql(construct.to_string())
+}
+
+fn is_aggregate(expr: &LogicalExpression) -> bool {
+    matches!(
+        expr,
+        LogicalExpression::Sum(_)
+            | LogicalExpression::Avg(_)
+            | LogicalExpression::Max(_)
+            | LogicalExpression::Min(_)
+            | LogicalExpression::Count(_)
+    )
+}
+
+/// A `LIMIT` or `OFFSET` row count.
+fn row_count(expr: Expr) -> Result<usize, Error> {
+    match &expr {
+        Expr::Value(value) => match &value.value {
+            Value::Number(number, _) => number.parse().map_err(|_| unsupported(&expr)),
+            _ => Err(unsupported(&expr)),
+        },
+        _ => Err(unsupported(&expr)),
+    }
+}
+
+fn expression(expr: Expr) -> Result<LogicalExpression, Error> {
+    match expr {
+        Expr::Identifier(ident) => Ok(col(&ident.value)),
+        Expr::Nested(expr) => expression(*expr),
+        Expr::Value(value) => literal(value.value, false),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => match *expr {
+            Expr::Value(value) => literal(value.value, true),
+            expr => Err(unsupported(format!("-{}", expr))),
+        },
+        Expr::BinaryOp { left, op, right } => {
+            let left = expression(*left)?;
+            let right = expression(*right)?;
+            Ok(match op {
+                BinaryOperator::Plus => LogicalExpression::Add(Box::new(Add::new(left, right))),
+                BinaryOperator::Minus => LogicalExpression::Sub(Box::new(Sub::new(left, right))),
+                BinaryOperator::Multiply => LogicalExpression::Mul(Box::new(Mul::new(left, right))),
+                BinaryOperator::Divide => LogicalExpression::Div(Box::new(Div::new(left, right))),
+                BinaryOperator::Modulo => LogicalExpression::Mod(Box::new(Mod::new(left, right))),
+                BinaryOperator::Eq => left.eq(right),
+                BinaryOperator::NotEq => left.neq(right),
+                BinaryOperator::Gt => left.gt(right),
+                BinaryOperator::GtEq => left.gteq(right),
+                BinaryOperator::Lt => left.lt(right),
+                BinaryOperator::LtEq => left.lteq(right),
+                BinaryOperator::And => left.and(right),
+                BinaryOperator::Or => left.or(right),
+                op => return Err(Error::UnsupportedSql(format!("operator {}", op))),
+            })
+        }
+        Expr::Function(function) => {
+            let name = match function.name.0.as_slice() {
+                [ObjectNamePart::Identifier(ident)] => ident.value.to_lowercase(),
+                _ => return Err(unsupported(&function)),
+            };
+            let arg = match &function.args {
+                FunctionArguments::List(list) if list.duplicate_treatment.is_none() => {
+                    match list.args.as_slice() {
+                        [FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))] => arg.clone(),
+                        _ => return Err(unsupported(&function)),
+                    }
+                }
+                _ => return Err(unsupported(&function)),
+            };
+            let arg = expression(arg)?;
+            match name.as_str() {
+                "sum" => Ok(sum(arg)),
+                "avg" => Ok(avg(arg)),
+                "max" => Ok(max(arg)),
+                "min" => Ok(min(arg)),
+                "count" => Ok(count(arg)),
+                _ => Err(unsupported(&function)),
+            }
+        }
+        expr => Err(unsupported(expr)),
+    }
+}
+
+fn literal(value: Value, negative: bool) -> Result<LogicalExpression, Error> {
+    match value {
+        Value::Number(number, _) => {
+            let number = if negative {
+                format!("-{}", number)
+            } else {
+                number
+            };
+            number
+                .parse::<i32>()
+                .map(lit)
+                .or_else(|_| number.parse::<f64>().map(lit))
+                .map_err(|_| Error::UnsupportedSql(number))
+        }
+        Value::SingleQuotedString(string) if !negative => Ok(lit(string)),
+        Value::Boolean(bool) if !negative => Ok(lit(bool)),
+        value => Err(unsupported(value)),
+    }
+}
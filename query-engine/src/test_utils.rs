@@ -0,0 +1,79 @@
+use std::fmt::Write;
+
+use arrow2::array::{get_display, Array};
+
+use crate::record_batch::RecordBatch;
+
+fn display(array: &dyn Array, row: usize) -> String {
+    let mut result = String::new();
+    get_display(array, "NULL")(&mut result, row).unwrap();
+    result
+}
+
+/// Compares two sequences of batches, panicking with a readable report of
+/// every differing schema, row count or value.
+pub fn assert_batches_eq(actual: &[RecordBatch], expected: &[RecordBatch]) {
+    let mut diff = String::new();
+    if actual.len() != expected.len() {
+        writeln!(
+            diff,
+            "expected {} batches, actual {}",
+            expected.len(),
+            actual.len()
+        )
+        .unwrap();
+    }
+    for (i, (actual, expected)) in actual.iter().zip(expected.iter()).enumerate() {
+        let fields = actual.schema().fields.iter().zip(&expected.schema().fields);
+        if actual.schema().fields.len() != expected.schema().fields.len()
+            || fields.clone().any(|(actual, expected)| {
+                actual.name != expected.name || actual.data_type != expected.data_type
+            })
+        {
+            writeln!(
+                diff,
+                "batch {}: expected schema {:?}, actual {:?}",
+                i,
+                expected.schema().fields,
+                actual.schema().fields
+            )
+            .unwrap();
+            continue;
+        }
+        let (actual_rows, expected_rows) = (actual.columns().len(), expected.columns().len());
+        if actual_rows != expected_rows {
+            writeln!(
+                diff,
+                "batch {}: expected {} rows, actual {}",
+                i, expected_rows, actual_rows
+            )
+            .unwrap();
+            continue;
+        }
+        for ((field, actual), expected) in actual
+            .schema()
+            .fields
+            .iter()
+            .zip(actual.columns().arrays())
+            .zip(expected.columns().arrays())
+        {
+            for row in 0..actual_rows {
+                if *actual.slice(row, 1) != *expected.slice(row, 1) {
+                    writeln!(
+                        diff,
+                        "batch {}, column `{}`, row {}: expected {}, actual {}",
+                        i,
+                        field.name,
+                        row,
+                        display(&**expected, row),
+                        display(&**actual, row)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+    if !diff.is_empty() {
+        panic!("batches are not equal:\n{}", diff);
+    }
+}
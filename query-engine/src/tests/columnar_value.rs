@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use arrow2::array::Int32Array;
+use arrow2::datatypes::DataType;
+use arrow2::scalar::{PrimitiveScalar, Utf8Scalar};
+
+use crate::columnar_value::ColumnarValue;
+use crate::error::Error;
+
+#[test]
+fn test_len_and_data_type() {
+    let array = ColumnarValue::Array(Arc::new(Int32Array::from([Some(1), None])));
+    assert_eq!(array.len(), Some(2));
+    assert_eq!(array.data_type(), DataType::Int32);
+
+    let scalar = ColumnarValue::Scalar(Box::new(Utf8Scalar::<i32>::new(Some("a"))));
+    assert_eq!(scalar.len(), None);
+    assert_eq!(scalar.data_type(), DataType::Utf8);
+}
+
+#[test]
+fn test_into_array() {
+    let scalar = ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(DataType::Int64, Some(7i64))));
+    assert_eq!(
+        format!("{:?}", scalar.into_array(5).unwrap()),
+        "Int64[7, 7, 7, 7, 7]"
+    );
+
+    let null = ColumnarValue::Scalar(Box::new(PrimitiveScalar::<f32>::new(
+        DataType::Float32,
+        None,
+    )));
+    assert_eq!(
+        format!("{:?}", null.into_array(3).unwrap()),
+        "Float32[None, None, None]"
+    );
+
+    // Arrays are returned as they are, whatever the number of rows.
+    let array = ColumnarValue::Array(Arc::new(Int32Array::from([Some(1), None])));
+    assert_eq!(
+        format!("{:?}", array.into_array(5).unwrap()),
+        "Int32[1, None]"
+    );
+}
+
+#[test]
+fn test_into_array_unsupported_scalar() {
+    let scalar = ColumnarValue::Scalar(Box::new(PrimitiveScalar::new(DataType::Int16, Some(7i16))));
+    assert!(matches!(
+        scalar.into_array(2),
+        Err(Error::ScalarToArrayError(_))
+    ));
+}
@@ -0,0 +1,277 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Float64Array, Int32Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+
+use crate::{
+    data_source::{CsvDataSource, DataSource, MemoryDataSource, ParquetDataSource, ScanBatches},
+    error::Error,
+    logical_plan::{LogicalPlan, Scan},
+    physical_plan::{PhysicalPlan, ScanExec},
+    query_planner::create_physical_plan,
+    record_batch::RecordBatch,
+};
+
+fn csv_scan(projection: Option<Vec<String>>) -> Result<Vec<String>, Error> {
+    let ds = DataSource::Csv(CsvDataSource::new("src/tests/test.csv")?);
+    let mut schema = ds.schema();
+    if let Some(projection) = &projection {
+        schema
+            .fields
+            .retain(|field| projection.contains(&field.name));
+    }
+    let batches = PhysicalPlan::Scan(ScanExec::new(ds, projection, schema))
+        .execute()?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(batches[0]
+        .arrays()
+        .iter()
+        .map(|array| format!("{:?}", array))
+        .collect())
+}
+
+#[test]
+fn test_csv_schema() {
+    let schema = DataSource::Csv(CsvDataSource::new("src/tests/test.csv").unwrap()).schema();
+    let types = schema
+        .fields
+        .iter()
+        .map(|field| (field.name.as_str(), field.data_type.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            ("id", DataType::Int32),
+            ("name", DataType::Utf8),
+            ("price", DataType::Float64),
+            ("active", DataType::Boolean),
+        ]
+    );
+}
+
+#[test]
+fn test_csv_scan() {
+    assert_eq!(
+        csv_scan(None).unwrap(),
+        [
+            "Int32[1, 2, 3]",
+            "Utf8Array[Smith, Jane, None, bob]",
+            "Float64[2.5, 3, None]",
+            "BooleanArray[true, false, true]",
+        ]
+    );
+}
+
+#[test]
+fn test_csv_keep_empty_strings() {
+    let ds = CsvDataSource::new("src/tests/test.csv")
+        .unwrap()
+        // No column reads empty strings as nulls.
+        .with_empty_as_null(vec![]);
+    let ds = DataSource::Csv(ds);
+    let schema = ds.schema();
+    let batches = PhysicalPlan::Scan(ScanExec::new(ds, None, schema))
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", batches[0].arrays()[1]),
+        "Utf8Array[Smith, Jane, , bob]"
+    );
+}
+
+#[test]
+fn test_csv_projection() {
+    let projection = vec!["price".to_string(), "id".to_string()];
+    assert_eq!(
+        csv_scan(Some(projection)).unwrap(),
+        ["Int32[1, 2, 3]", "Float64[2.5, 3, None]"]
+    );
+    let projection = vec!["missing".to_string()];
+    assert!(matches!(
+        csv_scan(Some(projection)),
+        Err(Error::NoFieldInSchema(name)) if name == "missing"
+    ));
+}
+
+#[test]
+fn test_csv_scan_streams() {
+    let ds = CsvDataSource::new("src/tests/test.csv")
+        .unwrap()
+        .with_batch_size(1);
+    let mut ids = vec![];
+    for batch in DataSource::Csv(ds).scan(None).unwrap() {
+        let batch = batch.unwrap();
+        assert_eq!(batch.len(), 1);
+        ids.push(format!("{:?}", batch.arrays()[0]));
+    }
+    assert_eq!(ids, ["Int32[1]", "Int32[2]", "Int32[3]"]);
+}
+
+fn memory_source() -> DataSource {
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Utf8, true),
+        Field::new("c", DataType::Float64, true),
+    ]);
+    let batch = |a: &[i32], b: &[&str], c: &[f64]| {
+        let columns: Vec<Arc<dyn Array>> = vec![
+            Arc::new(Int32Array::from_slice(a)),
+            Arc::new(Utf8Array::<i32>::from_slice(b)),
+            Arc::new(Float64Array::from_slice(c)),
+        ];
+        RecordBatch::try_new(schema.clone(), Chunk::new(columns)).unwrap()
+    };
+    let batches = vec![
+        batch(&[1, 2], &["x", "y"], &[0.5, 1.5]),
+        batch(&[3], &["z"], &[2.5]),
+    ];
+    DataSource::Memory(MemoryDataSource::try_new(schema.clone(), batches).unwrap())
+}
+
+#[test]
+fn test_memory_scan() {
+    let projection = vec!["c".to_string(), "a".to_string()];
+    let batches = memory_source()
+        .scan(Some(projection))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let columns = batches
+        .iter()
+        .map(|batch| {
+            batch
+                .arrays()
+                .iter()
+                .map(|array| format!("{:?}", array))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // The columns come in schema order.
+    assert_eq!(
+        columns,
+        [
+            ["Int32[1, 2]", "Float64[0.5, 1.5]"],
+            ["Int32[3]", "Float64[2.5]"],
+        ]
+    );
+
+    let projection = vec!["a".to_string(), "d".to_string()];
+    assert!(matches!(
+        memory_source().scan(Some(projection)),
+        Err(Error::NoFieldInSchema(name)) if name == "d"
+    ));
+}
+
+fn debug_batches(batches: ScanBatches) -> Vec<Vec<String>> {
+    batches
+        .map(|batch| {
+            batch
+                .unwrap()
+                .arrays()
+                .iter()
+                .map(|array| format!("{:?}", array))
+                .collect()
+        })
+        .collect()
+}
+
+fn assert_same_scan(source: fn() -> DataSource, names: &[&str], indices: Vec<usize>) {
+    let names = names.iter().map(|name| name.to_string()).collect();
+    let by_name = debug_batches(source().scan(Some(names)).unwrap());
+    let by_index = debug_batches(source().scan_indices(indices).unwrap());
+    assert!(!by_name.is_empty());
+    assert_eq!(by_name, by_index);
+
+    let len = source().schema().fields.len();
+    assert!(matches!(
+        source().scan_indices(vec![0, len]),
+        Err(Error::ExceedingBoundsError(i)) if i == len
+    ));
+    // Positions are taken in schema order only.
+    assert!(matches!(
+        source().scan_indices(vec![1, 0]),
+        Err(Error::InvalidArgument(_))
+    ));
+    assert!(matches!(
+        source().scan_indices(vec![0, 0]),
+        Err(Error::InvalidArgument(_))
+    ));
+}
+
+#[test]
+fn test_scan_indices() {
+    assert_same_scan(
+        || DataSource::Parquet(ParquetDataSource::new("src/tests/test.parquet").unwrap()),
+        &["id", "bool_col"],
+        vec![0, 1],
+    );
+    assert_same_scan(
+        || DataSource::Csv(CsvDataSource::new("src/tests/test.csv").unwrap()),
+        &["price", "id"],
+        vec![0, 2],
+    );
+    assert_same_scan(memory_source, &["c", "a"], vec![0, 2]);
+}
+
+/// Scans `names`, which are out of order and repeat a column, and checks that
+/// the columns come once each in schema order.
+fn assert_schema_order(source: fn() -> DataSource, names: &[&str]) {
+    let names = names
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    let schema = source().projected_schema(Some(&names)).unwrap();
+    let fields = &source().schema().fields;
+    let expected = fields
+        .iter()
+        .filter(|field| names.contains(&field.name))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(schema.fields, expected);
+
+    // A logical scan labels the columns it reads with that schema.
+    let plan = LogicalPlan::Scan(Scan::new("", source(), Some(names)).unwrap());
+    assert_eq!(plan.schema().unwrap(), &schema);
+    let batches = create_physical_plan(plan)
+        .unwrap()
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let types = batches[0]
+        .arrays()
+        .iter()
+        .map(|array| array.data_type().clone())
+        .collect::<Vec<_>>();
+    let expected = expected
+        .iter()
+        .map(|field| field.data_type.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(types, expected);
+
+    let missing = vec!["missing".to_string()];
+    assert!(matches!(
+        source().scan(Some(missing.clone())),
+        Err(Error::NoFieldInSchema(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        Scan::new("", source(), Some(missing)),
+        Err(Error::NoFieldInSchema(name)) if name == "missing"
+    ));
+}
+
+#[test]
+fn test_projection_follows_schema_order() {
+    assert_schema_order(
+        || DataSource::Parquet(ParquetDataSource::new("src/tests/test.parquet").unwrap()),
+        &["bool_col", "id", "bool_col"],
+    );
+    assert_schema_order(
+        || DataSource::Csv(CsvDataSource::new("src/tests/test.csv").unwrap()),
+        &["price", "id", "price"],
+    );
+    assert_schema_order(memory_source, &["c", "a", "c"]);
+}
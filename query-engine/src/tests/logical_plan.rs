@@ -0,0 +1,208 @@
+use crate::{
+    data_source::{DataSource, ParquetDataSource},
+    error::Error,
+    logical_plan::{
+        builder::LogicalPlanBuilder,
+        format_logical_plan,
+        logical_expression::{Add, Div, LogicalExpression, Mul},
+        LogicalPlan, OrderBy,
+    },
+    prelude::*,
+    query_planner::create_physical_plan,
+};
+
+fn builder() -> LogicalPlanBuilder {
+    let path = "src/tests/test.parquet";
+    let ds = DataSource::Parquet(ParquetDataSource::new(path).unwrap());
+    LogicalPlanBuilder::scan(path, ds)
+}
+
+fn plan() -> LogicalPlan {
+    builder()
+        .filter(col("id").gt(lit(1)))
+        .project(vec![col("id"), col("bool_col")])
+        .unwrap()
+        .sort(vec![OrderBy::desc("id")])
+        .limit(1, 2)
+        .build()
+}
+
+#[test]
+fn test_builder_display() {
+    assert_eq!(
+        format_logical_plan(&plan(), 0),
+        "Limit: skip=1, fetch=2 \n \
+         \tSort: #id DESC,  \n \
+         \t \tProjection: #id, #bool_col,  \n \
+         \t \t \tSelection: #id > '1',  \n \
+         \t \t \t \tScan: src/tests/test.parquet; projection=None \n"
+    );
+}
+
+#[test]
+fn test_builder_schema() {
+    let plan = plan();
+    let names = plan
+        .schema()
+        .unwrap()
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["id", "bool_col"]);
+}
+
+#[test]
+fn test_builder_execute() {
+    let batches = plan()
+        .to_physical_plan()
+        .unwrap()
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let ids = batches
+        .iter()
+        .map(|batch| format!("{:?}", batch[0]))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, ["Int32[6, 5]"]);
+}
+
+#[test]
+fn test_create_physical_plan() {
+    let plan = builder().filter(col("id").eq(lit(3))).build();
+    let physical = create_physical_plan(plan).unwrap();
+    assert_eq!(physical.name(), "Selection");
+    let batches = physical
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(format!("{:?}", batches[0][0]), "Int32[3]");
+}
+
+#[test]
+fn test_create_physical_plan_unknown_column() {
+    let plan = builder().filter(col("nope").eq(lit(3))).build();
+    assert!(matches!(
+        create_physical_plan(plan),
+        Err(Error::NoFieldInSchema(name)) if name == "nope"
+    ));
+}
+
+fn add(left: LogicalExpression, right: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Add(Box::new(Add::new(left, right)))
+}
+
+fn mul(left: LogicalExpression, right: LogicalExpression) -> LogicalExpression {
+    LogicalExpression::Mul(Box::new(Mul::new(left, right)))
+}
+
+#[test]
+fn test_fold_constants() {
+    let expr = add(col("a"), mul(lit(2), lit(3))).fold_constants();
+    assert_eq!(expr.to_string(), "#a + '6'");
+
+    let expr = add(lit(1), lit(2))
+        .gt(lit(2))
+        .and(lit(true))
+        .fold_constants();
+    assert!(matches!(expr, LogicalExpression::LiteralBool(b) if b.value));
+
+    let expr = add(mul(col("a"), lit(2)), lit(3)).fold_constants();
+    assert_eq!(expr.to_string(), "#a * '2' + '3'");
+}
+
+#[test]
+fn test_fold_constants_keeps_errors() {
+    let overflow = add(lit(i32::MAX), lit(1)).fold_constants();
+    assert!(matches!(overflow, LogicalExpression::Add(_)));
+    let by_zero = LogicalExpression::Div(Box::new(Div::new(lit(1), lit(0)))).fold_constants();
+    assert!(matches!(by_zero, LogicalExpression::Div(_)));
+    let nan = lit(f64::NAN).eq(lit(f64::NAN)).fold_constants();
+    assert!(matches!(nan, LogicalExpression::Eq(_)));
+}
+
+#[test]
+fn test_optimize_folds_constants() {
+    let plan = builder()
+        .filter(col("id").gt(add(lit(1), lit(2))))
+        .build()
+        .optimize()
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Scan: src/tests/test.parquet; projection=id, ; filter=#id > '3' \n"
+    );
+}
+
+#[test]
+fn test_predicate_push_down() {
+    let plan = builder()
+        .project(vec![col("id"), col("bool_col")])
+        .unwrap()
+        .sort(vec![OrderBy::asc("id")])
+        .filter(col("id").gt(lit(1)))
+        .build()
+        .optimize()
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Sort: #id ASC,  \n \
+         \tProjection: #id, #bool_col,  \n \
+         \t \tScan: src/tests/test.parquet; projection=id, bool_col, ; filter=#id > '1' \n"
+    );
+}
+
+#[test]
+fn test_scan_filter() {
+    let plan = || {
+        builder()
+            .filter(col("id").gt(lit(1)))
+            .filter(col("id").lt(lit(6)))
+            .project(vec![col("bool_col")])
+            .unwrap()
+            .build()
+    };
+    let optimized = plan().optimize().unwrap();
+    // The scan keeps the column its filter reads.
+    assert_eq!(
+        format_logical_plan(&optimized, 0),
+        "Projection: #bool_col,  \n \
+         \tScan: src/tests/test.parquet; projection=id, bool_col, ; filter=#id > '1' && #id < '6' \n"
+    );
+    let execute = |plan| {
+        create_physical_plan(plan)
+            .unwrap()
+            .execute()
+            .unwrap()
+            .map(|batch| format!("{:?}", batch.unwrap()[0]))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(execute(optimized), execute(plan()));
+    assert_eq!(
+        execute(plan().optimize().unwrap()),
+        ["BooleanArray[true, false, true, false]"]
+    );
+}
+
+#[test]
+fn test_predicate_on_computed_column_stays() {
+    let plan = builder()
+        .project(vec![col("id"), add(col("id"), lit(1))])
+        .unwrap()
+        .filter(col("add").gt(lit(3)))
+        .limit(0, 2)
+        .filter(col("id").gt(lit(1)))
+        .build()
+        .optimize()
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Selection: #id > '1',  \n \
+         \tLimit: skip=0, fetch=2 \n \
+         \t \tSelection: #add > '3',  \n \
+         \t \t \tProjection: #id, #id + '1',  \n \
+         \t \t \t \tScan: src/tests/test.parquet; projection=id,  \n"
+    );
+}
@@ -1,6 +1,24 @@
+mod columnar_value;
+mod data_source;
+mod logical_plan;
+mod physical_expressions;
+mod physical_plan;
+mod record_batch;
+mod schema;
+#[cfg(feature = "sql")]
+mod sql;
+mod type_coercion;
+
+use arrow2::datatypes::DataType;
+
 use crate::{
+    config::max_expression_depth,
     dataframe::{DataFrame, DataFrameTrait},
-    logical_plan::format_logical_plan,
+    error::Error,
+    logical_plan::{
+        format_logical_plan,
+        logical_expression::{Add, LogicalExpression},
+    },
     prelude::*,
 };
 
@@ -37,13 +55,109 @@ fn test_max() {
     assert_eq!(format!("{:?}", result[0][1]), "Int32[6, 7]");
 }
 
+#[test]
+fn test_sum_booleans() {
+    let df =
+        DataFrame::parquet("src/tests/test.parquet").aggregate(vec![], vec![sum(col("bool_col"))]);
+    assert_eq!(df.schema().fields[0].data_type, DataType::Int64);
+    let result = df.execute().unwrap();
+    assert_eq!(format!("{:?}", result[0][0]), "Int64[4]");
+}
+
 #[test]
 fn test_projection_push_down() {
     let df = DataFrame::parquet("src/tests/test.parquet")
         .project(vec![col("bool_col")])
         .filter(col("id").eq(lit_int(4)));
     assert_eq!(
-        format_logical_plan(&df.logical_plan().optimize(), 0),
-        "Selection: #id == '4',  \n \tProjection: #bool_col,  \n \t \tScan: src/tests/test.parquet; projection=bool_col, id,  \n"
+        format_logical_plan(&df.logical_plan().optimize().unwrap(), 0),
+        "Selection: #id == '4',  \n \tProjection: #bool_col,  \n \t \tScan: src/tests/test.parquet; projection=id, bool_col,  \n"
+    );
+}
+
+#[test]
+fn test_lit() {
+    let plan = DataFrame::parquet("src/tests/test.parquet").logical_plan();
+    let cases = [
+        (lit(5), DataType::Int32, "'5'"),
+        (lit(2.5), DataType::Float64, "'2.5'"),
+        (lit("x"), DataType::Utf8, "'x'"),
+        (lit(true), DataType::Boolean, "'true'"),
+    ];
+    for (expr, data_type, display) in cases {
+        assert_eq!(expr.to_field(&plan).unwrap().data_type, data_type);
+        assert_eq!(format!("{}", expr), display);
+    }
+    assert!(matches!(lit(5), LogicalExpression::LiteralInteger(_)));
+    assert!(matches!(lit(2.5), LogicalExpression::LiteralFloat(_)));
+    assert!(matches!(lit("x"), LogicalExpression::LiteralString(_)));
+    assert!(matches!(lit(true), LogicalExpression::LiteralBool(_)));
+}
+
+#[test]
+fn test_expression_too_deep() {
+    let mut expr = col("id");
+    for _ in 0..max_expression_depth() {
+        expr = LogicalExpression::Add(Box::new(Add::new(expr, lit(1))));
+    }
+    let result = DataFrame::parquet("src/tests/test.parquet")
+        .project(vec![expr])
+        .execute();
+    assert!(matches!(result, Err(Error::ExpressionTooDeep(1000))));
+}
+
+#[test]
+fn test_filter_too_deep() {
+    let df = || {
+        let mut expr = col("id");
+        for _ in 0..max_expression_depth() {
+            expr = LogicalExpression::Add(Box::new(Add::new(expr, lit(1))));
+        }
+        DataFrame::parquet("src/tests/test.parquet").filter(expr.eq(lit(0)))
+    };
+    // Rejected before the optimizer recurses into the filter.
+    assert!(matches!(
+        df().logical_plan().optimize(),
+        Err(Error::ExpressionTooDeep(1000))
+    ));
+    assert!(matches!(
+        df().execute(),
+        Err(Error::ExpressionTooDeep(1000))
+    ));
+}
+
+#[test]
+fn test_sum_count_avg() {
+    let result = DataFrame::parquet("src/tests/test.parquet")
+        .aggregate(
+            vec![col("bool_col")],
+            vec![sum(col("id")), count(col("id")), avg(col("id"))],
+        )
+        .execute()
+        .unwrap();
+    assert_eq!(format!("{:?}", result[0][1]), "Int64[12, 16]");
+    assert_eq!(format!("{:?}", result[0][2]), "Int64[4, 4]");
+    assert_eq!(format!("{:?}", result[0][3]), "Float64[3, 4]");
+}
+
+#[test]
+fn test_error_trait() {
+    let error: Box<dyn std::error::Error> = Box::new(Error::DowncastError);
+    assert_eq!(error.to_string(), "Error wile downcasting Array.");
+    assert!(error.source().is_none());
+
+    let error: Box<dyn std::error::Error> = Box::new(Error::ArrowError(
+        arrow2::error::ArrowError::InvalidArgumentError("bad".to_string()),
+    ));
+    assert!(error.to_string().starts_with("ArrowError: "));
+    assert!(error.source().is_some());
+
+    let result: Result<(), Box<dyn std::error::Error>> = (|| {
+        Err(Error::DifferentSizes("#0".to_string(), "#1".to_string()))?;
+        Ok(())
+    })();
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "The expressions `#0` and `#1` have different sizes."
     );
 }
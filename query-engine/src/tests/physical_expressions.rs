@@ -0,0 +1,2019 @@
+use std::sync::Arc;
+
+use arrow2::array::{
+    Array, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array, MutableArray,
+    MutablePrimitiveArray, Utf8Array,
+};
+use arrow2::bitmap::Bitmap;
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::scalar::{BooleanScalar, PrimitiveScalar, Scalar, Utf8Scalar};
+
+use crate::{
+    columnar_value::ColumnarValue, error::Error, operator::Operator,
+    physical_plan::physical_expressions::*,
+};
+
+fn chunk(arrays: Vec<Arc<dyn Array>>) -> Chunk<Arc<dyn Array>> {
+    Chunk::new(arrays)
+}
+
+fn evaluate_array(expr: &dyn PhysicalExpression, input: &Chunk<Arc<dyn Array>>) -> String {
+    match expr.evaluate(input).unwrap() {
+        ColumnarValue::Array(array) => format!("{:?}", array),
+        ColumnarValue::Scalar(scalar) => format!("{:?}", scalar),
+    }
+}
+
+#[test]
+fn test_empty_string_to_null() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("a"),
+        Some(""),
+        None,
+    ]))]);
+    let expr = EmptyStringToNullExpression::new(Box::new(ColumnExpression { index: 0 }));
+    assert_eq!(evaluate_array(&expr, &input), "Utf8Array[a, None, None]");
+
+    let expr =
+        EmptyStringToNullExpression::new(Box::new(LiteralStringExpression::new("".to_string())));
+    match expr.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+            assert_eq!(scalar.value(), None);
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+fn aggregate_batches(expr: &dyn PhysicalAggregateExpression, batches: &[&[f64]]) -> Option<f64> {
+    let mut accumulator = expr.create_accumulator(0);
+    for batch in batches {
+        let array = Arc::new(Float64Array::from_slice(batch)) as Arc<dyn Array>;
+        accumulator
+            .accumulate(&[ColumnarValue::Array(array)], None)
+            .unwrap();
+    }
+    match accumulator.final_value().unwrap() {
+        ColumnarValue::Scalar(scalar) => scalar
+            .as_any()
+            .downcast_ref::<PrimitiveScalar<f64>>()
+            .unwrap()
+            .value(),
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_int64_column() {
+    let input = chunk(vec![Arc::new(Int64Array::from([
+        Some(i64::MAX),
+        None,
+        Some(-1),
+    ]))]);
+    let expr = ColumnExpression { index: 0 };
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        format!("Int64[{}, None, -1]", i64::MAX)
+    );
+}
+
+#[test]
+fn test_column_errors() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1)]))]);
+    let expr = ColumnExpression { index: 1 };
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::ColumnIndexError(1))
+    ));
+
+    let input = chunk(vec![Arc::new(arrow2::array::UInt8Array::from([Some(1)]))]);
+    let expr = ColumnExpression { index: 0 };
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::UnsupportedColumnType(DataType::UInt8))
+    ));
+}
+
+#[test]
+fn test_resolve_column_by_name() {
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Utf8, true),
+        Field::new("a", DataType::Int64, true),
+    ]);
+    let column = ColumnByNameExpression::new("b").resolve(&schema).unwrap();
+    assert_eq!(column.index, 1);
+
+    assert!(matches!(
+        ColumnByNameExpression::new("c").resolve(&schema),
+        Err(Error::NoFieldInSchema(name)) if name == "c"
+    ));
+    assert!(matches!(
+        ColumnByNameExpression::new("a").resolve(&schema),
+        Err(Error::AmbiguousColumn(name)) if name == "a"
+    ));
+}
+
+#[test]
+fn test_min_max_nan_is_order_independent() {
+    let orders: [[f64; 3]; 6] = [
+        [1.0, f64::NAN, 2.0],
+        [1.0, 2.0, f64::NAN],
+        [f64::NAN, 1.0, 2.0],
+        [f64::NAN, 2.0, 1.0],
+        [2.0, 1.0, f64::NAN],
+        [2.0, f64::NAN, 1.0],
+    ];
+    let column = || Box::new(ColumnExpression { index: 0 });
+    for order in orders {
+        let batches = [&order[0..1], &order[1..2], &order[2..3]];
+        for batches in [&batches[..], &[&order[..]]] {
+            let max = MaxExpression::new(column());
+            assert_eq!(aggregate_batches(&max, batches), Some(2.0));
+            let min = MinExpression::new(column());
+            assert_eq!(aggregate_batches(&min, batches), Some(1.0));
+
+            let max = MaxExpression::new(column()).with_nan_handling(NanHandling::Propagate);
+            assert!(aggregate_batches(&max, batches).unwrap().is_nan());
+            let min = MinExpression::new(column()).with_nan_handling(NanHandling::Propagate);
+            assert!(aggregate_batches(&min, batches).unwrap().is_nan());
+        }
+    }
+}
+
+fn evaluate_bool_scalar(
+    expr: &dyn PhysicalExpression,
+    input: &Chunk<Arc<dyn Array>>,
+) -> Option<bool> {
+    match expr.evaluate(input).unwrap() {
+        ColumnarValue::Scalar(scalar) => scalar
+            .as_any()
+            .downcast_ref::<BooleanScalar>()
+            .unwrap()
+            .value(),
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_neq_array_scalar() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1), None, Some(2)]))]);
+    let expr = NeqExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(LiteralIntegerExpression::new(1)),
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, None, true]"
+    );
+    let expr = NeqExpression::new(
+        Box::new(LiteralIntegerExpression::new(1)),
+        Box::new(ColumnExpression { index: 0 }),
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, None, true]"
+    );
+}
+
+#[test]
+fn test_ordering_comparisons() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(2), Some(3), None])),
+        Arc::new(Int32Array::from([Some(2), Some(2), Some(2), Some(2)])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let two = || Box::new(LiteralIntegerExpression::new(2));
+    let cases: [(Box<dyn PhysicalExpression>, &str); 6] = [
+        (
+            Box::new(LtExpression::new(column(0), two())),
+            "[true, false, false, None]",
+        ),
+        (
+            Box::new(LtEqExpression::new(column(0), column(1))),
+            "[true, true, false, None]",
+        ),
+        (
+            Box::new(GtExpression::new(column(0), column(1))),
+            "[false, false, true, None]",
+        ),
+        (
+            Box::new(GtEqExpression::new(column(0), two())),
+            "[false, true, true, None]",
+        ),
+        // A scalar on the left flips the kernel, not the result.
+        (
+            Box::new(LtExpression::new(two(), column(0))),
+            "[false, false, true, None]",
+        ),
+        (
+            Box::new(GtEqExpression::new(two(), column(0))),
+            "[true, true, false, None]",
+        ),
+    ];
+    for (expr, expected) in cases {
+        assert_eq!(
+            evaluate_array(&*expr, &input),
+            format!("BooleanArray{}", expected),
+            "{}",
+            expr
+        );
+    }
+
+    let lt = LtExpression::new(two(), Box::new(LiteralIntegerExpression::new(3)));
+    assert_eq!(evaluate_bool_scalar(&lt, &input), Some(true));
+    let gt = GtExpression::new(
+        Box::new(LiteralFloatExpression::new(f64::NAN)),
+        Box::new(LiteralFloatExpression::new(1.0)),
+    );
+    assert_eq!(evaluate_bool_scalar(&gt, &input), Some(false));
+}
+
+#[test]
+fn test_and_or() {
+    let (t, f) = (Some(true), Some(false));
+    let input = chunk(vec![
+        Arc::new(BooleanArray::from([t, t, t, f, f, f, None, None, None])),
+        Arc::new(BooleanArray::from([t, f, None, t, f, None, t, f, None])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let boolean = |value| Box::new(LiteralBoolExpression::new(value));
+    let null = || Box::new(LiteralNullExpression::new(DataType::Boolean));
+
+    let and = AndExpression::new(column(0), column(1));
+    assert_eq!(
+        evaluate_array(&and, &input),
+        "BooleanArray[true, false, None, false, false, false, None, false, None]"
+    );
+    let or = OrExpression::new(column(0), column(1));
+    assert_eq!(
+        evaluate_array(&or, &input),
+        "BooleanArray[true, true, true, true, false, None, true, None, None]"
+    );
+
+    let and = AndExpression::new(column(1), boolean(false));
+    assert_eq!(
+        evaluate_array(&and, &input),
+        "BooleanArray[false, false, false, false, false, false, false, false, false]"
+    );
+    let or = OrExpression::new(boolean(true), column(1));
+    assert_eq!(
+        evaluate_array(&or, &input),
+        "BooleanArray[true, true, true, true, true, true, true, true, true]"
+    );
+    let and = AndExpression::new(null(), column(1));
+    assert_eq!(
+        evaluate_array(&and, &input),
+        "BooleanArray[None, false, None, None, false, None, None, false, None]"
+    );
+    let or = OrExpression::new(column(1), null());
+    assert_eq!(
+        evaluate_array(&or, &input),
+        "BooleanArray[true, None, None, true, None, None, true, None, None]"
+    );
+
+    let and = AndExpression::new(boolean(false), null());
+    assert_eq!(evaluate_bool_scalar(&and, &input), Some(false));
+    let and = AndExpression::new(boolean(true), null());
+    assert_eq!(evaluate_bool_scalar(&and, &input), None);
+    let or = OrExpression::new(null(), boolean(true));
+    assert_eq!(evaluate_bool_scalar(&or, &input), Some(true));
+    let or = OrExpression::new(boolean(false), boolean(false));
+    assert_eq!(evaluate_bool_scalar(&or, &input), Some(false));
+}
+
+#[test]
+fn test_not() {
+    let input = chunk(vec![Arc::new(Int32Array::from([
+        Some(1),
+        Some(2),
+        None,
+        Some(1),
+    ]))]);
+    let eq = || {
+        Box::new(EqExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(LiteralIntegerExpression::new(1)),
+        ))
+    };
+    let not = NotExpression::new(eq());
+    assert_eq!(format!("{}", not), format!("NOT {}", eq()));
+    let mask = eq().evaluate(&input).unwrap().to_array(input.len());
+    let negated = not.evaluate(&input).unwrap().to_array(input.len());
+    assert_eq!(
+        format!("{:?}", negated),
+        "BooleanArray[false, true, None, false]"
+    );
+    assert_eq!(negated.validity(), mask.validity());
+
+    let not = NotExpression::new(Box::new(LiteralNullExpression::new(DataType::Boolean)));
+    assert_eq!(evaluate_bool_scalar(&not, &input), None);
+    let not = NotExpression::new(Box::new(LiteralBoolExpression::new(false)));
+    assert_eq!(evaluate_bool_scalar(&not, &input), Some(true));
+}
+
+#[test]
+fn test_neq_display() {
+    let expr = NeqExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    assert_eq!(format!("{}", expr), "#0 != #1");
+}
+
+#[test]
+fn test_neq_scalar_null() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1)]))]);
+    let null = LiteralIntegerExpression {
+        value: PrimitiveScalar::new(DataType::Int32, None),
+    };
+    let expr = NeqExpression::new(Box::new(null), Box::new(LiteralIntegerExpression::new(1)));
+    assert_eq!(evaluate_bool_scalar(&expr, &input), None);
+    let expr = NeqExpression::new(
+        Box::new(LiteralIntegerExpression::new(2)),
+        Box::new(LiteralIntegerExpression::new(1)),
+    );
+    assert_eq!(evaluate_bool_scalar(&expr, &input), Some(true));
+}
+
+#[test]
+fn test_div_modes() {
+    let input = chunk(vec![Arc::new(Int32Array::from_slice([7, 9]))]);
+    let seven = || Box::new(LiteralIntegerExpression::new(7));
+    let two = || Box::new(LiteralIntegerExpression::new(2));
+
+    let expr = DivExpression::new(seven(), two());
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(3), data_type: Int32 }"
+    );
+    let expr = FloatDivExpression::new(seven(), two());
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(3.5), data_type: Float64 }"
+    );
+
+    let expr = DivExpression::new(Box::new(ColumnExpression { index: 0 }), two());
+    assert_eq!(evaluate_array(&expr, &input), "Int32[3, 4]");
+    let expr = FloatDivExpression::new(Box::new(ColumnExpression { index: 0 }), two());
+    assert_eq!(evaluate_array(&expr, &input), "Float64[3.5, 4.5]");
+    assert_eq!(
+        format!("{}", expr),
+        "CAST(#0 AS Float64) / CAST(#PrimitiveScalar { value: Some(2), data_type: Int32 } AS Float64)"
+    );
+    // Unlike the integer division it would otherwise be mistaken for.
+    let div = DivExpression::new(Box::new(ColumnExpression { index: 0 }), two());
+    assert_ne!(expr.to_string(), div.to_string());
+}
+
+#[test]
+fn test_division_by_zero() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(6), Some(7), None, Some(8)])),
+        Arc::new(Int32Array::from([Some(2), Some(0), Some(0), None])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let zero = || Box::new(LiteralIntegerExpression::new(0));
+
+    let expr = DivExpression::new(column(0), zero());
+    assert!(matches!(expr.evaluate(&input), Err(Error::DivisionByZero)));
+    let expr = DivExpression::new(Box::new(LiteralIntegerExpression::new(7)), zero());
+    assert!(matches!(expr.evaluate(&input), Err(Error::DivisionByZero)));
+
+    let expr = DivExpression::new(column(0), column(1));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[3, None, None, None]");
+    let mut out = MutablePrimitiveArray::<i32>::new();
+    expr.evaluate_into(&input, &mut out).unwrap();
+    assert_eq!(format!("{:?}", out.as_box()), "Int32[3, None, None, None]");
+
+    // Floats follow IEEE 754 instead.
+    let expr = DivExpression::new(
+        Box::new(LiteralFloatExpression::new(1.0)),
+        Box::new(LiteralFloatExpression::new(0.0)),
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(inf), data_type: Float64 }"
+    );
+}
+
+#[test]
+fn test_overflow_handling() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(70_000), None])),
+        Arc::new(Int32Array::from([Some(2), Some(70_000), Some(70_000)])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+
+    let expr = MulExpression::new(column(0), column(1));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        format!("Int32[2, {}, None]", 70_000i32.wrapping_mul(70_000))
+    );
+    let expr = expr.with_overflow_handling(OverflowHandling::Error);
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::ArithmeticOverflow)
+    ));
+    let mut out = MutablePrimitiveArray::<i32>::new();
+    assert!(expr.evaluate_into(&input, &mut out).is_err());
+
+    // Evaluating in place wraps like `evaluate`.
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(i32::MIN), Some(i32::MAX)])),
+        Arc::new(Int32Array::from([Some(-1), Some(2)])),
+    ]);
+    for (expr, expected) in [
+        (
+            Box::new(DivExpression::new(column(0), column(1))) as Box<dyn PhysicalExpression>,
+            format!("Int32[{}, {}]", i32::MIN, i32::MAX / 2),
+        ),
+        (
+            Box::new(MulExpression::new(column(0), column(1))),
+            format!(
+                "Int32[{}, {}]",
+                i32::MIN.wrapping_neg(),
+                i32::MAX.wrapping_mul(2)
+            ),
+        ),
+    ] {
+        assert_eq!(evaluate_array(&*expr, &input), expected);
+        let mut out = MutablePrimitiveArray::<i32>::new();
+        expr.evaluate_into(&input, &mut out).unwrap();
+        assert_eq!(format!("{:?}", out.as_box()), expected);
+    }
+
+    // Nulls in the operands are not overflows.
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), None])),
+        Arc::new(Int32Array::from([Some(2), Some(3)])),
+    ]);
+    let expr =
+        MulExpression::new(column(0), column(1)).with_overflow_handling(OverflowHandling::Error);
+    assert_eq!(evaluate_array(&expr, &input), "Int32[2, None]");
+
+    let expr = AddExpression::new(
+        Box::new(LiteralIntegerExpression::new(i32::MAX)),
+        Box::new(LiteralIntegerExpression::new(1)),
+    )
+    .with_overflow_handling(OverflowHandling::Error);
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::ArithmeticOverflow)
+    ));
+}
+
+#[test]
+fn test_mod() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(17), Some(-7), None])),
+        Arc::new(Float64Array::from_slice([5.5, -1.0, 4.0])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let int = |value| Box::new(LiteralIntegerExpression::new(value));
+
+    let expr = ModExpression::new(column(0), int(5));
+    assert_eq!(
+        expr.to_string(),
+        "#0 % #PrimitiveScalar { value: Some(5), data_type: Int32 }"
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Int32[2, -2, None]");
+    let expr = ModExpression::new(int(10), int(4));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(2), data_type: Int32 }"
+    );
+    let expr = ModExpression::new(column(1), Box::new(LiteralFloatExpression::new(2.0)));
+    assert_eq!(evaluate_array(&expr, &input), "Float64[1.5, -1, 0]");
+
+    let expr = ModExpression::new(column(0), int(0));
+    assert!(matches!(expr.evaluate(&input), Err(Error::DivisionByZero)));
+
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from_slice(["a", "b"]))]);
+    let expr = ModExpression::new(
+        column(0),
+        Box::new(LiteralStringExpression::new("c".to_string())),
+    );
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::PhysicalTypeNotSuported(_))
+    ));
+}
+
+#[test]
+fn test_neg() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(3), None, Some(-4)])),
+        Arc::new(Utf8Array::<i32>::from_slice(["a", "b", "c"])),
+    ]);
+    let expr = NegExpression::new(Box::new(ColumnExpression { index: 0 }));
+    assert_eq!(expr.to_string(), "-#0");
+    assert_eq!(evaluate_array(&expr, &input), "Int32[-3, None, 4]");
+
+    let expr = NegExpression::new(Box::new(LiteralFloatExpression::new(2.5)));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(-2.5), data_type: Float64 }"
+    );
+
+    let expr = NegExpression::new(Box::new(ColumnExpression { index: 1 }));
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::PhysicalTypeNotSuported(_))
+    ));
+}
+
+#[test]
+fn test_is_null() {
+    let input = chunk(vec![Arc::new(Int32Array::from([
+        Some(1),
+        None,
+        Some(3),
+        None,
+    ]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+
+    let expr = IsNullExpression::new(column());
+    assert_eq!(expr.to_string(), "#0 IS NULL");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, false, true]"
+    );
+    let expr = IsNotNullExpression::new(column());
+    assert_eq!(expr.to_string(), "#0 IS NOT NULL");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, true, false]"
+    );
+
+    let null = || Box::new(LiteralNullExpression::new(DataType::Int32));
+    assert_eq!(
+        evaluate_array(&IsNullExpression::new(null()), &input),
+        "BooleanScalar { value: Some(true) }"
+    );
+    assert_eq!(
+        evaluate_array(&IsNotNullExpression::new(null()), &input),
+        "BooleanScalar { value: Some(false) }"
+    );
+}
+
+#[test]
+fn test_case() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(5), Some(12), None])),
+        Arc::new(Float64Array::from_slice([0.5, 1.5, 2.5, 3.5])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let int = |value| Box::new(LiteralIntegerExpression::new(value));
+    let below = |value| Box::new(LtExpression::new(column(0), int(value)));
+    let branches = || -> Vec<(Box<dyn PhysicalExpression>, Box<dyn PhysicalExpression>)> {
+        vec![(below(3), int(100)), (below(10), column(1))]
+    };
+
+    let expr = CaseExpression::new(branches(), Some(int(-1)));
+    assert_eq!(
+        expr.to_string(),
+        "CASE WHEN #0 < #PrimitiveScalar { value: Some(3), data_type: Int32 } \
+         THEN #PrimitiveScalar { value: Some(100), data_type: Int32 } \
+         WHEN #0 < #PrimitiveScalar { value: Some(10), data_type: Int32 } THEN #1 \
+         ELSE #PrimitiveScalar { value: Some(-1), data_type: Int32 } END"
+    );
+    // The results are coerced to Float64, and a null condition is not true.
+    assert_eq!(evaluate_array(&expr, &input), "Float64[100, 1.5, -1, -1]");
+
+    let expr = CaseExpression::new(branches(), None);
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Float64[100, 1.5, None, None]"
+    );
+
+    let expr = CaseExpression::new(
+        vec![(
+            Box::new(LiteralBoolExpression::new(true)),
+            Box::new(LiteralStringExpression::new("a".to_string())),
+        )],
+        None,
+    );
+    assert!(expr.is_constant());
+    match expr.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+            assert_eq!(scalar.value(), Some("a"));
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+
+    let expr = CaseExpression::new(
+        vec![(
+            below(3),
+            Box::new(LiteralStringExpression::new("a".to_string())),
+        )],
+        Some(int(1)),
+    );
+    assert!(matches!(
+        expr.evaluate(&input),
+        Err(Error::IncompatibleTypes(..))
+    ));
+}
+
+#[test]
+fn test_coalesce() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), None, None, None])),
+        Arc::new(Int32Array::from([Some(2), Some(20), None, None])),
+        Arc::new(Float64Array::from([Some(3.0), Some(30.0), Some(0.5), None])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index }) as Box<dyn PhysicalExpression>;
+
+    let expr = CoalesceExpression::new(vec![column(0), column(1), column(2)]).unwrap();
+    assert_eq!(expr.to_string(), "COALESCE(#0, #1, #2)");
+    assert_eq!(evaluate_array(&expr, &input), "Float64[1, 20, 0.5, None]");
+
+    // Nothing after the first constant that isn't null is ever used, but the
+    // result still has the type of every child.
+    let expr = CoalesceExpression::new(vec![
+        column(0),
+        Box::new(LiteralNullExpression::new(DataType::Int32)),
+        Box::new(LiteralIntegerExpression::new(-1)),
+        column(2),
+    ])
+    .unwrap();
+    assert_eq!(
+        expr.to_string(),
+        "COALESCE(#0, NULL::Int32, #PrimitiveScalar { value: Some(-1), data_type: Int32 }, #2)"
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Float64[1, -1, -1, -1]");
+}
+
+#[test]
+fn test_null_if() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(3), Some(-1), None, Some(-1)])),
+        Arc::new(Utf8Array::<i32>::from([
+            Some("a"),
+            Some(""),
+            None,
+            Some("b"),
+        ])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index }) as Box<dyn PhysicalExpression>;
+
+    let expr = NullIfExpression::new(column(0), Box::new(LiteralIntegerExpression::new(-1)));
+    assert_eq!(
+        expr.to_string(),
+        "NULLIF(#0, #PrimitiveScalar { value: Some(-1), data_type: Int32 })"
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Int32[3, None, None, None]");
+
+    let expr = NullIfExpression::new(
+        column(1),
+        Box::new(LiteralStringExpression::new(String::new())),
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Utf8Array[a, None, None, b]");
+}
+
+#[test]
+fn test_greatest_least() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(9), Some(1), Some(2), None, None])),
+        Arc::new(Int32Array::from([Some(1), Some(8), Some(3), Some(4), None])),
+        Arc::new(Int64Array::from([Some(5), Some(2), Some(7), None, None])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index }) as Box<dyn PhysicalExpression>;
+
+    let expr = GreatestExpression::new(vec![column(0), column(1), column(2)]);
+    assert_eq!(expr.to_string(), "GREATEST(#0, #1, #2)");
+    assert_eq!(evaluate_array(&expr, &input), "Int64[9, 8, 7, 4, None]");
+
+    let expr = LeastExpression::new(vec![column(0), column(1), column(2)]);
+    assert_eq!(expr.to_string(), "LEAST(#0, #1, #2)");
+    assert_eq!(evaluate_array(&expr, &input), "Int64[1, 1, 2, 4, None]");
+
+    let expr = GreatestExpression::new(vec![
+        column(0),
+        Box::new(LiteralFloatExpression::new(f64::NAN)),
+    ]);
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Float64[NaN, NaN, NaN, NaN, NaN]"
+    );
+
+    let expr = LeastExpression::new(vec![
+        column(0),
+        Box::new(LiteralStringExpression::new("a".to_string())),
+    ]);
+    assert!(expr.evaluate(&input).is_err());
+}
+
+#[test]
+fn test_in_list() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(2), Some(5), None])),
+        Arc::new(Utf8Array::<i32>::from([
+            Some("a"),
+            Some("b"),
+            None,
+            Some("c"),
+        ])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let ints = |values: &[Option<i32>]| {
+        values
+            .iter()
+            .map(|value| Box::new(PrimitiveScalar::new(DataType::Int32, *value)) as Box<dyn Scalar>)
+            .collect::<Vec<_>>()
+    };
+
+    let expr = InListExpression::new(column(0), ints(&[Some(1), Some(3), Some(5)])).unwrap();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, true, None]"
+    );
+    let expr = expr.negated();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, false, None]"
+    );
+
+    // A null in the list only matters for rows that match nothing else.
+    let expr = InListExpression::new(column(0), ints(&[Some(1), None]))
+        .unwrap()
+        .negated();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, None, None, None]"
+    );
+
+    let strings = ["a", "c"]
+        .iter()
+        .map(|value| Box::new(Utf8Scalar::<i32>::new(Some(*value))) as Box<dyn Scalar>)
+        .collect();
+    let expr = InListExpression::new(column(1), strings).unwrap();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, None, true]"
+    );
+
+    let expr = InListExpression::new(Box::new(LiteralIntegerExpression::new(3)), ints(&[Some(3)]))
+        .unwrap();
+    match expr.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => assert_eq!(
+            scalar
+                .as_any()
+                .downcast_ref::<BooleanScalar>()
+                .unwrap()
+                .value(),
+            Some(true)
+        ),
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_between() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(2), Some(5), Some(6), None])),
+        Arc::new(Int32Array::from_slice([0, 3, 5, 7, 0])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let int = |value| Box::new(LiteralIntegerExpression::new(value));
+
+    let expr = BetweenExpression::new(column(0), int(2), int(5));
+    assert_eq!(
+        expr.to_string(),
+        "#0 BETWEEN #PrimitiveScalar { value: Some(2), data_type: Int32 } \
+         AND #PrimitiveScalar { value: Some(5), data_type: Int32 }"
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, true, false, None]"
+    );
+
+    let expr = BetweenExpression::new(int(4), column(1), int(6));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, true, false, false, true]"
+    );
+
+    let expr = BetweenExpression::new(Box::new(LiteralFloatExpression::new(2.5)), int(2), int(3));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanScalar { value: Some(true) }"
+    );
+}
+
+fn final_scalar(accumulator: Box<dyn Accumulator>) -> Box<dyn Scalar> {
+    match accumulator.final_value().unwrap() {
+        ColumnarValue::Scalar(scalar) => scalar,
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_product() {
+    let expr = ProductExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let mut accumulator = expr.create_accumulator(0);
+    for batch in [[Some(2), Some(3)], [None, Some(4)]] {
+        let array = Arc::new(Int32Array::from(batch)) as Arc<dyn Array>;
+        accumulator
+            .accumulate(&[ColumnarValue::Array(array)], None)
+            .unwrap();
+    }
+    assert_eq!(
+        format!("{:?}", final_scalar(accumulator)),
+        "PrimitiveScalar { value: Some(24), data_type: Int64 }"
+    );
+
+    let accumulator = expr.create_accumulator(0);
+    assert!(!final_scalar(accumulator).is_valid());
+
+    // Integer and float batches don't mix.
+    let mut accumulator = expr.create_accumulator(0);
+    let ints = Arc::new(Int32Array::from_slice([2])) as Arc<dyn Array>;
+    let floats = Arc::new(Float32Array::from_slice([1.5])) as Arc<dyn Array>;
+    accumulator
+        .accumulate(&[ColumnarValue::Array(ints)], None)
+        .unwrap();
+    let error = accumulator.accumulate(&[ColumnarValue::Array(floats)], None);
+    assert!(matches!(
+        error,
+        Err(Error::IncompatibleTypes(product, batch, Operator::Multiply))
+            if product == "Int64" && batch == "Float32"
+    ));
+}
+
+fn accumulate_batches(
+    expr: &dyn PhysicalAggregateExpression,
+    batches: Vec<Arc<dyn Array>>,
+) -> Box<dyn Scalar> {
+    let mut accumulator = expr.create_accumulator(0);
+    for array in batches {
+        accumulator
+            .accumulate(&[ColumnarValue::Array(array)], None)
+            .unwrap();
+    }
+    final_scalar(accumulator)
+}
+
+#[test]
+fn test_max_min_utf8_and_boolean() {
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let strings = || -> Vec<Arc<dyn Array>> {
+        vec![
+            Arc::new(Utf8Array::<i32>::from([Some("pear"), None])),
+            Arc::new(Utf8Array::<i32>::from([Some("apple"), Some("zucchini")])),
+        ]
+    };
+    let max = accumulate_batches(&MaxExpression::new(column()), strings());
+    let max = max.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+    assert_eq!(max.value(), Some("zucchini"));
+    let min = accumulate_batches(&MinExpression::new(column()), strings());
+    let min = min.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+    assert_eq!(min.value(), Some("apple"));
+
+    let flags = || -> Vec<Arc<dyn Array>> {
+        vec![
+            Arc::new(BooleanArray::from([Some(false), None])),
+            Arc::new(BooleanArray::from([Some(true), Some(false)])),
+        ]
+    };
+    let max = accumulate_batches(&MaxExpression::new(column()), flags());
+    let max = max.as_any().downcast_ref::<BooleanScalar>().unwrap();
+    assert_eq!(max.value(), Some(true));
+    let min = accumulate_batches(&MinExpression::new(column()), flags());
+    let min = min.as_any().downcast_ref::<BooleanScalar>().unwrap();
+    assert_eq!(min.value(), Some(false));
+}
+
+fn count_distinct(batches: Vec<Arc<dyn Array>>) -> Option<i64> {
+    let expr = CountDistinctExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let count = accumulate_batches(&expr, batches);
+    let count = count.as_any().downcast_ref::<PrimitiveScalar<i64>>();
+    count.unwrap().value()
+}
+
+#[test]
+fn test_count_distinct() {
+    let ints = vec![
+        Arc::new(Int32Array::from([Some(1), None, Some(2)])) as Arc<dyn Array>,
+        Arc::new(Int32Array::from([Some(2), None, Some(1)])),
+    ];
+    assert_eq!(count_distinct(ints), Some(2));
+    let longs = vec![Arc::new(Int64Array::from([Some(5), Some(5), Some(6)])) as Arc<dyn Array>];
+    assert_eq!(count_distinct(longs), Some(2));
+    let floats = vec![Arc::new(Float64Array::from([
+        Some(0.0),
+        Some(-0.0),
+        Some(f64::NAN),
+        Some(-f64::NAN),
+        Some(1.5),
+        None,
+    ])) as Arc<dyn Array>];
+    assert_eq!(count_distinct(floats), Some(3));
+    let strings = vec![
+        Arc::new(Utf8Array::<i32>::from([Some("a"), Some("b"), None])) as Arc<dyn Array>,
+        Arc::new(Utf8Array::<i32>::from([Some("b"), Some(""), None])),
+    ];
+    assert_eq!(count_distinct(strings), Some(3));
+    let nulls = vec![Arc::new(Int32Array::from([None, None])) as Arc<dyn Array>];
+    assert_eq!(count_distinct(nulls), Some(0));
+}
+
+#[test]
+fn test_max_single_batch() {
+    // The accumulator starts from a null and must adopt the first batch.
+    let expr = MaxExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![Arc::new(Int32Array::from([
+            Some(3),
+            None,
+            Some(7),
+            Some(5),
+        ]))],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(7), data_type: Int32 }"
+    );
+}
+
+#[test]
+fn test_float32() {
+    let input = chunk(vec![
+        Arc::new(Float32Array::from([Some(1.5), None, Some(-2.0)])),
+        Arc::new(Float32Array::from_slice([0.25, 1.0, 4.0])),
+    ]);
+    let expr = AddExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Float32[1.75, None, 2]");
+
+    let expr = MaxExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Float32Array::from([Some(1.5), None])),
+            Arc::new(Float32Array::from([Some(f32::NAN), Some(3.5)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(3.5), data_type: Float32 }"
+    );
+}
+
+#[test]
+fn test_cast() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), None, Some(-3)])),
+        Arc::new(Utf8Array::<i32>::from([Some("42"), Some("x"), None])),
+    ]);
+    let expr = CastExpression::new(Box::new(ColumnExpression { index: 0 }), DataType::Float64);
+    assert_eq!(expr.to_string(), "CAST(#0 AS Float64)");
+    assert_eq!(evaluate_array(&expr, &input), "Float64[1, None, -3]");
+
+    let expr = CastExpression::new(Box::new(ColumnExpression { index: 1 }), DataType::Int32);
+    assert_eq!(evaluate_array(&expr, &input), "Int32[42, None, None]");
+
+    let expr = CastExpression::new(Box::new(LiteralIntegerExpression::new(7)), DataType::Utf8);
+    assert!(expr.is_constant());
+    match expr.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+            assert_eq!(scalar.value(), Some("7"));
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+
+    // The scalar arithmetic arms see Float32 operands once they are cast.
+    let float32 = |value| {
+        Box::new(CastExpression::new(
+            Box::new(LiteralFloatExpression::new(value)),
+            DataType::Float32,
+        ))
+    };
+    let expr = AddExpression::new(float32(0.5), float32(0.25));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(0.75), data_type: Float32 }"
+    );
+}
+
+#[test]
+fn test_sum() {
+    let expr = SumExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Int32Array::from([Some(1), Some(2), None])),
+            Arc::new(Int32Array::from([None, Some(4)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(7), data_type: Int64 }"
+    );
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Float64Array::from([Some(0.5), None])),
+            Arc::new(Float64Array::from([Some(1.25), Some(2.0)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(3.75), data_type: Float64 }"
+    );
+
+    // Only the rows of the group count.
+    let mut accumulator = expr.create_accumulator(0);
+    let array = Arc::new(Int32Array::from_slice([1, 2, 3])) as Arc<dyn Array>;
+    let group = Bitmap::from([true, false, true]);
+    accumulator
+        .accumulate(&[ColumnarValue::Array(array)], Some(&group))
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", final_scalar(accumulator)),
+        "PrimitiveScalar { value: Some(4), data_type: Int64 }"
+    );
+
+    let accumulator = expr.create_accumulator(0);
+    assert!(!final_scalar(accumulator).is_valid());
+}
+
+#[test]
+fn test_sum_and_count_booleans() {
+    let batches = || -> Vec<Arc<dyn Array>> {
+        vec![
+            Arc::new(BooleanArray::from([Some(true), None, Some(false)])),
+            Arc::new(BooleanArray::from([Some(true), Some(true), None])),
+        ]
+    };
+    let sum = SumExpression::new(Box::new(ColumnExpression { index: 0 }));
+    assert_eq!(
+        format!("{:?}", accumulate_batches(&sum, batches())),
+        "PrimitiveScalar { value: Some(3), data_type: Int64 }"
+    );
+    let count = CountExpression::new(Box::new(ColumnExpression { index: 0 }));
+    assert_eq!(
+        format!("{:?}", accumulate_batches(&count, batches())),
+        "PrimitiveScalar { value: Some(4), data_type: Int64 }"
+    );
+
+    // Only the rows of the group count, and a group of nulls sums to null.
+    let mut accumulator = sum.create_accumulator(0);
+    let array = Arc::new(BooleanArray::from([Some(true), None, Some(true)])) as Arc<dyn Array>;
+    accumulator
+        .accumulate(
+            &[ColumnarValue::Array(array.clone())],
+            Some(&Bitmap::from([true, true, false])),
+        )
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", final_scalar(accumulator)),
+        "PrimitiveScalar { value: Some(1), data_type: Int64 }"
+    );
+    let mut accumulator = sum.create_accumulator(0);
+    accumulator
+        .accumulate(
+            &[ColumnarValue::Array(array)],
+            Some(&Bitmap::from([false, true, false])),
+        )
+        .unwrap();
+    assert!(!final_scalar(accumulator).is_valid());
+}
+
+#[test]
+fn test_sum_widens_int32() {
+    let expr = SumExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Int32Array::from_slice([i32::MAX, i32::MAX])),
+            Arc::new(Int32Array::from_slice([i32::MAX, 3])),
+        ],
+    );
+    let result = result.as_any().downcast_ref::<PrimitiveScalar<i64>>();
+    assert_eq!(result.unwrap().value(), Some(3 * i32::MAX as i64 + 3));
+}
+
+#[test]
+fn test_count() {
+    let expr = CountExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Int32Array::from([Some(1), None, Some(3)])),
+            Arc::new(Int32Array::from([None, None, Some(0)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(3), data_type: Int64 }"
+    );
+
+    let mut accumulator = expr.create_accumulator(0);
+    let array = Arc::new(Int32Array::from([Some(1), None, Some(3)])) as Arc<dyn Array>;
+    let group = Bitmap::from([true, true, false]);
+    accumulator
+        .accumulate(&[ColumnarValue::Array(array)], Some(&group))
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", final_scalar(accumulator)),
+        "PrimitiveScalar { value: Some(1), data_type: Int64 }"
+    );
+}
+
+#[test]
+fn test_avg() {
+    let expr = AvgExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Int32Array::from([Some(1), None, Some(2)])),
+            Arc::new(Int32Array::from([None, Some(4)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(2.3333333333333335), data_type: Float64 }"
+    );
+    let result = accumulate_batches(
+        &expr,
+        vec![Arc::new(Float64Array::from([None, Some(1.5), Some(2.5)]))],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(2.0), data_type: Float64 }"
+    );
+
+    let result = accumulate_batches(&expr, vec![Arc::new(Int32Array::from([None, None]))]);
+    assert!(!result.is_valid());
+}
+
+#[test]
+fn test_mode() {
+    let expr = ModeExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let result = accumulate_batches(
+        &expr,
+        vec![
+            Arc::new(Int32Array::from([Some(1), Some(2), Some(3)])),
+            Arc::new(Int32Array::from([None, None, Some(3), Some(2)])),
+        ],
+    );
+    assert_eq!(
+        format!("{:?}", result),
+        "PrimitiveScalar { value: Some(2), data_type: Int32 }"
+    );
+
+    let result = accumulate_batches(
+        &expr,
+        vec![Arc::new(Utf8Array::<i32>::from([
+            Some("a"),
+            Some("b"),
+            Some("b"),
+        ]))],
+    );
+    assert_eq!(
+        result
+            .as_any()
+            .downcast_ref::<Utf8Scalar<i32>>()
+            .unwrap()
+            .value(),
+        Some("b")
+    );
+
+    let result = accumulate_batches(&expr, vec![Arc::new(Int32Array::from([None, None]))]);
+    assert!(!result.is_valid());
+}
+
+fn accumulate_pair(
+    expr: &dyn PhysicalAggregateExpression,
+    x: Vec<Option<i32>>,
+    y: Vec<Option<f64>>,
+) -> Option<f64> {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from(x)),
+        Arc::new(Float64Array::from(y)),
+    ]);
+    let mut accumulator = expr.create_accumulator(0);
+    accumulator
+        .accumulate(&[expr.evaluate(&input).unwrap()], None)
+        .unwrap();
+    let scalar = final_scalar(accumulator);
+    scalar
+        .as_any()
+        .downcast_ref::<PrimitiveScalar<f64>>()
+        .unwrap()
+        .value()
+}
+
+#[test]
+fn test_covar_corr() {
+    let x = vec![Some(1), Some(2), None, Some(3), Some(4), Some(5), Some(6)];
+    let y = vec![
+        Some(2.0),
+        Some(4.0),
+        Some(9.0),
+        Some(5.0),
+        Some(4.0),
+        Some(5.0),
+        None,
+    ];
+    let covar = CovarExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    let corr = CorrExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    assert_eq!(covar.to_string(), "covar(#0, #1)");
+    assert!((accumulate_pair(&covar, x.clone(), y.clone()).unwrap() - 1.5).abs() < 1e-12);
+    let expected = 6.0 / 60f64.sqrt();
+    assert!((accumulate_pair(&corr, x, y).unwrap() - expected).abs() < 1e-12);
+
+    assert_eq!(
+        accumulate_pair(&covar, vec![Some(1)], vec![Some(2.0)]),
+        None
+    );
+    assert_eq!(
+        accumulate_pair(&corr, vec![Some(1), Some(2)], vec![Some(3.0), Some(3.0)]),
+        None
+    );
+    // The variance of a constant 0.1 only rounds to a tiny positive number.
+    assert_eq!(
+        accumulate_pair(&corr, (1..=7).map(Some).collect(), vec![Some(0.1); 7]),
+        None
+    );
+}
+
+#[test]
+fn test_regr_slope_intercept() {
+    // y = 3x - 2
+    let x = vec![Some(0), Some(1), None, Some(2), Some(4)];
+    let y = vec![Some(-2.0), Some(1.0), Some(100.0), Some(4.0), Some(10.0)];
+    let slope = RegrSlopeExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(ColumnExpression { index: 0 }),
+    );
+    let intercept = RegrInterceptExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(ColumnExpression { index: 0 }),
+    );
+    assert_eq!(slope.to_string(), "regr_slope(#1, #0)");
+    assert_eq!(accumulate_pair(&slope, x.clone(), y.clone()), Some(3.0));
+    assert_eq!(accumulate_pair(&intercept, x, y), Some(-2.0));
+
+    let x = vec![Some(2), Some(2)];
+    let y = vec![Some(1.0), Some(5.0)];
+    assert_eq!(accumulate_pair(&slope, x.clone(), y.clone()), None);
+    assert_eq!(accumulate_pair(&intercept, x, y), None);
+    // A constant 0.1 as x only has zero variance up to rounding.
+    let slope = RegrSlopeExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    let y = (1..=7).map(Some).collect::<Vec<_>>();
+    assert_eq!(accumulate_pair(&slope, y, vec![Some(0.1); 7]), None);
+}
+
+#[test]
+fn test_width_bucket() {
+    let input = chunk(vec![Arc::new(Float64Array::from([
+        Some(-1.0),
+        Some(0.0),
+        Some(2.5),
+        Some(4.999),
+        Some(5.0),
+        Some(10.0),
+        Some(10.5),
+        None,
+    ]))]);
+    let expr =
+        WidthBucketExpression::new(Box::new(ColumnExpression { index: 0 }), 0.0, 10.0, 4).unwrap();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Int32[0, 1, 2, 2, 3, 5, 5, None]"
+    );
+
+    let expr = WidthBucketExpression::new(Box::new(LiteralIntegerExpression::new(7)), 0.0, 10.0, 4)
+        .unwrap();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(3), data_type: Int32 }"
+    );
+
+    assert!(
+        WidthBucketExpression::new(Box::new(ColumnExpression { index: 0 }), 1.0, 1.0, 4).is_err()
+    );
+    assert!(
+        WidthBucketExpression::new(Box::new(ColumnExpression { index: 0 }), 0.0, 1.0, 0).is_err()
+    );
+}
+
+#[test]
+fn test_arithmetic_null_propagation() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(10), None, Some(10), None])),
+        Arc::new(Int32Array::from([Some(4), Some(4), None, None])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let int = |value: Option<i32>| {
+        Box::new(LiteralIntegerExpression {
+            value: PrimitiveScalar::new(DataType::Int32, value),
+        })
+    };
+
+    let expr = SubExpression::new(column(0), column(1));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[6, None, None, None]");
+    let expr = AddExpression::new(column(0), int(Some(5)));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[15, None, 15, None]");
+    let expr = AddExpression::new(column(0), int(None));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Int32[None, None, None, None]"
+    );
+    let expr = SubExpression::new(int(Some(5)), column(1));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[1, 1, None, None]");
+    let expr = SubExpression::new(int(None), column(1));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Int32[None, None, None, None]"
+    );
+    let expr = SubExpression::new(int(Some(5)), int(None));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: None, data_type: Int32 }"
+    );
+}
+
+#[test]
+fn test_evaluate_into_reuses_buffer() {
+    // #0 * 2 + #1
+    let expr = AddExpression::new(
+        Box::new(MulExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(LiteralIntegerExpression::new(2)),
+        )),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    let mut out = MutablePrimitiveArray::<i32>::with_capacity(4);
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(1), Some(2), None, Some(4)])),
+        Arc::new(Int32Array::from([Some(1), None, Some(1), Some(1)])),
+    ]);
+    expr.evaluate_into(&input, &mut out).unwrap();
+    assert_eq!(format!("{:?}", out.as_box()), evaluate_array(&expr, &input));
+    let buffer = out.values().as_ptr();
+
+    let input = chunk(vec![
+        Arc::new(Int32Array::from_slice([5, 6, 7])),
+        Arc::new(Int32Array::from_slice([1, 1, 1])),
+    ]);
+    expr.evaluate_into(&input, &mut out).unwrap();
+    assert_eq!(format!("{:?}", out.as_box()), "Int32[11, 13, 15]");
+    assert_eq!(out.values().as_ptr(), buffer);
+}
+
+#[test]
+fn test_mixed_type_operands_are_coerced() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from_slice([1, 2, 3])),
+        Arc::new(Float64Array::from_slice([0.5, 2.0, 3.5])),
+    ]);
+    let expr = AddExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Float64[1.5, 4, 6.5]");
+    let expr = EqExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(LiteralIntegerExpression::new(2)),
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, false]"
+    );
+    let expr = EqExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(LiteralStringExpression::new("1".to_string())),
+    );
+    assert!(expr.evaluate(&input).is_err());
+}
+
+#[test]
+fn test_mixed_int_float_math() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1), None, Some(3)]))]);
+    let expr = AddExpression::new(
+        Box::new(LiteralIntegerExpression::new(1)),
+        Box::new(LiteralFloatExpression::new(2.5)),
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "PrimitiveScalar { value: Some(3.5), data_type: Float64 }"
+    );
+    let expr = MulExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(LiteralFloatExpression::new(0.5)),
+    );
+    assert_eq!(evaluate_array(&expr, &input), "Float64[0.5, None, 1.5]");
+}
+
+#[test]
+fn test_concat() {
+    let input = chunk(vec![
+        Arc::new(Utf8Array::<i32>::from([Some("ab"), None, Some("")])),
+        Arc::new(Utf8Array::<i32>::from([Some("cd"), Some("x"), Some("y")])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let literal = |value: &str| Box::new(LiteralStringExpression::new(value.to_string()));
+
+    let expr = ConcatExpression::new(column(0), column(1));
+    assert_eq!(evaluate_array(&expr, &input), "Utf8Array[abcd, None, y]");
+    let expr = ConcatExpression::new(column(0), literal(".txt"));
+    assert_eq!(
+        expr.to_string(),
+        "#0 || #Utf8Scalar { value: Some(\".txt\"), phantom: PhantomData<i32> }"
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[ab.txt, None, .txt]"
+    );
+
+    let expr = ConcatExpression::new(literal("a"), literal("b"));
+    match expr.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar.as_any().downcast_ref::<Utf8Scalar<i32>>().unwrap();
+            assert_eq!(scalar.value(), Some("ab"));
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_starts_ends_with_contains() {
+    let input = chunk(vec![
+        Arc::new(Utf8Array::<i32>::from([
+            Some("apple pie"),
+            Some("pineapple"),
+            None,
+            Some("grape"),
+        ])),
+        Arc::new(Utf8Array::<i32>::from([
+            Some("pie"),
+            Some("pine"),
+            Some("x"),
+            None,
+        ])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let apple = || Box::new(LiteralStringExpression::new("apple".to_string()));
+
+    let expr = StartsWithExpression::new(column(0), apple());
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, None, false]"
+    );
+    let expr = EndsWithExpression::new(column(0), apple());
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, None, false]"
+    );
+    let expr = ContainsExpression::new(column(0), apple());
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, true, None, false]"
+    );
+
+    // The pattern can vary by row.
+    let expr = StartsWithExpression::new(column(0), column(1));
+    assert_eq!(expr.to_string(), "starts_with(#0, #1)");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, true, None, None]"
+    );
+    let expr = EndsWithExpression::new(column(0), column(1));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, None, None]"
+    );
+}
+
+#[test]
+fn test_substring() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("hello"),
+        Some("héllo wörld"),
+        Some(""),
+        None,
+    ]))]);
+    let substring = |start, length| {
+        SubstringExpression::new(Box::new(ColumnExpression { index: 0 }), start, length)
+    };
+
+    let expr = substring(2, Some(3));
+    assert_eq!(expr.to_string(), "SUBSTRING(#0 FROM 2 FOR 3)");
+    assert_eq!(evaluate_array(&expr, &input), "Utf8Array[ell, éll, , None]");
+    assert_eq!(
+        evaluate_array(&substring(4, Some(100)), &input),
+        "Utf8Array[lo, lo wörld, , None]"
+    );
+    assert_eq!(
+        evaluate_array(&substring(8, None), &input),
+        "Utf8Array[, örld, , None]"
+    );
+    // The window starts before the string, so fewer characters are left.
+    assert_eq!(
+        evaluate_array(&substring(-1, Some(3)), &input),
+        "Utf8Array[h, h, , None]"
+    );
+    assert_eq!(
+        evaluate_array(&substring(1, Some(-2)), &input),
+        "Utf8Array[, , , None]"
+    );
+}
+
+#[test]
+fn test_upper_lower() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("straße"),
+        Some("ÉCOLE"),
+        None,
+        Some("MiXeD 1"),
+    ]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+
+    let expr = UpperExpression::new(column());
+    assert_eq!(expr.to_string(), "UPPER(#0)");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[STRASSE, ÉCOLE, None, MIXED 1]"
+    );
+    let expr = LowerExpression::new(column());
+    assert_eq!(expr.to_string(), "LOWER(#0)");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[straße, école, None, mixed 1]"
+    );
+}
+
+#[test]
+fn test_trim() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("  a b \t"),
+        Some("xxaxyx"),
+        None,
+        Some("\n"),
+    ]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+
+    let expr = TrimExpression::new(column(), TrimMode::Both);
+    assert_eq!(expr.to_string(), "TRIM(#0)");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[a b, xxaxyx, None, ]"
+    );
+    let expr = TrimExpression::new(column(), TrimMode::Left);
+    assert_eq!(expr.to_string(), "LTRIM(#0)");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[a b \t, xxaxyx, None, ]"
+    );
+    let expr = TrimExpression::new(column(), TrimMode::Right).with_characters("xy");
+    assert_eq!(expr.to_string(), "RTRIM(#0, 'xy')");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[  a b \t, xxa, None, \n]"
+    );
+}
+
+#[test]
+fn test_replace() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("a-b-c"),
+        Some("2024-01-31"),
+        None,
+    ]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+
+    let expr = ReplaceExpression::new(column(), "-".to_string(), "/".to_string());
+    assert_eq!(expr.to_string(), "REPLACE(#0, '-', '/')");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[a/b/c, 2024/01/31, None]"
+    );
+
+    let expr = RegexpReplaceExpression::new(column(), r"(\d+)-(\d+)-(\d+)", "$3.$2.$1".to_string())
+        .unwrap();
+    assert_eq!(
+        expr.to_string(),
+        r"regexp_replace(#0, '(\d+)-(\d+)-(\d+)', '$3.$2.$1')"
+    );
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "Utf8Array[a-b-c, 31.01.2024, None]"
+    );
+
+    assert!(matches!(
+        RegexpReplaceExpression::new(column(), "(", String::new()),
+        Err(Error::RegexError(_))
+    ));
+}
+
+fn like(pattern: &str, escape: Option<char>) -> LikeExpression {
+    LikeExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        pattern.to_string(),
+        escape,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_like() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("50%"),
+        Some("50 percent"),
+        Some("5_0"),
+        None,
+        Some(""),
+    ]))]);
+    assert_eq!(
+        evaluate_array(&like("50%", None), &input),
+        "BooleanArray[true, true, false, None, false]"
+    );
+    assert_eq!(
+        evaluate_array(&like("%", None), &input),
+        "BooleanArray[true, true, true, None, true]"
+    );
+    assert_eq!(
+        evaluate_array(&like("_%_0", None), &input),
+        "BooleanArray[false, false, true, None, false]"
+    );
+
+    let expr = like("50\\%", Some('\\'));
+    assert_eq!(expr.to_string(), "#0 LIKE '50\\%' ESCAPE '\\'");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, false, false, None, false]"
+    );
+    assert_eq!(
+        evaluate_array(&like("5!_0", Some('!')), &input),
+        "BooleanArray[false, false, true, None, false]"
+    );
+    // An escaped ordinary character matches itself.
+    assert_eq!(
+        evaluate_array(&like("!50%", Some('!')), &input),
+        "BooleanArray[true, true, false, None, false]"
+    );
+    assert!(LikeExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        "50!".to_string(),
+        Some('!')
+    )
+    .is_err());
+}
+
+#[test]
+fn test_like_prefix_and_single_char() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("Abc"),
+        Some("abc"),
+        Some("xbc"),
+        Some("bc"),
+        None,
+    ]))]);
+    assert_eq!(
+        evaluate_array(&like("A%", None), &input),
+        "BooleanArray[true, false, false, false, None]"
+    );
+    assert_eq!(
+        evaluate_array(&like("_bc", None), &input),
+        "BooleanArray[true, true, true, false, None]"
+    );
+
+    let expr = LikeExpression::new(
+        Box::new(LiteralNullExpression::new(DataType::Utf8)),
+        "%".to_string(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanScalar { value: None }"
+    );
+}
+
+#[test]
+fn test_ilike() {
+    let input = chunk(vec![Arc::new(Utf8Array::<i32>::from([
+        Some("Alice"),
+        Some("ALICE"),
+        Some("bob"),
+        None,
+        Some("ÉCOLE"),
+    ]))]);
+    let ilike = |pattern: &str| {
+        ILikeExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            pattern.to_string(),
+            None,
+        )
+        .unwrap()
+    };
+    let expr = ilike("al%");
+    assert_eq!(expr.to_string(), "#0 ILIKE 'al%'");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[true, true, false, None, false]"
+    );
+    assert_eq!(
+        evaluate_array(&ilike("%CE"), &input),
+        "BooleanArray[true, true, false, None, false]"
+    );
+    assert_eq!(
+        evaluate_array(&ilike("é%"), &input),
+        "BooleanArray[false, false, false, None, true]"
+    );
+
+    let expr = ilike("AL%").negated();
+    assert_eq!(expr.to_string(), "#0 NOT ILIKE 'AL%'");
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[false, false, true, None, true]"
+    );
+    assert_eq!(
+        evaluate_array(&like("Al%", None).negated(), &input),
+        "BooleanArray[false, true, true, None, true]"
+    );
+}
+
+#[test]
+fn test_count_if() {
+    let array = BooleanArray::from([Some(true), Some(false), None, Some(true), Some(true)]);
+    assert_eq!(count_true(&array), 3);
+    assert_eq!(count_true(&array.slice(1, 3)), 1);
+
+    let expr = CountIfExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let mut accumulator = expr.create_accumulator(0);
+    let group = Bitmap::from([true, true, true, false, true]);
+    accumulator
+        .accumulate(&[ColumnarValue::Array(Arc::new(array))], Some(&group))
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", final_scalar(accumulator)),
+        "PrimitiveScalar { value: Some(2), data_type: Int64 }"
+    );
+}
+
+#[test]
+fn test_scalar_broadcast_threshold() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1), None, Some(7)]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let seven = || Box::new(LiteralIntegerExpression::new(7));
+    let evaluate = |threshold| {
+        (
+            evaluate_array(
+                &SubExpression::new(column(), seven()).with_broadcast_threshold(threshold),
+                &input,
+            ),
+            evaluate_array(
+                &EqExpression::new(seven(), column()).with_broadcast_threshold(threshold),
+                &input,
+            ),
+            evaluate_array(
+                &BitAndExpression::new(column(), seven()).with_broadcast_threshold(threshold),
+                &input,
+            ),
+        )
+    };
+    let scalar_kernels = evaluate(0);
+    let broadcast = evaluate(1024);
+    assert_eq!(scalar_kernels, broadcast);
+    assert_eq!(broadcast.0, "Int32[-6, None, 0]");
+    assert_eq!(broadcast.1, "BooleanArray[false, None, true]");
+    assert_eq!(broadcast.2, "Int32[1, None, 7]");
+}
+
+#[test]
+fn test_null_literal_operand() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1), None, Some(3)]))]);
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let null = |data_type| Box::new(LiteralNullExpression::new(data_type));
+
+    let expr = AddExpression::new(column(), null(DataType::Int32));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[None, None, None]");
+    let expr = MulExpression::new(null(DataType::Float64), column());
+    assert_eq!(evaluate_array(&expr, &input), "Float64[None, None, None]");
+    let expr = SubExpression::new(column(), null(DataType::Null));
+    assert_eq!(evaluate_array(&expr, &input), "Int32[None, None, None]");
+    let expr = EqExpression::new(column(), null(DataType::Int32));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanArray[None, None, None]"
+    );
+    let expr = AddExpression::new(column(), null(DataType::Utf8));
+    assert!(expr.evaluate(&input).is_err());
+}
+
+#[test]
+fn test_null_literal_scalar_operands() {
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(1)]))]);
+    let null = |data_type| Box::new(LiteralNullExpression::new(data_type));
+    let one = || Box::new(LiteralIntegerExpression::new(1));
+
+    for data_type in [DataType::Int32, DataType::Null] {
+        let expr = EqExpression::new(null(data_type.clone()), one());
+        assert_eq!(
+            evaluate_array(&expr, &input),
+            "BooleanScalar { value: None }"
+        );
+        let expr = AddExpression::new(one(), null(data_type.clone()));
+        assert_eq!(
+            evaluate_array(&expr, &input),
+            "PrimitiveScalar { value: None, data_type: Int32 }"
+        );
+        let expr = DivExpression::new(one(), null(data_type));
+        assert_eq!(
+            evaluate_array(&expr, &input),
+            "PrimitiveScalar { value: None, data_type: Int32 }"
+        );
+    }
+    let expr = EqExpression::new(null(DataType::Null), null(DataType::Null));
+    assert_eq!(
+        evaluate_array(&expr, &input),
+        "BooleanScalar { value: None }"
+    );
+}
+
+#[test]
+fn test_merge_matches_single_accumulator() {
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let exprs: Vec<Box<dyn PhysicalAggregateExpression>> = vec![
+        Box::new(MaxExpression::new(column())),
+        Box::new(MinExpression::new(column())),
+        Box::new(SumExpression::new(column())),
+        Box::new(CountExpression::new(column())),
+        Box::new(AvgExpression::new(column())),
+        Box::new(CountDistinctExpression::new(column())),
+        Box::new(ModeExpression::new(column())),
+    ];
+    let batches = [
+        Arc::new(Int32Array::from([Some(3), None, Some(7)])) as Arc<dyn Array>,
+        Arc::new(Int32Array::from([Some(-2), Some(3)])),
+        Arc::new(Int32Array::from([None, None])),
+        Arc::new(Int32Array::from([Some(5)])),
+    ];
+    for expr in exprs {
+        let expected = format!("{:?}", accumulate_batches(&*expr, batches.to_vec()));
+        for split in 0..=batches.len() {
+            let (left, right) = batches.split_at(split);
+            let mut merged = expr.create_accumulator(0);
+            for batch in left {
+                merged
+                    .accumulate(&[ColumnarValue::Array(batch.clone())], None)
+                    .unwrap();
+            }
+            let mut other = expr.create_accumulator(0);
+            for batch in right {
+                other
+                    .accumulate(&[ColumnarValue::Array(batch.clone())], None)
+                    .unwrap();
+            }
+            merged.merge(other).unwrap();
+            assert_eq!(format!("{:?}", final_scalar(merged)), expected, "{}", expr);
+        }
+    }
+}
+
+#[test]
+fn test_merge_rejects_other_accumulators() {
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let mut sum = SumExpression::new(column()).create_accumulator(0);
+    let count = CountExpression::new(column()).create_accumulator(0);
+    assert!(matches!(sum.merge(count), Err(Error::DowncastError)));
+}
+
+#[test]
+fn test_round_floor_ceil() {
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let input = chunk(vec![Arc::new(Float64Array::from([
+        Some(2.567),
+        Some(-1.2),
+        None,
+    ]))]);
+    let round = RoundExpression::new(column()).with_decimals(1);
+    assert_eq!(evaluate_array(&round, &input), "Float64[2.6, -1.2, None]");
+    let round = RoundExpression::new(column());
+    assert_eq!(evaluate_array(&round, &input), "Float64[3, -1, None]");
+    let floor = FloorExpression::new(column());
+    assert_eq!(evaluate_array(&floor, &input), "Float64[2, -2, None]");
+    let ceil = CeilExpression::new(column());
+    assert_eq!(evaluate_array(&ceil, &input), "Float64[3, -1, None]");
+
+    let input = chunk(vec![Arc::new(Int32Array::from([Some(7), Some(-7)]))]);
+    let round = RoundExpression::new(column()).with_decimals(-1);
+    assert_eq!(evaluate_array(&round, &input), "Float64[10, -10]");
+
+    let null = CeilExpression::new(Box::new(LiteralNullExpression::new(DataType::Float64)));
+    match null.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            assert_eq!(scalar.data_type(), &DataType::Float64);
+            assert!(!scalar.is_valid());
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_abs() {
+    let column = || Box::new(ColumnExpression { index: 0 });
+    let cases: [(Arc<dyn Array>, &str); 3] = [
+        (
+            Arc::new(Int32Array::from([Some(-3), None, Some(4)])),
+            "Int32[3, None, 4]",
+        ),
+        (
+            Arc::new(Int64Array::from([Some(-5), Some(i64::MIN)])),
+            "Int64[5, -9223372036854775808]",
+        ),
+        (
+            Arc::new(Float64Array::from([Some(-1.5), None])),
+            "Float64[1.5, None]",
+        ),
+    ];
+    for (array, expected) in cases {
+        let input = chunk(vec![array]);
+        assert_eq!(
+            evaluate_array(&AbsExpression::new(column()), &input),
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_sqrt() {
+    let input = chunk(vec![Arc::new(Int32Array::from([
+        Some(4),
+        Some(-1),
+        None,
+        Some(0),
+    ]))]);
+    let sqrt = SqrtExpression::new(Box::new(ColumnExpression { index: 0 }));
+    assert_eq!(evaluate_array(&sqrt, &input), "Float64[2, None, None, 0]");
+}
+
+#[test]
+fn test_pow() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(2), Some(3), None])),
+        Arc::new(Float64Array::from([Some(0.5), Some(2.0), Some(2.0)])),
+    ]);
+    let column = |index| Box::new(ColumnExpression { index });
+    let pow = PowExpression::new(column(0), Box::new(LiteralIntegerExpression::new(3)));
+    assert_eq!(evaluate_array(&pow, &input), "Float64[8, 27, None]");
+    let pow = PowExpression::new(Box::new(LiteralFloatExpression::new(4.0)), column(1));
+    assert_eq!(evaluate_array(&pow, &input), "Float64[2, 16, 16]");
+    let pow = PowExpression::new(
+        Box::new(LiteralIntegerExpression::new(2)),
+        Box::new(LiteralIntegerExpression::new(10)),
+    );
+    match pow.evaluate(&input).unwrap() {
+        ColumnarValue::Scalar(scalar) => {
+            let scalar = scalar.as_any().downcast_ref::<PrimitiveScalar<f64>>();
+            assert_eq!(scalar.unwrap().value(), Some(1024.0));
+        }
+        ColumnarValue::Array(_) => panic!("expected a scalar"),
+    }
+}
+
+#[test]
+fn test_bitwise() {
+    let input = chunk(vec![
+        Arc::new(Int32Array::from([Some(5), Some(6), None, Some(-8)])),
+        Arc::new(Int64Array::from([Some(1), Some(3), Some(1), Some(2)])),
+    ]);
+    let flags = || Box::new(ColumnExpression { index: 0 });
+    let int = |value| Box::new(LiteralIntegerExpression::new(value));
+
+    let and = BitAndExpression::new(flags(), int(0x4));
+    assert_eq!(evaluate_array(&and, &input), "Int32[4, 4, None, 0]");
+    let or = BitOrExpression::new(flags(), int(1));
+    assert_eq!(evaluate_array(&or, &input), "Int32[5, 7, None, -7]");
+    let xor = BitXorExpression::new(flags(), Box::new(ColumnExpression { index: 1 }));
+    assert_eq!(evaluate_array(&xor, &input), "Int64[4, 5, None, -6]");
+    let shl = ShiftLeftExpression::new(flags(), int(2));
+    assert_eq!(evaluate_array(&shl, &input), "Int32[20, 24, None, -32]");
+    let shr = ShiftRightExpression::new(flags(), int(1));
+    assert_eq!(evaluate_array(&shr, &input), "Int32[2, 3, None, -4]");
+
+    let float = BitAndExpression::new(flags(), Box::new(LiteralFloatExpression::new(1.0)));
+    assert!(matches!(
+        float.evaluate(&input),
+        Err(Error::IncompatibleTypes(_, _, _))
+    ));
+}
@@ -0,0 +1,1035 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Int32Array, Int64Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::compute::sort::{SortColumn, SortOptions};
+use arrow2::datatypes::{DataType, Field, Schema};
+use arrow2::scalar::Utf8Scalar;
+
+use crate::{
+    columnar_value::ColumnarValue,
+    data_source::{DataSource, MemoryDataSource, ParquetDataSource},
+    physical_plan::{
+        extreme_rows, finalize_accumulators, lexsort_to_indices,
+        ordering::{enforce_ordering, ensure_input_ordering, SortExpression, SortKey},
+        physical_expressions::{
+            AddExpression, ColumnExpression, CountIfExpression, EqExpression, LagFunction,
+            LeadFunction, LiteralBoolExpression, LiteralIntegerExpression, LiteralStringExpression,
+            MaxExpression, MinExpression, NeqExpression, NtileFunction,
+            PhysicalAggregateExpression, PhysicalExpression, SumExpression, WindowFunction,
+        },
+        slice_chunk, AggregateExec, CrossJoinExec, DistinctExec, HashJoinExec, LimitExec,
+        PhysicalPlan, ProjectionExec, ScanExec, SelectionExec, SortExec, UnionExec, WindowExec,
+    },
+    record_batch::RecordBatch,
+};
+
+fn parquet_scan() -> PhysicalPlan {
+    let ds = DataSource::Parquet(ParquetDataSource::new("src/tests/test.parquet").unwrap());
+    let schema = ds.schema();
+    PhysicalPlan::Scan(ScanExec::new(ds, None, schema))
+}
+
+fn collect(plan: PhysicalPlan) -> Vec<Chunk<Arc<dyn Array>>> {
+    plan.execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_limit() {
+    let scan = parquet_scan();
+    let schema = scan.schema().clone();
+    let result = collect(PhysicalPlan::Limit(LimitExec::new(vec![scan], 3, schema)));
+    assert_eq!(result.len(), 1);
+    assert!(result[0].arrays().iter().all(|array| array.len() == 3));
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[4, 5, 6]");
+}
+
+fn limit(fetch: usize, skip: usize) -> Vec<Chunk<Arc<dyn Array>>> {
+    let input = int_scan(vec![
+        [vec![Some(1), Some(2), Some(3)], vec![None; 3]],
+        [vec![Some(4), Some(5)], vec![None; 2]],
+        [vec![Some(6)], vec![None]],
+    ]);
+    let schema = input.schema().clone();
+    let limit = LimitExec::new(vec![input], fetch, schema).with_skip(skip);
+    collect(PhysicalPlan::Limit(limit))
+}
+
+#[test]
+fn test_limit_skip() {
+    let debug = |batches: Vec<Chunk<Arc<dyn Array>>>| {
+        batches
+            .iter()
+            .map(|batch| format!("{:?}", batch[0]))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(debug(limit(2, 0)), ["Int32[1, 2]"]);
+    assert_eq!(debug(limit(4, 0)), ["Int32[1, 2, 3]", "Int32[4]"]);
+    assert_eq!(debug(limit(2, 1)), ["Int32[2, 3]"]);
+    assert_eq!(debug(limit(2, 3)), ["Int32[4, 5]"]);
+    assert_eq!(debug(limit(10, 4)), ["Int32[5]", "Int32[6]"]);
+    assert!(limit(10, 6).is_empty());
+}
+
+#[test]
+fn test_slice_chunk_is_zero_copy() {
+    let array = Int32Array::from_slice([1, 2, 3, 4, 5]);
+    let chunk = Chunk::new(vec![Arc::new(array.clone()) as Arc<dyn Array>]);
+    let sliced = slice_chunk(&chunk, 1, 3);
+    let sliced = sliced[0].as_any().downcast_ref::<Int32Array>().unwrap();
+    assert_eq!(sliced.len(), 3);
+    assert_eq!(sliced.values().as_ptr(), array.values()[1..].as_ptr());
+}
+
+#[test]
+fn test_scan_empty_as_null() {
+    let ds = ParquetDataSource::new("src/tests/strings.parquet").unwrap();
+    let ds = DataSource::Parquet(ds);
+    let schema = ds.schema();
+    let result = collect(PhysicalPlan::Scan(ScanExec::new(ds, None, schema)));
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "Utf8Array[Alice, , bob, , None, straße]"
+    );
+
+    let ds = ParquetDataSource::new("src/tests/strings.parquet")
+        .unwrap()
+        .with_empty_as_null(vec!["name".to_string()]);
+    let ds = DataSource::Parquet(ds);
+    let schema = ds.schema();
+    let result = collect(PhysicalPlan::Scan(ScanExec::new(ds, None, schema)));
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "Utf8Array[Alice, None, bob, None, None, straße]"
+    );
+}
+
+#[test]
+fn test_stable_lexsort() {
+    let keys = Int32Array::from_slice([2, 1, 2, 1, 2, 1, 2, 1]);
+    let columns = [SortColumn {
+        values: &keys,
+        options: Some(SortOptions {
+            descending: true,
+            nulls_first: false,
+        }),
+    }];
+    let indices = lexsort_to_indices(&columns, true).unwrap();
+    assert_eq!(indices.values().as_slice(), &[0, 2, 4, 6, 1, 3, 5, 7]);
+}
+
+#[test]
+fn test_finalize_accumulators() {
+    let max = MaxExpression::new(Box::new(ColumnExpression { index: 0 }));
+    let groups: [&[i32]; 3] = [&[1, 3, 2], &[5], &[9, 4]];
+    let accumulators = groups
+        .iter()
+        .map(|values| {
+            let mut accumulator = max.create_accumulator(0);
+            let array = Arc::new(Int32Array::from_slice(values)) as Arc<dyn Array>;
+            accumulator
+                .accumulate(&[ColumnarValue::Array(array)], None)
+                .unwrap();
+            accumulator
+        })
+        .collect();
+    let result = finalize_accumulators(accumulators).unwrap();
+    assert_eq!(format!("{:?}", result), "Int32[3, 5, 9]");
+}
+
+#[test]
+fn test_ntile() {
+    let ntile = NtileFunction::new(4).unwrap();
+    let partition = Chunk::new(vec![
+        Arc::new(Int32Array::from_vec((0..10).collect())) as Arc<dyn Array>
+    ]);
+    assert_eq!(
+        format!("{:?}", ntile.evaluate_partition(&partition).unwrap()),
+        "Int32[1, 1, 1, 2, 2, 2, 3, 3, 4, 4]"
+    );
+    let partition = slice_chunk(&partition, 0, 2);
+    assert_eq!(
+        format!("{:?}", ntile.evaluate_partition(&partition).unwrap()),
+        "Int32[1, 2]"
+    );
+    assert!(NtileFunction::new(0).is_err());
+}
+
+/// The input's fields followed by one Int32 field per window function, named
+/// after the function.
+fn window_schema(input: &PhysicalPlan, functions: &[Box<dyn WindowFunction>]) -> Schema {
+    let mut fields = input.schema().fields.clone();
+    fields.extend(
+        functions
+            .iter()
+            .map(|function| Field::new(function.to_string(), DataType::Int32, true)),
+    );
+    Schema::from(fields)
+}
+
+#[test]
+fn test_window_ntile() {
+    let scan = parquet_scan();
+    let functions: Vec<Box<dyn WindowFunction>> = vec![Box::new(NtileFunction::new(3).unwrap())];
+    let schema = window_schema(&scan, &functions);
+    // bool_col is true for even ids, giving two partitions of four rows.
+    let window = WindowExec::new(
+        vec![scan],
+        vec![Box::new(ColumnExpression { index: 1 })],
+        vec![(
+            Box::new(ColumnExpression { index: 0 }),
+            SortOptions {
+                descending: true,
+                nulls_first: false,
+            },
+        )],
+        functions,
+        schema,
+    );
+    let window = PhysicalPlan::Window(window);
+    let width = window.schema().fields.len();
+    let result = collect(window);
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].arrays().len(), width);
+    assert_eq!(
+        format!("{:?}", result[0][0]),
+        "Int32[7, 5, 3, 1, 6, 4, 2, 0]"
+    );
+    let ntile = result[0].arrays().last().unwrap();
+    assert_eq!(format!("{:?}", ntile), "Int32[1, 1, 2, 3, 1, 1, 2, 3]");
+}
+
+#[test]
+fn test_lag_lead() {
+    let partition = Chunk::new(vec![
+        Arc::new(Int32Array::from([Some(1), None, Some(3), Some(4)])) as Arc<dyn Array>,
+        Arc::new(Utf8Array::<i32>::from_slice(["a", "b", "c", "d"])) as Arc<dyn Array>,
+    ]);
+    let evaluate = |function: &dyn WindowFunction| {
+        format!("{:?}", function.evaluate_partition(&partition).unwrap())
+    };
+    let lag = LagFunction::new(Box::new(ColumnExpression { index: 0 }), 1, None);
+    assert_eq!(evaluate(&lag), "Int32[None, 1, None, 3]");
+    let lead = LeadFunction::new(
+        Box::new(ColumnExpression { index: 1 }),
+        2,
+        Some(Box::new(Utf8Scalar::<i32>::from(Some("z")))),
+    );
+    assert_eq!(evaluate(&lead), "Utf8Array[c, d, z, z]");
+    let lag = LagFunction::new(Box::new(ColumnExpression { index: 0 }), 9, None);
+    assert_eq!(evaluate(&lag), "Int32[None, None, None, None]");
+}
+
+#[test]
+fn test_window_lag() {
+    let scan = parquet_scan();
+    let functions: Vec<Box<dyn WindowFunction>> = vec![Box::new(LagFunction::new(
+        Box::new(ColumnExpression { index: 0 }),
+        1,
+        None,
+    ))];
+    let schema = window_schema(&scan, &functions);
+    let window = WindowExec::new(
+        vec![scan],
+        vec![Box::new(ColumnExpression { index: 1 })],
+        vec![(
+            Box::new(ColumnExpression { index: 0 }),
+            SortOptions::default(),
+        )],
+        functions,
+        schema,
+    );
+    let window = PhysicalPlan::Window(window);
+    let width = window.schema().fields.len();
+    let result = collect(window);
+    assert_eq!(result[0].arrays().len(), width);
+    assert_eq!(
+        format!("{:?}", result[0][0]),
+        "Int32[1, 3, 5, 7, 0, 2, 4, 6]"
+    );
+    let lag = result[0].arrays().last().unwrap();
+    assert_eq!(format!("{:?}", lag), "Int32[None, 1, 3, 5, None, 0, 2, 4]");
+}
+
+fn sorted_scan(keys: Vec<SortExpression>) -> PhysicalPlan {
+    let scan = parquet_scan();
+    let schema = scan.schema().clone();
+    PhysicalPlan::Sort(SortExec::new(vec![scan], keys, schema))
+}
+
+#[test]
+fn test_sort() {
+    let descending = SortOptions {
+        descending: true,
+        nulls_first: false,
+    };
+    let result = collect(sorted_scan(vec![SortExpression::column(0, descending)]));
+    assert_eq!(
+        format!("{:?}", result[0][0]),
+        "Int32[7, 6, 5, 4, 3, 2, 1, 0]"
+    );
+}
+
+#[test]
+fn test_sort_multiple_keys() {
+    let input = || {
+        int_scan(vec![
+            [vec![Some(1), Some(2), None], vec![Some(1), None, Some(5)]],
+            [
+                vec![Some(1), Some(2), Some(1)],
+                vec![Some(3), Some(4), None],
+            ],
+        ])
+    };
+    let sort_with = |keys: Vec<SortExpression>, stable| {
+        let input = input();
+        let schema = input.schema().clone();
+        collect(PhysicalPlan::Sort(
+            SortExec::new(vec![input], keys, schema).with_stable(stable),
+        ))
+    };
+    let sort = |keys: Vec<SortExpression>| sort_with(keys, true);
+    let ascending = |nulls_first| SortOptions {
+        descending: false,
+        nulls_first,
+    };
+    let descending = |nulls_first| SortOptions {
+        descending: true,
+        nulls_first,
+    };
+
+    let result = sort(vec![
+        SortExpression::column(0, ascending(false)),
+        SortExpression::column(1, descending(true)),
+    ]);
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, 1, 1, 2, 2, None]");
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "Int32[None, 3, 1, None, 4, 5]"
+    );
+
+    let result = sort(vec![
+        SortExpression::column(0, descending(true)),
+        SortExpression::column(1, ascending(false)),
+    ]);
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[None, 2, 2, 1, 1, 1]");
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "Int32[5, 4, None, 1, 3, None]"
+    );
+
+    // Keys that leave no ties sort the same either way.
+    let keys = || {
+        vec![
+            SortExpression::column(0, descending(true)),
+            SortExpression::column(1, ascending(false)),
+        ]
+    };
+    assert_eq!(sort_with(keys(), false), sort_with(keys(), true));
+}
+
+#[test]
+fn test_sort_by_expression() {
+    let input = int_scan(vec![
+        [vec![Some(1), Some(2), None], vec![Some(1), None, Some(5)]],
+        [
+            vec![Some(1), Some(2), Some(1)],
+            vec![Some(3), Some(4), None],
+        ],
+    ]);
+    let schema = input.schema().clone();
+    let sum = AddExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 1 }),
+    );
+    // Ascending with nulls first; rows with a null sum keep their order.
+    let key = SortExpression::new(Box::new(sum), SortOptions::default());
+    let plan = PhysicalPlan::Sort(SortExec::new(vec![input], vec![key], schema));
+    // A computed key says nothing about the order of the output columns.
+    assert!(plan.output_ordering().is_empty());
+
+    let result = collect(plan);
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[2, None, 1, 1, 1, 2]");
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "Int32[None, 5, None, 1, 3, 4]"
+    );
+}
+
+#[test]
+fn test_ensure_input_ordering() {
+    let by_id = vec![SortKey::new(0, SortOptions::default())];
+    let is_sort = |plan: &PhysicalPlan| matches!(plan, PhysicalPlan::Sort(_));
+
+    // Already sorted on the key below a limit, which keeps the order.
+    let sorted = sorted_scan(vec![SortExpression::from(by_id[0])]);
+    let schema = sorted.schema().clone();
+    let limit = PhysicalPlan::Limit(LimitExec::new(vec![sorted], 5, schema));
+    assert_eq!(limit.output_ordering(), &by_id[..]);
+    let plan = ensure_input_ordering(limit, &by_id);
+    assert!(!is_sort(&plan));
+    assert_eq!(format!("{:?}", collect(plan)[0][0]), "Int32[0, 1, 2, 3, 4]");
+
+    // Sorted on a different direction, or not at all.
+    let descending = SortOptions {
+        descending: true,
+        nulls_first: false,
+    };
+    let plan = ensure_input_ordering(
+        sorted_scan(vec![SortExpression::column(0, descending)]),
+        &by_id,
+    );
+    assert!(is_sort(&plan));
+    let plan = ensure_input_ordering(parquet_scan(), &by_id);
+    assert!(is_sort(&plan));
+    assert_eq!(
+        format!("{:?}", collect(plan)[0][0]),
+        "Int32[0, 1, 2, 3, 4, 5, 6, 7]"
+    );
+
+    // A plain sort requires no ordering of its input, so nothing is inserted.
+    let plan = enforce_ordering(sorted_scan(vec![SortExpression::from(by_id[0])]));
+    assert!(matches!(plan.children().unwrap()[0], PhysicalPlan::Scan(_)));
+}
+
+#[test]
+fn test_enforce_window_ordering() {
+    let descending = SortOptions {
+        descending: true,
+        nulls_first: false,
+    };
+    let window = |input: PhysicalPlan, order_by: Box<dyn PhysicalExpression>| {
+        let functions: Vec<Box<dyn WindowFunction>> =
+            vec![Box::new(NtileFunction::new(3).unwrap())];
+        let schema = window_schema(&input, &functions);
+        PhysicalPlan::Window(WindowExec::new(
+            vec![input],
+            vec![Box::new(ColumnExpression { index: 1 })],
+            vec![(order_by, descending)],
+            functions,
+            schema,
+        ))
+    };
+    let by_id = || Box::new(ColumnExpression { index: 0 });
+    let child = |plan: &PhysicalPlan| matches!(plan.children().unwrap()[0], PhysicalPlan::Sort(_));
+
+    // The window wants its input by bool_col and then id descending.
+    let keys = vec![
+        SortKey::new(1, SortOptions::default()),
+        SortKey::new(0, descending),
+    ];
+    let plan = window(parquet_scan(), by_id());
+    assert_eq!(plan.required_input_ordering(), vec![Some(keys.clone())]);
+    let plan = enforce_ordering(plan);
+    assert!(child(&plan));
+    let result = collect(plan);
+    assert_eq!(
+        format!("{:?}", result[0][0]),
+        "Int32[7, 5, 3, 1, 6, 4, 2, 0]"
+    );
+    let ntile = result[0].arrays().last().unwrap();
+    assert_eq!(format!("{:?}", ntile), "Int32[1, 1, 2, 3, 1, 1, 2, 3]");
+
+    // Input sorted on those keys already is left as it is.
+    let sorted = sorted_scan(keys.into_iter().map(SortExpression::from).collect());
+    let plan = enforce_ordering(window(sorted, by_id()));
+    assert!(child(&plan));
+    assert!(matches!(
+        plan.children().unwrap()[0].children().unwrap()[0],
+        PhysicalPlan::Scan(_)
+    ));
+
+    // A computed key leaves the window to sort its input itself.
+    let computed = Box::new(AddExpression::new(
+        by_id(),
+        Box::new(LiteralIntegerExpression::new(0)),
+    ));
+    let plan = window(parquet_scan(), computed);
+    assert_eq!(plan.required_input_ordering(), vec![None]);
+    assert!(!child(&enforce_ordering(plan)));
+}
+
+#[test]
+fn test_group_by_many_groups() {
+    // Every key appears once in each batch, so each group takes rows from
+    // both of them.
+    let batch = |keys: std::ops::Range<i32>| {
+        [
+            keys.clone().map(Some).collect(),
+            keys.map(|key| Some(key % 7)).collect(),
+        ]
+    };
+    let input = int_scan(vec![batch(0..5000), batch(0..5000)]);
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("sum", DataType::Int64, true),
+    ]);
+    let aggregate = AggregateExec::new(
+        vec![input],
+        vec![Box::new(ColumnExpression { index: 0 })],
+        vec![Box::new(SumExpression::new(Box::new(ColumnExpression {
+            index: 1,
+        })))],
+        schema,
+    );
+    let result = collect(PhysicalPlan::Aggregate(aggregate));
+    let keys = result[0][0].as_any().downcast_ref::<Int32Array>().unwrap();
+    let sums = result[0][1].as_any().downcast_ref::<Int64Array>().unwrap();
+    assert_eq!(keys.values().as_slice(), (0..5000).collect::<Vec<_>>());
+    assert!(keys
+        .values()
+        .iter()
+        .zip(sums.values().iter())
+        .all(|(key, sum)| *sum == 2 * (*key % 7) as i64));
+}
+
+#[test]
+fn test_group_by_empty_input() {
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("sum", DataType::Int64, true),
+    ]);
+    let aggregate = AggregateExec::new(
+        vec![int_scan(vec![])],
+        vec![Box::new(ColumnExpression { index: 0 })],
+        vec![Box::new(SumExpression::new(Box::new(ColumnExpression {
+            index: 1,
+        })))],
+        schema,
+    );
+    let result = collect(PhysicalPlan::Aggregate(aggregate));
+    assert_eq!(result.len(), 1);
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[]");
+    assert_eq!(format!("{:?}", result[0][1]), "Int64[]");
+}
+
+fn strings_scan() -> PhysicalPlan {
+    let ds = DataSource::Parquet(ParquetDataSource::new("src/tests/strings.parquet").unwrap());
+    let schema = ds.schema();
+    PhysicalPlan::Scan(ScanExec::new(ds, None, schema))
+}
+
+fn int_scan(batches: Vec<[Vec<Option<i32>>; 2]>) -> PhysicalPlan {
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("b", DataType::Int32, true),
+    ]);
+    let batches = batches
+        .into_iter()
+        .map(|[a, b]| {
+            let columns: Vec<Arc<dyn Array>> =
+                vec![Arc::new(Int32Array::from(a)), Arc::new(Int32Array::from(b))];
+            RecordBatch::try_new(schema.clone(), Chunk::new(columns)).unwrap()
+        })
+        .collect();
+    let ds = DataSource::Memory(MemoryDataSource::try_new(schema.clone(), batches).unwrap());
+    PhysicalPlan::Scan(ScanExec::new(ds, None, schema))
+}
+
+fn memory_scan() -> PhysicalPlan {
+    int_scan(vec![[
+        vec![Some(1), Some(2), None],
+        vec![Some(2), Some(2), Some(3)],
+    ]])
+}
+
+#[test]
+fn test_projection() {
+    let exprs: Vec<Box<dyn PhysicalExpression>> = vec![
+        Box::new(AddExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(LiteralIntegerExpression::new(1)),
+        )),
+        Box::new(EqExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(ColumnExpression { index: 1 }),
+        )),
+        Box::new(LiteralIntegerExpression::new(7)),
+    ];
+    let projection =
+        PhysicalPlan::Projection(ProjectionExec::try_new(vec![memory_scan()], exprs).unwrap());
+    assert_eq!(
+        projection.schema().fields,
+        vec![
+            Field::new(
+                "#0 + #PrimitiveScalar { value: Some(1), data_type: Int32 }",
+                DataType::Int32,
+                true
+            ),
+            Field::new("#0 == #1", DataType::Boolean, true),
+            Field::new(
+                "#PrimitiveScalar { value: Some(7), data_type: Int32 }",
+                DataType::Int32,
+                true
+            ),
+        ]
+    );
+    let result = collect(projection);
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[2, 3, None]");
+    assert_eq!(
+        format!("{:?}", result[0][1]),
+        "BooleanArray[false, true, None]"
+    );
+    // Scalar results are broadcast to the batch length.
+    assert_eq!(format!("{:?}", result[0][2]), "Int32[7, 7, 7]");
+}
+
+#[test]
+fn test_hash_join() {
+    let left = int_scan(vec![[
+        vec![Some(1), Some(2), None, Some(3), Some(2)],
+        vec![Some(10), Some(20), Some(30), Some(40), Some(50)],
+    ]]);
+    let right = int_scan(vec![
+        [vec![Some(2), Some(4)], vec![Some(-1), Some(-2)]],
+        [vec![None, Some(1)], vec![Some(-3), Some(-4)]],
+    ]);
+    let mut fields = left.schema().fields.clone();
+    fields.extend(right.schema().fields.clone());
+    let join = HashJoinExec::new(
+        left,
+        right,
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(ColumnExpression { index: 0 }),
+        Schema::from(fields),
+    );
+    let result = collect(PhysicalPlan::HashJoin(join))
+        .iter()
+        .map(|batch| {
+            batch
+                .arrays()
+                .iter()
+                .map(|array| format!("{:?}", array))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // 3 and 4 have no match and the null keys match nothing, not even each
+    // other.
+    assert_eq!(
+        result,
+        [
+            [
+                "Int32[2, 2]",
+                "Int32[20, 50]",
+                "Int32[2, 2]",
+                "Int32[-1, -1]"
+            ],
+            ["Int32[1]", "Int32[10]", "Int32[1]", "Int32[-4]"],
+        ]
+    );
+}
+
+#[test]
+fn test_cross_join() {
+    let left = int_scan(vec![[vec![Some(1), Some(2)], vec![Some(10), Some(20)]]]);
+    let right = int_scan(vec![
+        [vec![Some(3), Some(4)], vec![None, None]],
+        [vec![Some(5)], vec![None]],
+    ]);
+    let mut fields = left.schema().fields.clone();
+    fields.extend(right.schema().fields.clone());
+    let join = CrossJoinExec::new(left, right, Schema::from(fields));
+    let result = collect(PhysicalPlan::CrossJoin(join));
+    assert_eq!(result.iter().map(|batch| batch.len()).sum::<usize>(), 6);
+    // One batch per pair of a left and a right batch.
+    assert!(result.iter().all(|batch| batch.arrays().len() == 4));
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, 1, 2, 2]");
+    assert_eq!(format!("{:?}", result[0][1]), "Int32[10, 10, 20, 20]");
+    assert_eq!(format!("{:?}", result[0][2]), "Int32[3, 4, 3, 4]");
+    assert_eq!(format!("{:?}", result[1][0]), "Int32[1, 2]");
+    assert_eq!(format!("{:?}", result[1][2]), "Int32[5, 5]");
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_projection_and_selection() {
+    let input = || {
+        int_scan(
+            (0..100)
+                .map(|i| {
+                    let a = (0..10)
+                        .map(|j| (j != i % 10).then_some(i * 10 + j))
+                        .collect();
+                    [a, vec![Some(i); 10]]
+                })
+                .collect(),
+        )
+    };
+    let run = |parallel: bool| {
+        let exprs: Vec<Box<dyn PhysicalExpression>> = vec![Box::new(AddExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(ColumnExpression { index: 1 }),
+        ))];
+        let schema = Schema::from(vec![Field::new("a + b", DataType::Int32, true)]);
+        let projection =
+            ProjectionExec::new(vec![input()], exprs, schema.clone()).with_parallel(parallel);
+        let predicate = NeqExpression::new(
+            Box::new(ColumnExpression { index: 0 }),
+            Box::new(LiteralIntegerExpression::new(42)),
+        );
+        let selection = SelectionExec::new(
+            vec![PhysicalPlan::Projection(projection)],
+            Box::new(predicate),
+            schema,
+        )
+        .with_parallel(parallel);
+        collect(PhysicalPlan::Selection(selection))
+            .iter()
+            .map(|batch| format!("{:?}", batch[0]))
+            .collect::<Vec<_>>()
+    };
+    let sequential = run(false);
+    assert_eq!(sequential.len(), 100);
+    assert_eq!(run(true), sequential);
+}
+
+#[test]
+fn test_distinct() {
+    let input = int_scan(vec![
+        [
+            vec![Some(1), Some(1), None, Some(2)],
+            vec![Some(1), Some(1), None, Some(1)],
+        ],
+        [vec![None, Some(2), Some(3)], vec![None, Some(1), None]],
+        [vec![Some(1)], vec![Some(1)]],
+    ]);
+    let schema = input.schema().clone();
+    let distinct = DistinctExec::new(vec![input], schema);
+    let result = collect(PhysicalPlan::Distinct(distinct))
+        .iter()
+        .map(|batch| format!("{:?} {:?}", batch[0], batch[1]))
+        .collect::<Vec<_>>();
+    // Rows of nulls are duplicates of each other, and a batch can end up empty.
+    assert_eq!(
+        result,
+        [
+            "Int32[1, None, 2] Int32[1, None, 1]",
+            "Int32[3] Int32[None]",
+            "Int32[] Int32[]",
+        ]
+    );
+}
+
+fn union_inputs() -> (PhysicalPlan, PhysicalPlan) {
+    let left = int_scan(vec![[vec![Some(1), Some(2)], vec![Some(10), None]]]);
+    let right = int_scan(vec![[vec![Some(2), Some(3)], vec![None, Some(30)]]]);
+    (left, right)
+}
+
+fn first_columns(plan: PhysicalPlan) -> Vec<String> {
+    collect(plan)
+        .iter()
+        .map(|batch| format!("{:?}", batch[0]))
+        .collect()
+}
+
+#[test]
+fn test_union_all() {
+    let (left, right) = union_inputs();
+    let union = UnionExec::try_new(left, right).unwrap();
+    assert_eq!(
+        first_columns(PhysicalPlan::Union(union)),
+        ["Int32[1, 2]", "Int32[2, 3]"]
+    );
+}
+
+#[test]
+fn test_union_distinct() {
+    let (left, right) = union_inputs();
+    let union = UnionExec::try_new(left, right).unwrap().distinct();
+    assert_eq!(first_columns(union), ["Int32[1, 2]", "Int32[3]"]);
+}
+
+#[test]
+fn test_union_schema_mismatch() {
+    let (left, _) = union_inputs();
+    let right = strings_scan();
+    assert!(UnionExec::try_new(left, right).is_err());
+}
+
+/// The `rows_in` recorded on the span of each operator, by operator name.
+#[cfg(feature = "tracing")]
+fn traced_rows_in(plan: PhysicalPlan) -> std::collections::HashMap<String, u64> {
+    use std::sync::Mutex;
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{
+        layer::{Context, SubscriberExt},
+        registry::LookupSpan,
+        Layer,
+    };
+
+    #[derive(Default)]
+    struct Fields {
+        operator: Option<String>,
+        rows_in: Option<u64>,
+    }
+
+    impl Visit for Fields {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "rows_in" {
+                self.rows_in = Some(value);
+            }
+        }
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "operator" {
+                self.operator = Some(value.to_string());
+            }
+        }
+        fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    #[derive(Clone, Default)]
+    struct RowsIn(Arc<Mutex<Vec<(Id, Fields)>>>);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for RowsIn {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _: Context<'_, S>) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            self.0.lock().unwrap().push((id.clone(), fields));
+        }
+        fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            let mut spans = self.0.lock().unwrap();
+            if let Some((_, fields)) = spans.iter_mut().find(|(span, _)| span == id) {
+                values.record(fields);
+            }
+        }
+    }
+
+    let layer = RowsIn::default();
+    let subscriber = tracing_subscriber::registry().with(layer.clone());
+    tracing::subscriber::with_default(subscriber, || collect(plan));
+    let spans = layer.0.lock().unwrap();
+    spans
+        .iter()
+        .filter_map(|(_, fields)| Some((fields.operator.clone()?, fields.rows_in?)))
+        .collect()
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_trace_rows_in_over_two_inputs() {
+    let (left, _) = union_inputs();
+    let union = UnionExec::try_new(left, memory_scan()).unwrap();
+    let rows_in = traced_rows_in(PhysicalPlan::Union(union));
+    // Two rows from the left input and three from the right one.
+    assert_eq!(rows_in.get("Union"), Some(&5));
+    assert_eq!(rows_in.get("Scan"), None);
+}
+
+#[test]
+fn test_selection_drops_null_predicates() {
+    let scan = strings_scan();
+    let schema = scan.schema().clone();
+    // name is null for id 5.
+    let predicate = NeqExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(LiteralStringExpression::new("bob".to_string())),
+    );
+    let selection = SelectionExec::new(vec![scan], Box::new(predicate), schema);
+    let result = collect(PhysicalPlan::Selection(selection));
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, 2, 4, 6]");
+}
+
+#[test]
+fn test_selection_matches_row_loop() {
+    let a = (0..1000)
+        .map(|i| (i % 7 != 0).then_some(i))
+        .collect::<Vec<_>>();
+    let b = (0..1000).map(|i| Some(i % 3)).collect::<Vec<_>>();
+    let input = int_scan(vec![[a.clone(), b.clone()]]);
+    let schema = input.schema().clone();
+    let predicate = EqExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(LiteralIntegerExpression::new(1)),
+    );
+    let selection = SelectionExec::new(vec![input], Box::new(predicate), schema);
+    let result = collect(PhysicalPlan::Selection(selection));
+    let expected = a
+        .iter()
+        .zip(&b)
+        .filter(|(_, b)| **b == Some(1))
+        .map(|(a, _)| *a)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        format!("{:?}", result[0][0]),
+        format!("{:?}", Int32Array::from(expected))
+    );
+}
+
+#[test]
+fn test_selection_keep_nulls() {
+    let scan = strings_scan();
+    let schema = scan.schema().clone();
+    let predicate = NeqExpression::new(
+        Box::new(ColumnExpression { index: 1 }),
+        Box::new(LiteralStringExpression::new("bob".to_string())),
+    );
+    let selection =
+        SelectionExec::new(vec![scan], Box::new(predicate), schema).with_keep_nulls(true);
+    let result = collect(PhysicalPlan::Selection(selection));
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, 2, 4, 5, 6]");
+}
+
+#[test]
+fn test_selection_matches_nothing() {
+    let scan = memory_scan();
+    let schema = scan.schema().clone();
+    let predicate = EqExpression::new(
+        Box::new(ColumnExpression { index: 0 }),
+        Box::new(LiteralIntegerExpression::new(100)),
+    );
+    let selection = SelectionExec::new(vec![scan], Box::new(predicate), schema.clone());
+    let selection = PhysicalPlan::Selection(selection);
+    assert_eq!(selection.schema(), &schema);
+    let result = collect(selection);
+    assert_eq!(result[0].len(), 0);
+    assert_eq!(result[0].arrays().len(), 2);
+}
+
+fn int_equality(left: i32, right: i32) -> Box<EqExpression> {
+    Box::new(EqExpression::new(
+        Box::new(LiteralIntegerExpression::new(left)),
+        Box::new(LiteralIntegerExpression::new(right)),
+    ))
+}
+
+#[test]
+fn test_selection_constant_false() {
+    // Executing a limit without an input fails, so an empty result shows the
+    // input was never executed.
+    let schema = strings_scan().schema().clone();
+    let input = PhysicalPlan::Limit(LimitExec::new(vec![], 1, schema.clone()));
+    let selection = SelectionExec::new(vec![input], int_equality(1, 2), schema);
+    assert!(collect(PhysicalPlan::Selection(selection)).is_empty());
+}
+
+#[test]
+fn test_selection_constant_true() {
+    let scan = strings_scan();
+    let schema = scan.schema().clone();
+    let selection = SelectionExec::new(vec![scan], int_equality(1, 1), schema);
+    let result = collect(PhysicalPlan::Selection(selection));
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, 2, 3, 4, 5, 6]");
+}
+
+fn global_aggregate(input: PhysicalPlan) -> Vec<Chunk<Arc<dyn Array>>> {
+    let schema = Schema::from(vec![
+        Field::new("count_if", DataType::Int64, false),
+        Field::new("max", DataType::Int32, true),
+    ]);
+    let aggregate = AggregateExec::new(
+        vec![input],
+        vec![],
+        vec![
+            Box::new(CountIfExpression::new(Box::new(ColumnExpression {
+                index: 1,
+            }))),
+            Box::new(MaxExpression::new(Box::new(ColumnExpression { index: 0 }))),
+        ],
+        schema,
+    );
+    collect(PhysicalPlan::Aggregate(aggregate))
+}
+
+#[test]
+fn test_global_aggregate() {
+    let result = global_aggregate(parquet_scan());
+    assert_eq!(result.len(), 1);
+    assert_eq!(format!("{:?}", result[0][0]), "Int64[4]");
+    assert_eq!(format!("{:?}", result[0][1]), "Int32[7]");
+
+    let scan = parquet_scan();
+    let schema = scan.schema().clone();
+    let empty = SelectionExec::new(
+        vec![scan],
+        Box::new(LiteralBoolExpression::new(false)),
+        schema,
+    );
+    let result = global_aggregate(PhysicalPlan::Selection(empty));
+    assert_eq!(result.len(), 1);
+    assert_eq!(format!("{:?}", result[0][0]), "Int64[0]");
+    assert_eq!(format!("{:?}", result[0][1]), "Int32[None]");
+}
+
+#[test]
+fn test_group_by_over_batches() {
+    let input = int_scan(vec![
+        [
+            vec![Some(1), None, Some(0), Some(1)],
+            vec![Some(1), Some(2), Some(3), Some(4)],
+        ],
+        [
+            vec![None, Some(0), Some(2)],
+            vec![Some(10), Some(20), Some(30)],
+        ],
+    ]);
+    let schema = Schema::from(vec![
+        Field::new("a", DataType::Int32, true),
+        Field::new("sum", DataType::Int64, true),
+    ]);
+    let aggregate = AggregateExec::new(
+        vec![input],
+        vec![Box::new(ColumnExpression { index: 0 })],
+        vec![Box::new(SumExpression::new(Box::new(ColumnExpression {
+            index: 1,
+        })))],
+        schema,
+    );
+    let result = collect(PhysicalPlan::Aggregate(aggregate));
+    // Null keys form a group of their own, apart from 0.
+    assert_eq!(format!("{:?}", result[0][0]), "Int32[1, None, 0, 2]");
+    assert_eq!(format!("{:?}", result[0][1]), "Int64[5, 12, 23, 30]");
+}
+
+#[test]
+fn test_extreme_rows() {
+    let column = Int32Array::from(&[None, Some(1), Some(2), Some(3)]);
+    let chunk = Chunk::new(vec![Arc::new(column) as Arc<dyn Array>]);
+    let nulls_first = SortOptions {
+        descending: false,
+        nulls_first: true,
+    };
+    let rows = extreme_rows(&chunk, &SortKey::new(0, nulls_first)).unwrap();
+    assert_eq!(format!("{:?}", rows[0]), "Int32[1, 3]");
+}
+
+#[test]
+fn test_min_max_on_sorted_input() {
+    let min_max = |input| {
+        let schema = Schema::from(vec![
+            Field::new("min", DataType::Int32, true),
+            Field::new("max", DataType::Int32, true),
+        ]);
+        AggregateExec::new(
+            vec![input],
+            vec![],
+            vec![
+                Box::new(MinExpression::new(Box::new(ColumnExpression { index: 0 }))),
+                Box::new(MaxExpression::new(Box::new(ColumnExpression { index: 0 }))),
+            ],
+            schema,
+        )
+    };
+    // Unsorted input is aggregated in full.
+    assert_eq!(min_max(parquet_scan()).extremum_key(), None);
+
+    for descending in [false, true] {
+        let options = SortOptions {
+            descending,
+            nulls_first: false,
+        };
+        let aggregate = min_max(sorted_scan(vec![SortExpression::column(0, options)]));
+        // Only the ends of each sorted batch are read.
+        assert_eq!(aggregate.extremum_key(), Some(SortKey::new(0, options)));
+        let result = collect(PhysicalPlan::Aggregate(aggregate));
+        assert_eq!(format!("{:?}", result[0][0]), "Int32[0]");
+        assert_eq!(format!("{:?}", result[0][1]), "Int32[7]");
+    }
+}
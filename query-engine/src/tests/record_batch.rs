@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use arrow2::array::{Array, Int32Array, Utf8Array};
+use arrow2::chunk::Chunk;
+use arrow2::datatypes::{DataType, Field, Schema};
+
+use crate::{
+    columnar_value::ColumnarValue,
+    error::Error,
+    physical_plan::physical_expressions::{ColumnExpression, PhysicalExpression},
+    record_batch::{concat, pretty_format, RecordBatch},
+    test_utils::assert_batches_eq,
+};
+
+fn batch_from(ids: [Option<i32>; 3], names: [Option<&str>; 3]) -> RecordBatch {
+    let schema = Schema::from(vec![
+        Field::new("id", DataType::Int32, true),
+        Field::new("name", DataType::Utf8, true),
+    ]);
+    let columns = Chunk::new(vec![
+        Arc::new(Int32Array::from(ids)) as Arc<dyn Array>,
+        Arc::new(Utf8Array::<i32>::from(names)) as Arc<dyn Array>,
+    ]);
+    RecordBatch::try_new(schema, columns).unwrap()
+}
+
+fn batch() -> RecordBatch {
+    batch_from([Some(1), None, Some(3)], [Some("a"), Some("b"), None])
+}
+
+#[test]
+fn test_rows() {
+    let batch = batch();
+    let rows = batch
+        .rows()
+        .map(|row| Ok((row.get_i32(0)?, row.get_str(1)?)))
+        .collect::<Result<Vec<_>, Error>>()
+        .unwrap();
+    assert_eq!(
+        rows,
+        vec![(Some(1), Some("a")), (None, Some("b")), (Some(3), None)]
+    );
+
+    let row = batch.rows().next().unwrap();
+    assert!(matches!(row.get_f64(0), Err(Error::DowncastError)));
+    assert!(matches!(
+        row.get_i32(2),
+        Err(Error::ExceedingBoundsError(2))
+    ));
+}
+
+#[test]
+fn test_assert_batches_eq() {
+    assert_batches_eq(&[batch(), batch()], &[batch(), batch()]);
+}
+
+#[test]
+#[should_panic(expected = "batch 0, column `id`, row 1: expected 2, actual NULL")]
+fn test_assert_batches_eq_reports_value() {
+    let expected = batch_from([Some(1), Some(2), Some(3)], [Some("a"), Some("b"), None]);
+    assert_batches_eq(&[batch()], &[expected]);
+}
+
+#[test]
+fn test_evaluate_batch() {
+    let expr = ColumnExpression { index: 1 };
+    match expr.evaluate_batch(&batch()).unwrap() {
+        ColumnarValue::Array(array) => assert_eq!(format!("{:?}", array), "Utf8Array[a, b, None]"),
+        ColumnarValue::Scalar(_) => panic!("expected an array"),
+    }
+}
+
+#[test]
+fn test_pretty_format() {
+    let batch = batch_from(
+        [Some(1), None, Some(300)],
+        [Some("a"), Some("bcdefgh"), None],
+    );
+    assert_eq!(
+        pretty_format(&batch, None),
+        "\
++------+---------+
+| id   | name    |
++------+---------+
+| 1    | a       |
+| NULL | bcdefgh |
+| 300  | NULL    |
++------+---------+
+"
+    );
+    assert_eq!(
+        pretty_format(&batch, Some(5)),
+        "\
++------+-------+
+| id   | name  |
++------+-------+
+| 1    | a     |
+| NULL | bc... |
+| 300  | NULL  |
++------+-------+
+"
+    );
+}
+
+#[test]
+fn test_slice() {
+    let batch = batch();
+    assert_eq!(batch.num_rows(), 3);
+    let slice = batch.slice(1, 2);
+    assert_eq!(slice.num_rows(), 2);
+    assert_eq!(slice.schema(), batch.schema());
+    assert_eq!(format!("{:?}", slice.columns()[0]), "Int32[None, 3]");
+    assert_eq!(format!("{:?}", slice.columns()[1]), "Utf8Array[b, None]");
+}
+
+#[test]
+fn test_concat() {
+    let first = batch();
+    let second = batch_from([Some(4), Some(5), None], [None, Some("e"), Some("f")]);
+    let result = concat(&[first.slice(0, 2), second]).unwrap();
+    assert_eq!(result.num_rows(), 5);
+    assert_eq!(
+        format!("{:?}", result.columns()[0]),
+        "Int32[1, None, 4, 5, None]"
+    );
+    assert_eq!(
+        format!("{:?}", result.columns()[1]),
+        "Utf8Array[a, b, None, e, f]"
+    );
+
+    let other = RecordBatch::try_new(
+        Schema::from(vec![Field::new("id", DataType::Int32, true)]),
+        Chunk::new(vec![Arc::new(Int32Array::from([Some(1)])) as Arc<dyn Array>]),
+    )
+    .unwrap();
+    assert!(matches!(
+        concat(&[batch(), other]),
+        Err(Error::InvalidArgument(_))
+    ));
+    assert!(concat(&[]).is_err());
+}
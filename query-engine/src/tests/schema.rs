@@ -0,0 +1,76 @@
+use arrow2::datatypes::{DataType, Field, Schema};
+
+use crate::{error::Error, schema::SchemaExt};
+
+fn schema() -> Schema {
+    Schema::from(vec![
+        Field::new("id", DataType::Int32, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, true),
+    ])
+}
+
+#[test]
+fn test_project() {
+    let projected = schema().project(&[2, 0]).unwrap();
+    let names = projected
+        .fields
+        .iter()
+        .map(|x| x.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["score", "id"]);
+    assert!(matches!(
+        schema().project(&[0, 3]),
+        Err(Error::ExceedingBoundsError(3))
+    ));
+}
+
+#[test]
+fn test_lookup_by_name() {
+    let schema = schema();
+    assert_eq!(schema.index_of("name"), Some(1));
+    assert_eq!(schema.index_of("missing"), None);
+    assert_eq!(
+        schema.field_by_name("score").map(|x| x.data_type()),
+        Some(&DataType::Float64)
+    );
+    assert!(schema.field_by_name("missing").is_none());
+}
+
+#[test]
+fn test_merge() {
+    let other = Schema::from(vec![Field::new("rank", DataType::Int64, true)]);
+    let merged = schema().merge(&other).unwrap();
+    assert_eq!(merged.index_of("rank"), Some(3));
+    assert_eq!(merged.fields.len(), 4);
+    assert!(matches!(
+        schema().merge(&schema()),
+        Err(Error::DuplicateField(name)) if name == "id"
+    ));
+}
+
+#[test]
+fn test_union() {
+    let other = Schema::from(vec![
+        Field::new("score", DataType::Float64, false),
+        Field::new("name", DataType::Utf8, false),
+        Field::new("id", DataType::Int32, true),
+    ]);
+    let union = schema().union(&other).unwrap();
+    let fields = union
+        .fields
+        .iter()
+        .map(|x| (x.name.as_str(), x.is_nullable))
+        .collect::<Vec<_>>();
+    assert_eq!(fields, [("id", true), ("name", true), ("score", true)]);
+
+    let other = Schema::from(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("name", DataType::Utf8, true),
+        Field::new("score", DataType::Float64, true),
+    ]);
+    assert!(matches!(
+        schema().union(&other),
+        Err(Error::ConflictingFieldTypes(name, _, _)) if name == "id"
+    ));
+}
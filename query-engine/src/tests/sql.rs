@@ -0,0 +1,84 @@
+use crate::{
+    error::Error, logical_plan::format_logical_plan, query_planner::create_physical_plan,
+    sql::SqlContext,
+};
+
+fn context() -> SqlContext {
+    let mut context = SqlContext::new();
+    context.register_parquet("t", "src/tests/test.parquet");
+    context
+}
+
+#[test]
+fn test_sql_aggregate() {
+    let plan = context()
+        .create_logical_plan("SELECT bool_col, SUM(id) FROM t WHERE id > 1 GROUP BY bool_col")
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Aggregate: #bool_col, sum (#id),  \n \
+         \tSelection: #id > '1',  \n \
+         \t \tScan: src/tests/test.parquet; projection=None \n"
+    );
+    let batches = create_physical_plan(plan)
+        .unwrap()
+        .execute()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(format!("{:?}", batches[0][1]), "Int64[12, 15]");
+}
+
+#[test]
+fn test_sql_sort_and_limit() {
+    let plan = context()
+        .create_logical_plan("SELECT id, id * 2 FROM t ORDER BY id DESC LIMIT 2 OFFSET 1")
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Limit: skip=1, fetch=2 \n \
+         \tSort: #id DESC,  \n \
+         \t \tProjection: #id, #id * '2',  \n \
+         \t \t \tScan: src/tests/test.parquet; projection=None \n"
+    );
+}
+
+#[test]
+fn test_sql_sort_nulls() {
+    let plan = context()
+        .create_logical_plan(
+            "SELECT id FROM t ORDER BY id NULLS FIRST, bool_col DESC NULLS LAST, id DESC NULLS FIRST",
+        )
+        .unwrap();
+    assert_eq!(
+        format_logical_plan(&plan, 0),
+        "Sort: #id ASC NULLS FIRST, #bool_col DESC NULLS LAST, #id DESC,  \n \
+         \tProjection: #id,  \n \
+         \t \tScan: src/tests/test.parquet; projection=None \n"
+    );
+}
+
+#[test]
+fn test_sql_errors() {
+    let context = context();
+    let cases = [
+        "SELEC id FROM t",
+        "SELECT id FROM u",
+        "SELECT id FROM t JOIN t ON true",
+        "SELECT id AS x FROM t",
+        "SELECT SUM(id), bool_col FROM t GROUP BY bool_col",
+        "SELECT id FROM t WHERE id LIKE 'a'",
+        "SELECT *, id FROM t",
+        "SELECT nope FROM t",
+        "SELECT bool_col, SUM(nope) FROM t GROUP BY bool_col",
+    ];
+    let errors = cases.map(|sql| context.create_logical_plan(sql).err());
+    assert!(matches!(errors[0], Some(Error::SqlParseError(_))));
+    assert!(matches!(&errors[1], Some(Error::UnknownTable(name)) if name == "u"));
+    for error in &errors[2..errors.len() - 2] {
+        assert!(matches!(error, Some(Error::UnsupportedSql(_))));
+    }
+    for error in &errors[errors.len() - 2..] {
+        assert!(matches!(error, Some(Error::NoFieldInLogicalPlan(name)) if name == "nope"));
+    }
+}
@@ -0,0 +1,60 @@
+use arrow2::datatypes::DataType;
+
+use crate::{error::Error, operator::Operator, type_coercion::coerce_types};
+
+#[test]
+fn test_coerce_types() {
+    use DataType::*;
+    let cases = [
+        (Int32, Int32, Operator::Plus, Int32),
+        (Int32, Int64, Operator::Plus, Int64),
+        (Int64, Int32, Operator::Minus, Int64),
+        (Int32, Float32, Operator::Multiply, Float32),
+        (Int32, Float64, Operator::Divide, Float64),
+        (Int64, Float32, Operator::Plus, Float64),
+        (Float32, Float64, Operator::Plus, Float64),
+        (Null, Int32, Operator::Plus, Int32),
+        (Float64, Null, Operator::Modulo, Float64),
+        (Int64, Float64, Operator::Lt, Float64),
+        (Utf8, Utf8, Operator::Eq, Utf8),
+        (Boolean, Boolean, Operator::Neq, Boolean),
+        (Null, Utf8, Operator::GtEq, Utf8),
+        (Boolean, Boolean, Operator::And, Boolean),
+        (Null, Boolean, Operator::Or, Boolean),
+        (Int32, Int64, Operator::BitwiseAnd, Int64),
+        (Null, Int32, Operator::ShiftLeft, Int32),
+    ];
+    for (left, right, op, expected) in cases {
+        assert_eq!(
+            coerce_types(&left, &right, op).unwrap(),
+            expected,
+            "{:?} {} {:?}",
+            left,
+            op,
+            right
+        );
+    }
+
+    let unsupported = [
+        (Utf8, Int32, Operator::Eq),
+        (Utf8, Utf8, Operator::Plus),
+        (Boolean, Int32, Operator::Plus),
+        (Boolean, Boolean, Operator::Minus),
+        (Int32, Int32, Operator::And),
+        (Null, Utf8, Operator::Plus),
+        (Utf8, Boolean, Operator::Lt),
+        (Float64, Int32, Operator::BitwiseOr),
+        (Boolean, Boolean, Operator::BitwiseXor),
+    ];
+    for (left, right, op) in unsupported {
+        match coerce_types(&left, &right, op) {
+            Err(Error::IncompatibleTypes(l, r, o)) => {
+                assert_eq!(
+                    (l, r, o),
+                    (format!("{:?}", left), format!("{:?}", right), op)
+                )
+            }
+            other => panic!("{:?} {} {:?} gave {:?}", left, op, right, other),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use arrow2::datatypes::DataType;
+
+use crate::error::Error;
+use crate::operator::Operator;
+
+/// Position of a numeric type in the promotion lattice
+/// `Int32 -> Int64 -> Float32 -> Float64`.
+fn numeric_rank(data_type: &DataType) -> Option<u8> {
+    match data_type {
+        DataType::Int32 => Some(0),
+        DataType::Int64 => Some(1),
+        DataType::Float32 => Some(2),
+        DataType::Float64 => Some(3),
+        _ => None,
+    }
+}
+
+/// The smallest numeric type both `left` and `right` convert to.
+fn numeric_coercion(left: &DataType, right: &DataType) -> Option<DataType> {
+    let (l, r) = (numeric_rank(left)?, numeric_rank(right)?);
+    match (left, right) {
+        // Float32 only holds 24 bits of an integer exactly.
+        (DataType::Int64, DataType::Float32) | (DataType::Float32, DataType::Int64) => {
+            Some(DataType::Float64)
+        }
+        _ => Some(if l >= r { left.clone() } else { right.clone() }),
+    }
+}
+
+/// Returns the type both operands of `op` are cast to before evaluating it.
+///
+/// This is the single place that decides how mixed operand types combine, so
+/// that every operator promotes the same way. Equal types are kept as is, and a
+/// `Null` operand takes the other operand's type. Numeric operands follow
+/// `Int32 -> Int64 -> Float32 -> Float64`, except that Int64 and Float32 meet
+/// at Float64. Comparisons additionally accept two strings or two booleans,
+/// `And`/`Or` only booleans and bitwise operators only Int32 and Int64. Any
+/// other pair is an
+/// [`Error::IncompatibleTypes`].
+pub fn coerce_types(left: &DataType, right: &DataType, op: Operator) -> Result<DataType, Error> {
+    let coerced = match (left, right) {
+        (DataType::Null, other) | (other, DataType::Null) => Some(other.clone()),
+        _ if op.is_arithmetic() || op.is_bitwise() => numeric_coercion(left, right),
+        _ if op.is_comparison() => match (left, right) {
+            (DataType::Utf8, DataType::Utf8) | (DataType::Boolean, DataType::Boolean) => {
+                Some(left.clone())
+            }
+            _ => numeric_coercion(left, right),
+        },
+        (DataType::Boolean, DataType::Boolean) => Some(DataType::Boolean),
+        _ => None,
+    };
+    // A Null operand takes the other's type, which the operator must accept.
+    match coerced {
+        Some(data_type) if supports(op, &data_type) => Ok(data_type),
+        _ => Err(Error::IncompatibleTypes(
+            format!("{:?}", left),
+            format!("{:?}", right),
+            op,
+        )),
+    }
+}
+
+fn supports(op: Operator, data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Null => true,
+        DataType::Boolean => !op.is_arithmetic() && !op.is_bitwise(),
+        DataType::Utf8 => op.is_comparison(),
+        DataType::Int32 | DataType::Int64 if op.is_bitwise() => true,
+        _ => numeric_rank(data_type).is_some() && (op.is_arithmetic() || op.is_comparison()),
+    }
+}